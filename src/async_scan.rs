@@ -0,0 +1,92 @@
+//! Асинхронная точка входа сканирования для сетевых файловых систем.
+//!
+//! На NFS и SMB время уходит на ожидание ответов на запросы метаданных, а не
+//! на процессор, поэтому выгодно держать в полете намного больше запросов,
+//! чем ядер. [`scan_directory_async`] запускает сканирование в фоновом потоке
+//! не менее чем с [`ASYNC_MIN_JOBS`] потоками обхода и возвращает future,
+//! которое можно ожидать в любом асинхронном рантайме; [`block_on`] позволяет
+//! дождаться его без рантайма
+
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use crate::scan::{scan_directory_with_progress, ScanOptions, ScanProgress, ScanResult};
+
+/// Минимальное число одновременно обходимых директорий в асинхронном режиме
+pub const ASYNC_MIN_JOBS: usize = 64;
+
+// Результат фонового сканирования и waker задачи, которая его ждет
+struct Shared {
+    result: Option<io::Result<ScanResult>>,
+    waker: Option<Waker>,
+}
+
+/// Future результата фонового сканирования
+pub struct ScanFuture {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Future for ScanFuture {
+    type Output = io::Result<ScanResult>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Асинхронно сканирует директорию (см. описание модуля)
+pub fn scan_directory_async(dir: &Path, opts: &ScanOptions) -> impl Future<Output = io::Result<ScanResult>> {
+    scan_directory_async_with_progress(dir, opts, Arc::new(ScanProgress::default()))
+}
+
+/// То же, что `scan_directory_async`, но обновляет счетчики `progress`
+pub fn scan_directory_async_with_progress(dir: &Path, opts: &ScanOptions, progress: Arc<ScanProgress>) -> ScanFuture {
+    let shared = Arc::new(Mutex::new(Shared { result: None, waker: None }));
+    let dir = dir.to_path_buf();
+    let mut opts = opts.clone();
+    opts.jobs = opts.jobs.max(ASYNC_MIN_JOBS);
+    let thread_shared = Arc::clone(&shared);
+    thread::spawn(move || {
+        let result = scan_directory_with_progress(&dir, &opts, &progress);
+        let mut shared = thread_shared.lock().unwrap();
+        shared.result = Some(result);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    });
+    ScanFuture { shared }
+}
+
+// Будит поток, заблокированный в block_on
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Выполняет future в текущем потоке до завершения
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
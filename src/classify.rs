@@ -0,0 +1,422 @@
+//! Классификация файлов и директорий по имени
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// Расширения временных и резервных файлов
+const TEMP_EXTENSIONS: &[&str] = &["tmp", "temp", "swp", "bak", "old"];
+
+// Служебные файлы файловых менеджеров
+const TEMP_FILE_NAMES: &[&str] = &["thumbs.db", ".ds_store", "desktop.ini"];
+
+/// Проверяет, похож ли файл на временный (`*.tmp`, `*~`, `*.swp`, `Thumbs.db` и т.п.),
+/// который почти всегда можно удалить
+pub fn classify_temp_file(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name.to_lowercase(),
+        None => return false,
+    };
+
+    if name.ends_with('~') || TEMP_FILE_NAMES.contains(&name.as_str()) {
+        return true;
+    }
+
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => TEMP_EXTENSIONS.contains(&ext),
+        _ => false,
+    }
+}
+
+// Служебные файлы и директории macOS: индексы Spotlight, корзины томов,
+// локальные снимки Time Machine и артефакты архивов, созданных в Finder
+const MACOS_METADATA_NAMES: &[&str] = &[".DS_Store", "__MACOSX", ".Spotlight-V100", ".Trashes", ".MobileBackups"];
+
+/// Служебный файл или директория macOS, не содержащие пользовательских данных
+pub fn is_macos_metadata(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| MACOS_METADATA_NAMES.contains(&name))
+}
+
+/// Файл `._имя` с ресурсной вилкой и расширенными атрибутами, который macOS
+/// создает рядом с файлом на файловых системах без их поддержки (FAT, SMB)
+pub fn is_resource_fork(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| name.len() > 2 && name.starts_with("._"))
+}
+
+/// Категория файла по расширению
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileCategory {
+    Video,
+    Audio,
+    Image,
+    Document,
+    Archive,
+    Code,
+    Data,
+    Other,
+}
+
+impl FileCategory {
+    /// Название категории для отчета
+    pub fn label(self) -> &'static str {
+        match self {
+            FileCategory::Video => "Видео",
+            FileCategory::Audio => "Аудио",
+            FileCategory::Image => "Изображения",
+            FileCategory::Document => "Документы",
+            FileCategory::Archive => "Архивы",
+            FileCategory::Code => "Код",
+            FileCategory::Data => "Данные",
+            FileCategory::Other => "Прочее",
+        }
+    }
+}
+
+impl fmt::Display for FileCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+// Расширение (в нижнем регистре) -> категория
+static EXTENSION_CATEGORIES: &[(&str, FileCategory)] = &[
+    ("mp4", FileCategory::Video), ("mkv", FileCategory::Video), ("avi", FileCategory::Video),
+    ("mov", FileCategory::Video), ("wmv", FileCategory::Video), ("flv", FileCategory::Video),
+    ("webm", FileCategory::Video), ("m4v", FileCategory::Video), ("mpg", FileCategory::Video),
+    ("mpeg", FileCategory::Video), ("3gp", FileCategory::Video), ("vob", FileCategory::Video),
+
+    ("mp3", FileCategory::Audio), ("wav", FileCategory::Audio), ("flac", FileCategory::Audio),
+    ("aac", FileCategory::Audio), ("ogg", FileCategory::Audio), ("oga", FileCategory::Audio),
+    ("opus", FileCategory::Audio), ("m4a", FileCategory::Audio), ("wma", FileCategory::Audio),
+    ("aiff", FileCategory::Audio), ("mid", FileCategory::Audio), ("midi", FileCategory::Audio),
+
+    ("jpg", FileCategory::Image), ("jpeg", FileCategory::Image), ("png", FileCategory::Image),
+    ("gif", FileCategory::Image), ("bmp", FileCategory::Image), ("tif", FileCategory::Image),
+    ("tiff", FileCategory::Image), ("webp", FileCategory::Image), ("svg", FileCategory::Image),
+    ("ico", FileCategory::Image), ("heic", FileCategory::Image), ("heif", FileCategory::Image),
+    ("raw", FileCategory::Image), ("cr2", FileCategory::Image), ("nef", FileCategory::Image),
+    ("psd", FileCategory::Image), ("xcf", FileCategory::Image),
+
+    ("pdf", FileCategory::Document), ("doc", FileCategory::Document), ("docx", FileCategory::Document),
+    ("odt", FileCategory::Document), ("rtf", FileCategory::Document), ("txt", FileCategory::Document),
+    ("md", FileCategory::Document), ("xls", FileCategory::Document), ("xlsx", FileCategory::Document),
+    ("ods", FileCategory::Document), ("ppt", FileCategory::Document), ("pptx", FileCategory::Document),
+    ("odp", FileCategory::Document), ("epub", FileCategory::Document), ("djvu", FileCategory::Document),
+    ("tex", FileCategory::Document),
+
+    ("zip", FileCategory::Archive), ("tar", FileCategory::Archive), ("gz", FileCategory::Archive),
+    ("tgz", FileCategory::Archive), ("bz2", FileCategory::Archive), ("xz", FileCategory::Archive),
+    ("zst", FileCategory::Archive), ("7z", FileCategory::Archive), ("rar", FileCategory::Archive),
+    ("iso", FileCategory::Archive), ("dmg", FileCategory::Archive), ("deb", FileCategory::Archive),
+    ("rpm", FileCategory::Archive), ("jar", FileCategory::Archive), ("apk", FileCategory::Archive),
+
+    ("rs", FileCategory::Code), ("c", FileCategory::Code), ("h", FileCategory::Code),
+    ("cpp", FileCategory::Code), ("cc", FileCategory::Code), ("hpp", FileCategory::Code),
+    ("py", FileCategory::Code), ("js", FileCategory::Code), ("ts", FileCategory::Code),
+    ("jsx", FileCategory::Code), ("tsx", FileCategory::Code), ("java", FileCategory::Code),
+    ("kt", FileCategory::Code), ("go", FileCategory::Code), ("rb", FileCategory::Code),
+    ("php", FileCategory::Code), ("cs", FileCategory::Code), ("swift", FileCategory::Code),
+    ("sh", FileCategory::Code), ("lua", FileCategory::Code), ("pl", FileCategory::Code),
+    ("scala", FileCategory::Code), ("hs", FileCategory::Code), ("html", FileCategory::Code),
+    ("css", FileCategory::Code), ("scss", FileCategory::Code), ("vue", FileCategory::Code),
+
+    ("json", FileCategory::Data), ("xml", FileCategory::Data), ("csv", FileCategory::Data),
+    ("yaml", FileCategory::Data), ("yml", FileCategory::Data), ("toml", FileCategory::Data),
+    ("sql", FileCategory::Data), ("db", FileCategory::Data), ("sqlite", FileCategory::Data),
+    ("sqlite3", FileCategory::Data), ("parquet", FileCategory::Data), ("log", FileCategory::Data),
+    ("dat", FileCategory::Data),
+];
+
+/// Определяет категорию по расширению без точки; регистр не важен
+pub fn categorize_extension(ext: &str) -> FileCategory {
+    let ext = ext.to_lowercase();
+    EXTENSION_CATEGORIES.iter()
+        .find(|(known, _)| *known == ext)
+        .map_or(FileCategory::Other, |(_, category)| *category)
+}
+
+/// Суммирует размеры по категориям из статистики по расширениям
+pub fn categories_from_file_types(file_types: &BTreeMap<String, u64>) -> BTreeMap<FileCategory, u64> {
+    let mut categories = BTreeMap::new();
+    for (ext, size) in file_types {
+        *categories.entry(categorize_extension(ext)).or_insert(0) += size;
+    }
+    categories
+}
+
+/// Известная директория пакетного менеджера, которую можно безопасно очистить
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WellKnownDir {
+    NodeModules,
+    CargoRegistry,
+    PipCache,
+    GradleCaches,
+    MavenRepository,
+    Ivy2,
+    NugetPackages,
+}
+
+// Последние компоненты пути, по которым узнается директория
+static WELL_KNOWN_DIRS: &[(&[&str], WellKnownDir)] = &[
+    (&["node_modules"], WellKnownDir::NodeModules),
+    (&[".cargo", "registry"], WellKnownDir::CargoRegistry),
+    (&[".cache", "pip"], WellKnownDir::PipCache),
+    (&[".gradle", "caches"], WellKnownDir::GradleCaches),
+    (&[".m2", "repository"], WellKnownDir::MavenRepository),
+    (&[".ivy2"], WellKnownDir::Ivy2),
+    (&[".nuget", "packages"], WellKnownDir::NugetPackages),
+];
+
+impl WellKnownDir {
+    /// Название для отчета
+    pub fn label(self) -> &'static str {
+        match self {
+            WellKnownDir::NodeModules => "node_modules (npm/yarn)",
+            WellKnownDir::CargoRegistry => "Cargo registry",
+            WellKnownDir::PipCache => "pip cache",
+            WellKnownDir::GradleCaches => "Gradle caches",
+            WellKnownDir::MavenRepository => "Maven repository",
+            WellKnownDir::Ivy2 => "Ivy cache",
+            WellKnownDir::NugetPackages => "NuGet packages",
+        }
+    }
+
+    /// Рекомендуемая команда очистки
+    pub fn cleanup_command(self) -> &'static str {
+        match self {
+            WellKnownDir::NodeModules => "rm -rf node_modules (восстанавливается через npm install)",
+            WellKnownDir::CargoRegistry => "cargo cache --autoclean (cargo install cargo-cache)",
+            WellKnownDir::PipCache => "pip cache purge",
+            WellKnownDir::GradleCaches => "rm -rf ~/.gradle/caches",
+            WellKnownDir::MavenRepository => "mvn dependency:purge-local-repository",
+            WellKnownDir::Ivy2 => "rm -rf ~/.ivy2/cache",
+            WellKnownDir::NugetPackages => "dotnet nuget locals all --clear",
+        }
+    }
+}
+
+/// Узнает директорию кэша пакетного менеджера по последним компонентам пути
+/// (`node_modules`, `.cargo/registry`, `.cache/pip` и т.д.)
+pub fn classify_well_known_dir(path: &Path) -> Option<WellKnownDir> {
+    let components: Vec<&str> = path.components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect();
+    WELL_KNOWN_DIRS.iter()
+        .find(|(suffix, _)| components.ends_with(suffix))
+        .map(|(_, kind)| *kind)
+}
+
+/// Сервис облачной синхронизации, чью локальную копию содержит директория
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudService {
+    Dropbox,
+    GoogleDrive,
+    OneDrive,
+    ICloud,
+}
+
+impl CloudService {
+    /// Название для отчета
+    pub fn label(self) -> &'static str {
+        match self {
+            CloudService::Dropbox => "Dropbox",
+            CloudService::GoogleDrive => "Google Drive",
+            CloudService::OneDrive => "OneDrive",
+            CloudService::ICloud => "iCloud Drive",
+        }
+    }
+}
+
+// Служебные файлы и директории, которые клиенты синхронизации создают в корне
+// синхронизируемой директории
+static CLOUD_MARKERS: &[(&str, CloudService)] = &[
+    (".dropbox", CloudService::Dropbox),
+    (".dropbox.cache", CloudService::Dropbox),
+    (".tmp.drivedownload", CloudService::GoogleDrive),
+    (".tmp.driveupload", CloudService::GoogleDrive),
+    (".849C9593-D756-4E56-8D6E-42412F2A707B", CloudService::OneDrive),
+];
+
+// Префиксы имен поддиректорий ~/Library/CloudStorage (File Provider в macOS)
+static CLOUD_STORAGE_PREFIXES: &[(&str, CloudService)] = &[
+    ("Dropbox", CloudService::Dropbox),
+    ("GoogleDrive", CloudService::GoogleDrive),
+    ("OneDrive", CloudService::OneDrive),
+];
+
+/// Узнает корень синхронизируемой с облаком директории по служебным файлам
+/// клиента (`.dropbox`, `.tmp.drivedownload`...) или по расположению
+/// (см. [`classify_cloud_path`]). Проверяет файловую систему
+pub fn detect_cloud_sync(path: &Path) -> Option<CloudService> {
+    CLOUD_MARKERS.iter()
+        .find(|(marker, _)| fs::symlink_metadata(path.join(marker)).is_ok())
+        .map(|(_, service)| *service)
+        .or_else(|| classify_cloud_path(path))
+}
+
+/// Узнает корень облачной директории только по пути: iCloud Drive
+/// (`Library/Mobile Documents/com~apple~CloudDocs`) и поддиректории
+/// `Library/CloudStorage`
+pub fn classify_cloud_path(path: &Path) -> Option<CloudService> {
+    let components: Vec<&str> = path.components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect();
+    if components.ends_with(&["Library", "Mobile Documents", "com~apple~CloudDocs"]) {
+        return Some(CloudService::ICloud);
+    }
+    match components.as_slice() {
+        [.., "Library", "CloudStorage", name] => CLOUD_STORAGE_PREFIXES.iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, service)| *service),
+        _ => None,
+    }
+}
+
+// Тип файла, который узнается по сигнатуре: части (смещение, байты)
+// и расширения, которые ему соответствуют
+struct MagicType {
+    mime: &'static str,
+    parts: &'static [(usize, &'static [u8])],
+    extensions: &'static [&'static str],
+}
+
+const MAGIC_TYPES: &[MagicType] = &[
+    MagicType { mime: "image/png", parts: &[(0, b"\x89PNG\r\n\x1a\n")], extensions: &["png"] },
+    MagicType { mime: "image/jpeg", parts: &[(0, b"\xff\xd8\xff")], extensions: &["jpg", "jpeg", "jpe", "jfif"] },
+    MagicType { mime: "image/gif", parts: &[(0, b"GIF8")], extensions: &["gif"] },
+    MagicType { mime: "image/webp", parts: &[(0, b"RIFF"), (8, b"WEBP")], extensions: &["webp"] },
+    MagicType { mime: "image/tiff", parts: &[(0, b"II*\0")], extensions: &["tif", "tiff", "dng", "nef", "cr2"] },
+    MagicType { mime: "image/tiff", parts: &[(0, b"MM\0*")], extensions: &["tif", "tiff", "dng", "nef", "cr2"] },
+    MagicType { mime: "video/mp4", parts: &[(4, b"ftyp")], extensions: &["mp4", "m4v", "m4a", "mov", "3gp", "heic", "avif"] },
+    MagicType { mime: "video/x-matroska", parts: &[(0, b"\x1a\x45\xdf\xa3")], extensions: &["mkv", "webm", "mka"] },
+    MagicType { mime: "video/x-msvideo", parts: &[(0, b"RIFF"), (8, b"AVI ")], extensions: &["avi"] },
+    MagicType { mime: "audio/wav", parts: &[(0, b"RIFF"), (8, b"WAVE")], extensions: &["wav"] },
+    MagicType { mime: "audio/mpeg", parts: &[(0, b"ID3")], extensions: &["mp3"] },
+    MagicType { mime: "audio/flac", parts: &[(0, b"fLaC")], extensions: &["flac"] },
+    MagicType { mime: "audio/ogg", parts: &[(0, b"OggS")], extensions: &["ogg", "oga", "ogv", "opus"] },
+    MagicType { mime: "application/pdf", parts: &[(0, b"%PDF")], extensions: &["pdf"] },
+    MagicType {
+        mime: "application/zip",
+        parts: &[(0, b"PK\x03\x04")],
+        extensions: &["zip", "jar", "war", "apk", "aar", "docx", "xlsx", "pptx", "odt", "ods", "odp", "epub", "whl", "xpi", "ipa", "nupkg", "vsix"],
+    },
+    MagicType { mime: "application/gzip", parts: &[(0, b"\x1f\x8b")], extensions: &["gz", "tgz"] },
+    MagicType { mime: "application/x-bzip2", parts: &[(0, b"BZh")], extensions: &["bz2", "tbz2"] },
+    MagicType { mime: "application/x-xz", parts: &[(0, b"\xfd7zXZ\0")], extensions: &["xz", "txz"] },
+    MagicType { mime: "application/zstd", parts: &[(0, b"\x28\xb5\x2f\xfd")], extensions: &["zst"] },
+    MagicType { mime: "application/x-7z-compressed", parts: &[(0, b"7z\xbc\xaf\x27\x1c")], extensions: &["7z"] },
+    MagicType { mime: "application/vnd.rar", parts: &[(0, b"Rar!\x1a\x07")], extensions: &["rar"] },
+    MagicType { mime: "application/x-executable", parts: &[(0, b"\x7fELF")], extensions: &["so", "o", "ko", "elf"] },
+    MagicType { mime: "application/x-msdownload", parts: &[(0, b"MZ")], extensions: &["exe", "dll", "sys", "efi", "scr"] },
+    MagicType { mime: "application/x-sqlite3", parts: &[(0, b"SQLite format 3\0")], extensions: &["sqlite", "sqlite3", "db"] },
+    MagicType { mime: "application/wasm", parts: &[(0, b"\0asm")], extensions: &["wasm"] },
+];
+
+/// Сколько байт из начала файла нужно `detect_mime`
+pub const MIME_HEADER_LEN: usize = 16;
+
+/// Определяет MIME-тип по сигнатуре в начале файла (первые `MIME_HEADER_LEN` байт)
+pub fn detect_mime(header: &[u8]) -> Option<&'static str> {
+    MAGIC_TYPES.iter()
+        .find(|kind| kind.parts.iter().all(|(offset, magic)| header.get(*offset..).is_some_and(|rest| rest.starts_with(magic))))
+        .map(|kind| kind.mime)
+}
+
+/// Группа MIME-типа со слешем: `image/` для `image/png`
+pub fn mime_group(mime: &str) -> &str {
+    match mime.find('/') {
+        Some(slash) => &mime[..=slash],
+        None => mime,
+    }
+}
+
+/// Противоречит ли расширение содержимому: расширение известно, но относится
+/// к другому типу (например, `.jpg`, который на деле ZIP-архив). Неизвестные
+/// расширения и файлы без расширения противоречием не считаются
+pub fn extension_mismatch(ext: &str, mime: &str) -> bool {
+    let ext = ext.to_lowercase();
+    let known = MAGIC_TYPES.iter().any(|kind| kind.extensions.contains(&ext.as_str()));
+    known && !MAGIC_TYPES.iter().any(|kind| kind.mime == mime && kind.extensions.contains(&ext.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categorizes_extensions() {
+        assert_eq!(categorize_extension("mp4"), FileCategory::Video);
+        assert_eq!(categorize_extension("FLAC"), FileCategory::Audio);
+        assert_eq!(categorize_extension("rs"), FileCategory::Code);
+        assert_eq!(categorize_extension("tar"), FileCategory::Archive);
+        assert_eq!(categorize_extension(""), FileCategory::Other);
+        assert_eq!(categorize_extension("unknown"), FileCategory::Other);
+    }
+
+    #[test]
+    fn detects_well_known_dirs() {
+        assert_eq!(classify_well_known_dir(Path::new("/src/app/node_modules")), Some(WellKnownDir::NodeModules));
+        assert_eq!(classify_well_known_dir(Path::new("/home/u/.cargo/registry")), Some(WellKnownDir::CargoRegistry));
+        assert_eq!(classify_well_known_dir(Path::new("/home/u/.cache/pip")), Some(WellKnownDir::PipCache));
+        assert_eq!(classify_well_known_dir(Path::new("/home/u/.cargo")), None);
+        assert_eq!(classify_well_known_dir(Path::new("/srv/registry")), None);
+    }
+
+    #[test]
+    fn classifies_cloud_paths() {
+        let icloud = Path::new("/Users/u/Library/Mobile Documents/com~apple~CloudDocs");
+        assert_eq!(classify_cloud_path(icloud), Some(CloudService::ICloud));
+        let drive = Path::new("/Users/u/Library/CloudStorage/GoogleDrive-u@example.com");
+        assert_eq!(classify_cloud_path(drive), Some(CloudService::GoogleDrive));
+        assert_eq!(classify_cloud_path(Path::new("/Users/u/Library/CloudStorage/OneDrive-Personal")), Some(CloudService::OneDrive));
+        assert_eq!(classify_cloud_path(Path::new("/Users/u/Library/CloudStorage")), None);
+        assert_eq!(classify_cloud_path(Path::new("/home/u/Dropbox")), None);
+    }
+
+    #[test]
+    fn detects_temp_files() {
+        assert!(classify_temp_file(Path::new("/a/report.tmp")));
+        assert!(classify_temp_file(Path::new("notes.txt~")));
+        assert!(classify_temp_file(Path::new("Thumbs.db")));
+        assert!(!classify_temp_file(Path::new(".tmp")));
+        assert!(!classify_temp_file(Path::new("template.txt")));
+    }
+
+    #[test]
+    fn detects_macos_files() {
+        assert!(is_macos_metadata(Path::new("/Volumes/usb/.Spotlight-V100")));
+        assert!(is_macos_metadata(Path::new("photos/__MACOSX")));
+        assert!(!is_macos_metadata(Path::new("/Users/u/.DS_Store.bak")));
+        assert!(is_resource_fork(Path::new("/Volumes/usb/._IMG_0001.JPG")));
+        assert!(!is_resource_fork(Path::new("._")));
+        assert!(!is_resource_fork(Path::new(".profile")));
+    }
+
+    #[test]
+    fn detects_mime_by_signature() {
+        assert_eq!(detect_mime(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(detect_mime(b"PK\x03\x04\x14\0"), Some("application/zip"));
+        assert_eq!(detect_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(detect_mime(b"RIFF\0\0\0\0WAVEfmt "), Some("audio/wav"));
+        assert_eq!(detect_mime(b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(detect_mime(b"RIFF"), None);
+        assert_eq!(detect_mime(b"fn main() {}"), None);
+        assert_eq!(mime_group("image/png"), "image/");
+    }
+
+    #[test]
+    fn detects_extension_mismatch() {
+        assert!(extension_mismatch("jpg", "application/zip"));
+        assert!(!extension_mismatch("JPEG", "image/jpeg"));
+        assert!(!extension_mismatch("docx", "application/zip"));
+        assert!(!extension_mismatch("tiff", "image/tiff"));
+        assert!(!extension_mismatch("", "application/x-executable"));
+        assert!(!extension_mismatch("txt", "application/pdf"));
+    }
+}
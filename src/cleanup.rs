@@ -0,0 +1,155 @@
+//! Кандидаты на удаление для `diskspace interactive-clean` и журнал удалений
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::duplicates::DuplicateGroup;
+use crate::format::format_timestamp;
+use crate::report::collect_well_known_dirs;
+use crate::scan::ScanResult;
+
+/// Категория кандидатов на удаление
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CleanupCategory {
+    TempFiles,
+    Duplicates,
+    PackageCaches,
+    EmptyDirs,
+}
+
+impl CleanupCategory {
+    /// Название для отчета
+    pub fn label(self) -> &'static str {
+        match self {
+            CleanupCategory::TempFiles => "Временные файлы",
+            CleanupCategory::Duplicates => "Лишние копии дубликатов",
+            CleanupCategory::PackageCaches => "Кэши пакетных менеджеров",
+            CleanupCategory::EmptyDirs => "Пустые директории",
+        }
+    }
+
+    /// Ключ категории в журнале удалений
+    pub fn key(self) -> &'static str {
+        match self {
+            CleanupCategory::TempFiles => "temp",
+            CleanupCategory::Duplicates => "duplicate",
+            CleanupCategory::PackageCaches => "cache",
+            CleanupCategory::EmptyDirs => "empty-dir",
+        }
+    }
+}
+
+/// Кандидаты одной категории: путь и освобождаемое место
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanupGroup {
+    pub category: CleanupCategory,
+    pub items: Vec<(PathBuf, u64)>,
+}
+
+impl CleanupGroup {
+    /// Сколько места освободит удаление всех кандидатов
+    pub fn total(&self) -> u64 {
+        self.items.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Непустые категории кандидатов в порядке [`CleanupCategory`]. Из каждой
+/// группы дубликатов остается первая копия, кэши вложенных друг в друга
+/// директорий учитываются один раз
+pub fn collect_candidates(result: &ScanResult, duplicates: &[DuplicateGroup]) -> Vec<CleanupGroup> {
+    let copies = duplicates.iter()
+        .flat_map(|group| group.paths.iter().skip(1).map(|path| (path.clone(), group.size)))
+        .collect();
+    let dirs: Vec<_> = result.dir_infos.iter().map(|(path, info)| (path.clone(), info.clone())).collect();
+    let caches = collect_well_known_dirs(&dirs).into_iter()
+        .map(|(path, _, size)| (PathBuf::from(path), size))
+        .collect();
+    let empty = result.empty_dirs.iter().map(|path| (path.clone(), 0)).collect();
+
+    [
+        (CleanupCategory::TempFiles, result.temp_files.clone()),
+        (CleanupCategory::Duplicates, copies),
+        (CleanupCategory::PackageCaches, caches),
+        (CleanupCategory::EmptyDirs, empty),
+    ]
+    .into_iter()
+    .filter(|(_, items)| !items.is_empty())
+    .map(|(category, items)| CleanupGroup { category, items })
+    .collect()
+}
+
+/// Удаляет кандидата: файл, пустую директорию или директорию кэша целиком
+pub fn remove_candidate(category: CleanupCategory, path: &Path) -> io::Result<()> {
+    match category {
+        CleanupCategory::TempFiles | CleanupCategory::Duplicates => fs::remove_file(path),
+        // remove_dir удаляет только пустые директории, поэтому появившиеся
+        // после сканирования файлы не пострадают
+        CleanupCategory::EmptyDirs => fs::remove_dir(path),
+        CleanupCategory::PackageCaches => fs::remove_dir_all(path),
+    }
+}
+
+/// Журнал удалений: `$XDG_DATA_HOME/diskspace/cleanup.log`
+/// (по умолчанию `~/.local/share/diskspace/cleanup.log`)
+pub fn default_log_path() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+            Some(PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(data_home.join("diskspace").join("cleanup.log"))
+}
+
+/// Строка журнала: время (UTC), категория, размер в байтах и путь через табуляцию
+pub fn log_entry(time: SystemTime, category: CleanupCategory, path: &Path, size: u64) -> String {
+    format!("{}\t{}\t{}\t{}\n", format_timestamp(time), category.key(), size, path.display())
+}
+
+/// Дописывает запись об удалении в журнал, создавая его при необходимости
+pub fn append_log(log: &Path, category: CleanupCategory, path: &Path, size: u64) -> io::Result<()> {
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(log)?;
+    file.write_all(log_entry(SystemTime::now(), category, path, size).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::from_unix_seconds;
+    use crate::scan::DirInfo;
+
+    #[test]
+    fn collects_categories() {
+        let mut result = ScanResult {
+            temp_files: vec![("/p/a.tmp".into(), 10)],
+            empty_dirs: vec!["/p/empty".into()],
+            ..ScanResult::default()
+        };
+        for path in ["/p/node_modules", "/p/node_modules/x/node_modules"] {
+            result.dir_infos.insert(path.to_string(), DirInfo { size: 100, ..DirInfo::default() });
+        }
+        let duplicates = [DuplicateGroup { hash: String::new(), size: 5, paths: vec!["/p/1".into(), "/p/2".into(), "/p/3".into()] }];
+
+        let groups = collect_candidates(&result, &duplicates);
+        let categories: Vec<_> = groups.iter().map(|group| group.category).collect();
+        assert_eq!(categories, [CleanupCategory::TempFiles, CleanupCategory::Duplicates, CleanupCategory::PackageCaches, CleanupCategory::EmptyDirs]);
+        assert_eq!(groups[1].items, vec![(PathBuf::from("/p/2"), 5), ("/p/3".into(), 5)]);
+        assert_eq!(groups[1].total(), 10);
+        assert_eq!(groups[2].items, vec![(PathBuf::from("/p/node_modules"), 100)]);
+
+        assert!(collect_candidates(&ScanResult::default(), &[]).is_empty());
+    }
+
+    #[test]
+    fn formats_log_entry() {
+        let entry = log_entry(from_unix_seconds(86400), CleanupCategory::TempFiles, Path::new("/p/a.tmp"), 10);
+        assert_eq!(entry, "1970-01-02 00:00:00\ttemp\t10\t/p/a.tmp\n");
+    }
+}
@@ -0,0 +1,81 @@
+//! ANSI-цвета для терминального вывода.
+//!
+//! Цвет включается один раз при запуске через [`set_enabled`]; пока он выключен,
+//! функции оформления возвращают текст без escape-последовательностей.
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Цвет текста
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Cyan,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Blue => "34",
+            Color::Cyan => "36",
+        }
+    }
+}
+
+/// Определяет, нужен ли цвет: stdout - терминал, нет `--no-color` и переменной `NO_COLOR`
+pub fn should_colorize(no_color_flag: bool) -> bool {
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color_flag && !no_color_env && io::stdout().is_terminal()
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Окрашивает текст. Выравнивание нужно делать до вызова,
+/// иначе escape-последовательности учтутся в ширине колонки
+pub fn paint(text: &str, color: Color) -> String {
+    if enabled() {
+        format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Выделяет текст жирным (заголовки колонок)
+pub fn bold(text: &str) -> String {
+    if enabled() {
+        format!("\x1b[1m{}\x1b[0m", text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_respects_switch() {
+        set_enabled(false);
+        assert_eq!(paint("x", Color::Red), "x");
+        assert_eq!(bold("x"), "x");
+        set_enabled(true);
+        assert_eq!(paint("x", Color::Red), "\x1b[31mx\x1b[0m");
+        assert_eq!(bold("x"), "\x1b[1mx\x1b[0m");
+        set_enabled(false);
+    }
+}
@@ -0,0 +1,224 @@
+//! Скрипты автодополнения командной строки для bash, zsh, fish и PowerShell.
+//!
+//! Список опций берется из текста справки, поэтому автодополнение не
+//! расходится с `--help`
+
+use std::fmt::Write as _;
+
+/// Оболочка, для которой строится скрипт
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl Shell {
+    /// Имена оболочек для `diskspace completions ОБОЛОЧКА`
+    pub const NAMES: &'static str = "bash, zsh, fish, powershell";
+
+    pub fn parse(s: &str) -> Option<Shell> {
+        match s {
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            "powershell" | "pwsh" => Some(Shell::PowerShell),
+            _ => None,
+        }
+    }
+}
+
+/// Опция командной строки
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flag {
+    /// Короткая форма без дефиса: `j` для `-j`
+    pub short: Option<char>,
+    /// Длинная форма без дефисов: `jobs` для `--jobs`
+    pub long: Option<String>,
+    /// Имя значения (`ЧИСЛО`), если опция его принимает
+    pub value: Option<String>,
+    /// Первая строка описания
+    pub description: String,
+}
+
+/// Разбирает опции из справки. Строка опции начинается с отступа не больше
+/// шести пробелов и дефиса: `  -j, --jobs ЧИСЛО    Описание`. Если описание
+/// не поместилось в строку опции, берется следующая строка
+pub fn parse_help(help: &str) -> Vec<Flag> {
+    let lines: Vec<&str> = help.lines().collect();
+    let mut flags = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if !trimmed.starts_with('-') || line.len() - trimmed.len() > 6 {
+            continue;
+        }
+        let mut flag = Flag { short: None, long: None, value: None, description: String::new() };
+        let mut words = trimmed.split_whitespace().peekable();
+        while let Some(word) = words.next_if(|word| word.starts_with('-')) {
+            let word = word.trim_end_matches(',');
+            match word.strip_prefix("--") {
+                Some(long) => flag.long = Some(long.to_string()),
+                None => flag.short = word.strip_prefix('-').and_then(|short| short.chars().next()),
+            }
+        }
+        flag.value = words.next_if(|word| is_value_name(word)).map(str::to_string);
+        let rest: Vec<&str> = words.collect();
+        flag.description = if rest.is_empty() {
+            lines.get(i + 1).map_or("", |next| next.trim()).to_string()
+        } else {
+            rest.join(" ")
+        };
+        flags.push(flag);
+    }
+    flags
+}
+
+// Имена значений в справке пишутся заглавными буквами: ЧИСЛО, ФАЙЛ
+fn is_value_name(word: &str) -> bool {
+    word.chars().any(char::is_alphabetic) && !word.chars().any(char::is_lowercase)
+}
+
+/// Скрипт автодополнения для program с подкомандами subcommands
+pub fn generate(shell: Shell, program: &str, subcommands: &[&str], flags: &[Flag]) -> String {
+    match shell {
+        Shell::Bash => bash(program, subcommands, flags),
+        Shell::Zsh => zsh(program, subcommands, flags),
+        Shell::Fish => fish(program, subcommands, flags),
+        Shell::PowerShell => powershell(program, subcommands, flags),
+    }
+}
+
+// Все формы опций: -j, --jobs, ...
+fn flag_names(flags: &[Flag], with_value: bool) -> Vec<String> {
+    let mut names = Vec::new();
+    for flag in flags.iter().filter(|flag| !with_value || flag.value.is_some()) {
+        names.extend(flag.short.map(|short| format!("-{}", short)));
+        names.extend(flag.long.as_ref().map(|long| format!("--{}", long)));
+    }
+    names
+}
+
+fn bash(program: &str, subcommands: &[&str], flags: &[Flag]) -> String {
+    let function = format!("_{}", program.replace('-', "_"));
+    let mut out = String::new();
+    let _ = writeln!(out, "{}() {{", function);
+    out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    let _ = writeln!(out, "    case \"$prev\" in");
+    let _ = writeln!(out, "        {})", flag_names(flags, true).join("|"));
+    out.push_str("            COMPREPLY=($(compgen -f -- \"$cur\"))\n");
+    out.push_str("            return;;\n");
+    out.push_str("    esac\n");
+    out.push_str("    if [[ $cur == -* ]]; then\n");
+    let _ = writeln!(out, "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", flag_names(flags, false).join(" "));
+    out.push_str("    elif [[ $COMP_CWORD -eq 1 ]]; then\n");
+    let _ = writeln!(out, "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\") $(compgen -d -- \"$cur\"))", subcommands.join(" "));
+    out.push_str("    else\n");
+    out.push_str("        COMPREPLY=($(compgen -d -- \"$cur\"))\n");
+    out.push_str("    fi\n");
+    out.push_str("}\n");
+    let _ = writeln!(out, "complete -o filenames -F {} {}", function, program);
+    out
+}
+
+fn zsh(program: &str, subcommands: &[&str], flags: &[Flag]) -> String {
+    // Описание стоит в [...] внутри строки в одинарных кавычках
+    let escape = |s: &str| s.replace('\'', "'\\''").replace('[', "\\[").replace(']', "\\]").replace(':', "\\:");
+    let mut out = String::new();
+    let _ = writeln!(out, "#compdef {}", program);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "_arguments -s \\");
+    for flag in flags {
+        let value = flag.value.as_ref().map(|value| format!(":{}:_files", escape(value))).unwrap_or_default();
+        let names: Vec<String> = flag.short.map(|short| format!("-{}", short)).into_iter()
+            .chain(flag.long.as_ref().map(|long| format!("--{}", long)))
+            .collect();
+        for name in names {
+            let _ = writeln!(out, "  '{}[{}]{}' \\", name, escape(&flag.description), value);
+        }
+    }
+    let _ = writeln!(out, "  '1:команда или путь:(({}))' \\", subcommands.join(" "));
+    let _ = writeln!(out, "  '*:путь:_files -/'");
+    out
+}
+
+fn fish(program: &str, subcommands: &[&str], flags: &[Flag]) -> String {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('\'', "\\'");
+    let mut out = String::new();
+    let _ = writeln!(out, "complete -c {} -n '__fish_use_subcommand' -a '{}'", program, subcommands.join(" "));
+    for flag in flags {
+        let mut line = format!("complete -c {}", program);
+        if let Some(short) = flag.short {
+            let _ = write!(line, " -s {}", short);
+        }
+        if let Some(long) = &flag.long {
+            let _ = write!(line, " -l {}", long);
+        }
+        if flag.value.is_some() {
+            line.push_str(" -r");
+        }
+        let _ = writeln!(out, "{} -d '{}'", line, escape(&flag.description));
+    }
+    out
+}
+
+fn powershell(program: &str, subcommands: &[&str], flags: &[Flag]) -> String {
+    let escape = |s: &str| s.replace('\'', "''");
+    let mut out = String::new();
+    let _ = writeln!(out, "Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{", program);
+    out.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n");
+    out.push_str("    $completions = @(\n");
+    for subcommand in subcommands {
+        let _ = writeln!(out, "        @('{}', 'ParameterValue', '{}')", subcommand, subcommand);
+    }
+    for flag in flags {
+        let description = if flag.description.is_empty() { "-" } else { flag.description.as_str() };
+        for name in flag_names(std::slice::from_ref(flag), false) {
+            let _ = writeln!(out, "        @('{}', 'ParameterName', '{}')", name, escape(description));
+        }
+    }
+    out.push_str("    )\n");
+    out.push_str("    $completions | Where-Object { $_[0] -like \"$wordToComplete*\" } | ForEach-Object {\n");
+    out.push_str("        [System.Management.Automation.CompletionResult]::new($_[0], $_[0], $_[1], $_[2])\n");
+    out.push_str("    }\n");
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELP: &str = "Использование: diskspace [ОПЦИИ]\n\
+        \n\
+        Опции:\n\
+        \x20 -j, --jobs ЧИСЛО    Число потоков\n\
+        \x20     --min-size РАЗМЕР\n\
+        \x20                     Учитывать только файлы не меньше РАЗМЕРА\n\
+        \x20     --json          Вывести JSON\n\
+        \x20                     (продолжение с --csv)\n";
+
+    #[test]
+    fn parses_flags_from_help() {
+        let flags = parse_help(HELP);
+        assert_eq!(flags, vec![
+            Flag { short: Some('j'), long: Some("jobs".to_string()), value: Some("ЧИСЛО".to_string()), description: "Число потоков".to_string() },
+            Flag { short: None, long: Some("min-size".to_string()), value: Some("РАЗМЕР".to_string()), description: "Учитывать только файлы не меньше РАЗМЕРА".to_string() },
+            Flag { short: None, long: Some("json".to_string()), value: None, description: "Вывести JSON".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn scripts_list_all_flags() {
+        let flags = parse_help(HELP);
+        let bash = generate(Shell::Bash, "diskspace", &["scan"], &flags);
+        assert!(bash.contains("compgen -W \"-j --jobs --min-size --json\""));
+        assert!(bash.contains("-j|--jobs|--min-size)"));
+        let fish = generate(Shell::Fish, "diskspace", &["scan"], &flags);
+        assert!(fish.contains("complete -c diskspace -s j -l jobs -r -d 'Число потоков'"));
+        let zsh = generate(Shell::Zsh, "diskspace", &["scan"], &flags);
+        assert!(zsh.contains("'--min-size[Учитывать только файлы не меньше РАЗМЕРА]:РАЗМЕР:_files' \\"));
+        assert_eq!(Shell::parse("pwsh"), Some(Shell::PowerShell));
+        assert_eq!(Shell::parse("tcsh"), None);
+    }
+}
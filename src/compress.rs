@@ -0,0 +1,63 @@
+//! Оценка степени сжатия файлов по энтропии их начала (для --compress-estimate)
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Сколько байт в начале файла используется для оценки
+pub const SAMPLE_LEN: usize = 64 * 1024;
+
+/// Доля размера, которая останется после сжатия: энтропия Шеннона байтов
+/// sample, деленная на 8 бит. Это нижняя граница для сжатия без учета
+/// повторов, поэтому gzip на тексте с повторяющимися строками сожмет лучше,
+/// а на уже сжатых данных - чуть хуже из-за служебных данных
+pub fn compression_ratio(sample: &[u8]) -> f64 {
+    if sample.is_empty() {
+        return 1.0;
+    }
+    let mut counts = [0usize; 256];
+    for &byte in sample {
+        counts[byte as usize] += 1;
+    }
+    let len = sample.len() as f64;
+    let entropy: f64 = counts.iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    (entropy / 8.0).clamp(0.0, 1.0)
+}
+
+/// Оценка размера файла после сжатия по его первым `SAMPLE_LEN` байтам
+pub fn estimate_file(path: &Path, size: u64) -> io::Result<u64> {
+    let mut sample = Vec::with_capacity(SAMPLE_LEN.min(size as usize));
+    fs::File::open(path)?.take(SAMPLE_LEN as u64).read_to_end(&mut sample)?;
+    Ok((size as f64 * compression_ratio(&sample)).round() as u64)
+}
+
+/// Сэкономленная доля в процентах для исходного и сжатого размера
+pub fn savings_percent(original: u64, compressed: u64) -> f64 {
+    if original == 0 {
+        return 0.0;
+    }
+    original.saturating_sub(compressed) as f64 * 100.0 / original as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_follows_entropy() {
+        assert_eq!(compression_ratio(&[]), 1.0);
+        assert_eq!(compression_ratio(&[b'a'; 1000]), 0.0);
+        // Два равновероятных значения - 1 бит на байт
+        assert_eq!(compression_ratio(&[0, 1].repeat(500)), 0.125);
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(compression_ratio(&all_bytes), 1.0);
+        assert_eq!(savings_percent(200, 50), 75.0);
+        assert_eq!(savings_percent(0, 0), 0.0);
+    }
+}
@@ -0,0 +1,484 @@
+//! Файл конфигурации `~/.config/diskspace/config.toml`.
+//!
+//! Поддерживается подмножество TOML, достаточное для настроек программы:
+//! пары `ключ = значение`, строки, целые числа, логические значения,
+//! массивы (в том числе многострочные) и комментарии `#`.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::format::parse_size_str;
+use crate::report::SortKey;
+
+/// Пример конфигурации, записываемый командой `diskspace config --init`
+pub const EXAMPLE_CONFIG: &str = r#"# Настройки diskspace по умолчанию.
+# Опции командной строки имеют приоритет над значениями из этого файла.
+
+# Шаблоны исключаемых путей (добавляются к --exclude)
+# exclude = ["**/node_modules", ".git", "*.tmp"]
+
+# Количество строк в таблицах (0 - без ограничения)
+# top_dirs = 15
+# top_files = 5
+# top_extensions = 8
+
+# Сортировка директорий: size, name, count, largest-file, percent
+# sort = "size"
+# reverse = false
+
+# Число потоков и максимальная глубина сканирования
+# jobs = 4
+# max_depth = 3
+
+# Фильтр по размеру файлов (строка с суффиксом или число байт)
+# min_size = "1M"
+# max_size = "2G"
+
+# follow_symlinks = false
+
+# Поиск дубликатов
+# duplicates = false
+# dup_min_size = "1M"
+"#;
+
+/// Ключи, которые понимает программа, в порядке `EXAMPLE_CONFIG`
+pub const KEYS: &[&str] = &[
+    "exclude", "top_dirs", "top_files", "top_extensions", "sort", "reverse", "jobs", "max_depth", "min_size", "max_size",
+    "follow_symlinks", "duplicates", "dup_min_size",
+];
+
+/// Ошибка чтения или разбора файла конфигурации
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    /// Синтаксическая или смысловая ошибка в строке с указанным номером
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "не удалось прочитать {}: {}", path.display(), err),
+            ConfigError::Parse { line, message } => write!(f, "строка {}: {}", line, message),
+        }
+    }
+}
+
+/// Значения по умолчанию из файла конфигурации; `None` - ключ не задан
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub exclude: Vec<String>,
+    pub top_dirs: Option<usize>,
+    pub top_files: Option<usize>,
+    pub top_extensions: Option<usize>,
+    pub sort: Option<SortKey>,
+    pub reverse: Option<bool>,
+    pub jobs: Option<usize>,
+    pub max_depth: Option<u32>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub follow_symlinks: Option<bool>,
+    pub duplicates: Option<bool>,
+    pub dup_min_size: Option<u64>,
+    /// Ключи, которые программа не знает; выводятся как предупреждения
+    pub unknown_keys: Vec<String>,
+    /// Ключи в устаревшем написании через дефис, как у опций командной строки
+    /// (`top-dirs`), и их имя в текущем написании; значения применяются
+    pub deprecated_keys: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "строка",
+            Value::Integer(_) => "число",
+            Value::Boolean(_) => "логическое значение",
+            Value::Array(_) => "массив",
+        }
+    }
+}
+
+/// Путь к файлу конфигурации по умолчанию.
+///
+/// Учитывает `XDG_CONFIG_HOME`, иначе используется `~/.config`.
+pub fn default_config_path() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("diskspace").join("config.toml"));
+    }
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".config").join("diskspace").join("config.toml"))
+}
+
+impl Config {
+    /// Читает и разбирает файл конфигурации
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        Config::parse(&text)
+    }
+
+    /// Разбирает текст конфигурации
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (line, key, value) in parse_toml(text)? {
+            config.set(&key, value).map_err(|message| ConfigError::Parse { line, message })?;
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        match key {
+            "exclude" => self.exclude = expect_strings(key, value)?,
+            "top_dirs" => self.top_dirs = Some(expect_count(key, value)?),
+            "top_files" => self.top_files = Some(expect_count(key, value)?),
+            "top_extensions" => self.top_extensions = Some(expect_count(key, value)?),
+            "sort" => {
+                let name = expect_string(key, value)?;
+                self.sort = Some(SortKey::parse(&name)
+                    .ok_or_else(|| format!("неизвестный ключ сортировки: {}", name))?);
+            },
+            "reverse" => self.reverse = Some(expect_bool(key, value)?),
+            "jobs" => {
+                self.jobs = match expect_count(key, value)? {
+                    0 => return Err("jobs должно быть больше нуля".to_string()),
+                    n => Some(n),
+                };
+            },
+            "max_depth" => {
+                let depth = expect_count(key, value)?;
+                self.max_depth = Some(u32::try_from(depth).map_err(|_| format!("слишком большое значение {}", key))?);
+            },
+            "min_size" => self.min_size = Some(expect_size(key, value)?),
+            "max_size" => self.max_size = Some(expect_size(key, value)?),
+            "follow_symlinks" => self.follow_symlinks = Some(expect_bool(key, value)?),
+            "duplicates" => self.duplicates = Some(expect_bool(key, value)?),
+            "dup_min_size" => self.dup_min_size = Some(expect_size(key, value)?),
+            _ => {
+                let canonical = key.replace('-', "_");
+                if canonical != key && KEYS.contains(&canonical.as_str()) {
+                    self.deprecated_keys.push((key.to_string(), canonical.clone()));
+                    return self.set(&canonical, value);
+                }
+                self.unknown_keys.push(key.to_string());
+            },
+        }
+        Ok(())
+    }
+}
+
+fn type_error(key: &str, expected: &str, value: &Value) -> String {
+    format!("{}: ожидается {}, получено: {}", key, expected, value.type_name())
+}
+
+fn expect_string(key: &str, value: Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(type_error(key, "строка", &other)),
+    }
+}
+
+fn expect_bool(key: &str, value: Value) -> Result<bool, String> {
+    match value {
+        Value::Boolean(b) => Ok(b),
+        other => Err(type_error(key, "true или false", &other)),
+    }
+}
+
+fn expect_count(key: &str, value: Value) -> Result<usize, String> {
+    match value {
+        Value::Integer(n) => usize::try_from(n).map_err(|_| format!("{}: отрицательное значение {}", key, n)),
+        other => Err(type_error(key, "число", &other)),
+    }
+}
+
+// Размер задается строкой с суффиксом ("100M") или числом байт
+fn expect_size(key: &str, value: Value) -> Result<u64, String> {
+    match value {
+        Value::String(s) => parse_size_str(&s).map_err(|e| format!("{}: {}", key, e)),
+        Value::Integer(n) => u64::try_from(n).map_err(|_| format!("{}: отрицательное значение {}", key, n)),
+        other => Err(type_error(key, "размер", &other)),
+    }
+}
+
+fn expect_strings(key: &str, value: Value) -> Result<Vec<String>, String> {
+    match value {
+        Value::Array(items) => items.into_iter().map(|item| expect_string(key, item)).collect(),
+        // Одиночная строка допускается как массив из одного элемента
+        Value::String(s) => Ok(vec![s]),
+        other => Err(type_error(key, "массив строк", &other)),
+    }
+}
+
+// Разбирает документ в список (номер строки, полный ключ, значение).
+// Ключи внутри таблицы [section] получают префикс "section."
+fn parse_toml(text: &str) -> Result<Vec<(usize, String, Value)>, ConfigError> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line_number = index + 1;
+        let error = |message: String| ConfigError::Parse { line: line_number, message };
+        let line = strip_comment(lines[index]).trim().to_string();
+        index += 1;
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let name = line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+                .ok_or_else(|| error(format!("некорректный заголовок таблицы: {}", line)))?;
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let (key, raw_value) = line.split_once('=')
+            .ok_or_else(|| error(format!("ожидается 'ключ = значение': {}", line)))?;
+        let key = parse_key(key.trim()).ok_or_else(|| error(format!("некорректный ключ: {}", key.trim())))?;
+        let mut raw_value = raw_value.trim().to_string();
+
+        // Многострочный массив продолжается до закрывающей скобки
+        while raw_value.starts_with('[') && !brackets_balanced(&raw_value) {
+            let next = lines.get(index).ok_or_else(|| error("незакрытый массив".to_string()))?;
+            raw_value.push(' ');
+            raw_value.push_str(strip_comment(next).trim());
+            index += 1;
+        }
+
+        let value = parse_value(&raw_value).map_err(error)?;
+        let full_key = if section.is_empty() { key } else { format!("{}.{}", section, key) };
+        entries.push((line_number, full_key, value));
+    }
+    Ok(entries)
+}
+
+fn parse_key(key: &str) -> Option<String> {
+    if let Some(quoted) = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        return Some(quoted.to_string());
+    }
+    let valid = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then(|| key.to_string())
+}
+
+// Отбрасывает комментарий, не трогая "#" внутри строк
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            },
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '#' => return &line[..i],
+                _ => {}
+            },
+        }
+    }
+    line
+}
+
+fn brackets_balanced(value: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for c in value.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {},
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+fn parse_value(raw: &str) -> Result<Value, String> {
+    let mut parser = ValueParser { chars: raw.chars().collect(), pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(format!("лишние символы после значения: {}", raw));
+    }
+    Ok(value)
+}
+
+struct ValueParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ValueParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some(_) => self.bare(),
+            None => Err("отсутствует значение".to_string()),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escaped = self.peek().ok_or("незавершенная escape-последовательность")?;
+                    self.pos += 1;
+                    out.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        '\\' => '\\',
+                        '"' => '"',
+                        other => return Err(format!("неизвестная escape-последовательность: \\{}", other)),
+                    });
+                },
+                c => out.push(c),
+            }
+        }
+        Err("незакрытая строка".to_string())
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == '\'' {
+                return Ok(self.chars[start..self.pos - 1].iter().collect());
+            }
+        }
+        Err("незакрытая строка".to_string())
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {},
+                _ => return Err("ожидается ',' или ']' в массиве".to_string()),
+            }
+        }
+    }
+
+    fn bare(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace() && c != ',' && c != ']') {
+            self.pos += 1;
+        }
+        let token: String = self.chars[start..self.pos].iter().collect();
+        match token.as_str() {
+            "true" => Ok(Value::Boolean(true)),
+            "false" => Ok(Value::Boolean(false)),
+            _ => token.replace('_', "").parse::<i64>()
+                .map(Value::Integer)
+                .map_err(|_| format!("некорректное значение: {}", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config() {
+        let config = Config::parse(r#"
+            # комментарий
+            exclude = [
+                "**/node_modules", # зависимости
+                '.git',
+            ]
+            top_dirs = 20
+            sort = "name"
+            reverse = true
+            min_size = "1M"
+            max_size = 1_000
+            color = "never"
+        "#).unwrap();
+
+        assert_eq!(config.exclude, vec!["**/node_modules", ".git"]);
+        assert_eq!(config.top_dirs, Some(20));
+        assert_eq!(config.sort, Some(SortKey::Name));
+        assert_eq!(config.reverse, Some(true));
+        assert_eq!(config.min_size, Some(1024 * 1024));
+        assert_eq!(config.max_size, Some(1000));
+        assert_eq!(config.top_files, None);
+        assert_eq!(config.unknown_keys, vec!["color"]);
+    }
+
+    #[test]
+    fn reports_errors_with_line() {
+        let err = Config::parse("top_dirs = 5\nsort = 3\n").unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { line: 2, .. }));
+        assert!(Config::parse("exclude = [\"a\"").is_err());
+        assert!(Config::parse("top_dirs = -1").is_err());
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let config = Config::parse(EXAMPLE_CONFIG).unwrap();
+        assert!(config.unknown_keys.is_empty());
+        // Все известные ключи описаны в примере
+        for key in KEYS {
+            assert!(EXAMPLE_CONFIG.contains(&format!("# {} = ", key)), "{}", key);
+        }
+    }
+
+    #[test]
+    fn accepts_dashed_keys_with_warning() {
+        let config = Config::parse("top-dirs = 7
+follow-symlinks = true
+no-such-key = 1
+").unwrap();
+        assert_eq!(config.top_dirs, Some(7));
+        assert_eq!(config.follow_symlinks, Some(true));
+        assert_eq!(config.deprecated_keys, vec![
+            ("top-dirs".to_string(), "top_dirs".to_string()), ("follow-symlinks".to_string(), "follow_symlinks".to_string())]);
+        assert_eq!(config.unknown_keys, vec!["no-such-key"]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Сравнение двух результатов сканирования
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::scan::{DirInfo, ScanResult};
+
+/// Изменение размера директории между двумя сканированиями
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirChange {
+    pub path: String,
+    /// Размер в старом сканировании; `None` - директории не было
+    pub old_size: Option<u64>,
+    /// Размер в новом сканировании; `None` - директория исчезла
+    pub new_size: Option<u64>,
+}
+
+impl DirChange {
+    /// Разница new - old в байтах
+    pub fn delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64 - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+/// Директории, которые появились, исчезли или изменили размер между двумя
+/// сканированиями; каждый список упорядочен по пути
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanDiff {
+    pub new: Vec<DirChange>,
+    pub deleted: Vec<DirChange>,
+    pub changed: Vec<DirChange>,
+}
+
+impl ScanResult {
+    /// Сравнивает директории двух сканирований одного и того же дерева
+    pub fn diff(before: &ScanResult, after: &ScanResult) -> ScanDiff {
+        let mut changes = diff_dirs(&before.dir_infos, &after.dir_infos, 0);
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut diff = ScanDiff::default();
+        for change in changes {
+            match (change.old_size, change.new_size) {
+                (None, _) => diff.new.push(change),
+                (_, None) => diff.deleted.push(change),
+                _ => diff.changed.push(change),
+            }
+        }
+        diff
+    }
+}
+
+/// Находит директории, размер которых изменился не меньше чем на threshold байт.
+/// Результат отсортирован по модулю изменения по убыванию
+pub fn diff_dirs(old: &BTreeMap<String, DirInfo>, new: &BTreeMap<String, DirInfo>, threshold: u64) -> Vec<DirChange> {
+    let mut changes: Vec<DirChange> = old.keys().chain(new.keys().filter(|path| !old.contains_key(*path)))
+        .map(|path| DirChange {
+            path: path.clone(),
+            old_size: old.get(path).map(|info| info.size),
+            new_size: new.get(path).map(|info| info.size),
+        })
+        .filter(|change| {
+            // Без порога показываем и появление/исчезновение пустых директорий
+            let appeared_or_gone = change.old_size.is_none() || change.new_size.is_none();
+            change.delta().unsigned_abs() >= threshold.max(1) || (threshold == 0 && appeared_or_gone)
+        })
+        .collect();
+    changes.sort_by(|a, b| b.delta().unsigned_abs().cmp(&a.delta().unsigned_abs()).then_with(|| a.path.cmp(&b.path)));
+    changes
+}
+
+/// Рост директории между двумя снимками
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirGrowth {
+    pub change: DirChange,
+    /// Скорость роста в байтах за сутки
+    pub bytes_per_day: f64,
+}
+
+/// Директории, выросшие между сканированиями, разделенными elapsed, по
+/// убыванию скорости роста. Директория, которой нет в одном из сканирований,
+/// считается там пустой
+pub fn growth_rates(old: &BTreeMap<String, DirInfo>, new: &BTreeMap<String, DirInfo>, elapsed: Duration) -> Vec<DirGrowth> {
+    let days = elapsed.as_secs_f64() / 86_400.0;
+    // Интервал общий, поэтому порядок diff_dirs по изменению размера
+    // совпадает с порядком по скорости
+    diff_dirs(old, new, 1).into_iter()
+        .filter(|change| change.delta() > 0)
+        .map(|change| DirGrowth { bytes_per_day: change.delta() as f64 / days, change })
+        .collect()
+}
+
+/// Директории результата с путями относительно root (сам root - ".");
+/// нужно для сравнения деревьев, лежащих в разных местах
+pub fn relative_dir_infos(result: &ScanResult, root: &Path) -> BTreeMap<String, DirInfo> {
+    let mut infos: BTreeMap<String, DirInfo> = result.dir_infos.iter()
+        .map(|(path, info)| {
+            let relative = Path::new(path).strip_prefix(root).map_or_else(
+                |_| path.clone(),
+                |p| p.to_string_lossy().into_owned());
+            (relative, info.clone())
+        })
+        .collect();
+    infos.insert(".".to_string(), result.root.clone());
+    infos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infos(entries: &[(&str, u64)]) -> BTreeMap<String, DirInfo> {
+        entries.iter()
+            .map(|(path, size)| (path.to_string(), DirInfo { size: *size, ..DirInfo::default() }))
+            .collect()
+    }
+
+    #[test]
+    fn finds_changes_above_threshold() {
+        let old = infos(&[("a", 100), ("b", 500), ("gone", 50)]);
+        let new = infos(&[("a", 100), ("b", 900), ("new", 2000)]);
+
+        let changes = diff_dirs(&old, &new, 100);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["new", "b"]);
+        assert_eq!(changes[0].old_size, None);
+        assert_eq!(changes[1].delta(), 400);
+
+        let all = diff_dirs(&old, &new, 0);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2].delta(), -50);
+    }
+
+    #[test]
+    fn ranks_growth_per_day() {
+        let old = infos(&[("a", 100), ("b", 500), ("shrunk", 50)]);
+        let new = infos(&[("a", 100), ("b", 900), ("new", 2000)]);
+
+        let growth = growth_rates(&old, &new, Duration::from_secs(2 * 86_400));
+        let rates: Vec<(&str, f64)> = growth.iter().map(|g| (g.change.path.as_str(), g.bytes_per_day)).collect();
+        assert_eq!(rates, vec![("new", 1000.0), ("b", 200.0)]);
+        assert_eq!(growth[0].change.old_size, None);
+    }
+
+    #[test]
+    fn splits_scan_diff() {
+        let before = ScanResult { dir_infos: infos(&[("/a", 100), ("/b", 500), ("/gone", 0)]), ..ScanResult::default() };
+        let after = ScanResult { dir_infos: infos(&[("/a", 100), ("/b", 900), ("/new", 0)]), ..ScanResult::default() };
+
+        let diff = ScanResult::diff(&before, &after);
+        assert_eq!(diff.new, vec![DirChange { path: "/new".to_string(), old_size: None, new_size: Some(0) }]);
+        assert_eq!(diff.deleted, vec![DirChange { path: "/gone".to_string(), old_size: Some(0), new_size: None }]);
+        assert_eq!(diff.changed, vec![DirChange { path: "/b".to_string(), old_size: Some(500), new_size: Some(900) }]);
+    }
+}
@@ -0,0 +1,55 @@
+//! Граф директорий в формате Graphviz DOT (`dot -Tpng граф.dot -o граф.png`)
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::format::{format_size, size_format};
+use crate::scan::DirInfo;
+
+/// Порог размера директорий для графа по умолчанию - 100 МиБ
+pub const DEFAULT_DOT_THRESHOLD: u64 = 100 * 1024 * 1024;
+
+/// Экранирует строку для вставки в строку DOT в кавычках
+pub fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Строит орграф: узел - директория не меньше threshold (корень - всегда),
+/// ребро - от ближайшей показанной директории-предка. Размер и цвет узла
+/// растут с долей от общего размера
+pub fn dirs_to_dot(root: &Path, total: &DirInfo, dirs: &[(String, DirInfo)], threshold: u64) -> String {
+    let mut ids: HashMap<&Path, usize> = HashMap::new();
+    ids.insert(root, 0);
+    let mut out = String::new();
+    out.push_str("digraph diskspace {\n");
+    out.push_str("  rankdir=LR;\n");
+    out.push_str("  node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+    write_node(&mut out, 0, &root.to_string_lossy(), total.size, total.size);
+
+    // Родитель обрабатывается раньше потомка, если идти по возрастанию глубины
+    let mut shown: Vec<&(String, DirInfo)> = dirs.iter().filter(|(_, info)| info.size >= threshold).collect();
+    shown.sort_by_key(|(path, _)| (Path::new(path.as_str()).components().count(), path.as_str()));
+    for (path, info) in shown {
+        let path = Path::new(path.as_str());
+        let Some(parent_id) = path.ancestors().skip(1).find_map(|dir| ids.get(dir).copied()) else {
+            continue;
+        };
+        let id = ids.len();
+        ids.insert(path, id);
+        let name = path.file_name().map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+        write_node(&mut out, id, &name, info.size, total.size);
+        let _ = writeln!(out, "  n{} -> n{};", parent_id, id);
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Узел с подписью "имя\nразмер"; цвет - от зеленого (мало) к красному (много)
+fn write_node(out: &mut String, id: usize, name: &str, size: u64, total: u64) {
+    let fraction = if total == 0 { 0.0 } else { (size as f64 / total as f64).min(1.0) };
+    let hue = (1.0 - fraction) * 0.33;
+    let _ = writeln!(out, "  n{} [label=\"{}\\n{}\", fontsize={:.0}, width={:.2}, fillcolor=\"{:.3} 0.5 1.0\"];",
+        id, dot_escape(name), dot_escape(&format_size(size, size_format())),
+        10.0 + 14.0 * fraction, 1.0 + 3.0 * fraction, hue);
+}
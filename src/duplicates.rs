@@ -0,0 +1,129 @@
+//! Поиск дубликатов файлов по содержимому и разбор вывода `fdupes`
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::sha256;
+
+/// Группа файлов с одинаковым содержимым
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// SHA-256 содержимого в hex
+    pub hash: String,
+    /// Размер каждой копии
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Место, которое освободится, если оставить только одну копию
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Группирует файлы по содержимому и возвращает группы из двух и более копий,
+/// отсортированные по объему лишнего места
+pub fn find_duplicates(candidates: Vec<(PathBuf, u64)>) -> Vec<DuplicateGroup> {
+    // Дубликаты возможны только среди файлов одинакового размера,
+    // поэтому файлы с уникальным размером не хешируем
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, size) in candidates {
+        by_size.entry(size).or_default().push(path);
+    }
+
+    let mut groups = Vec::new();
+    for (size, paths) in by_size {
+        if paths.len() < 2 {
+            continue;
+        }
+
+        let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            // Файлы, которые не удалось прочитать, пропускаем
+            if let Ok(hash) = hash_file(&path) {
+                by_hash.entry(hash).or_default().push(path);
+            }
+        }
+
+        for (hash, mut paths) in by_hash {
+            if paths.len() > 1 {
+                paths.sort();
+                groups.push(DuplicateGroup { hash, size, paths });
+            }
+        }
+    }
+
+    groups.sort_by_key(|g| std::cmp::Reverse(g.wasted()));
+    groups
+}
+
+/// Вычисляет SHA-256 содержимого файла и возвращает его в виде hex-строки
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    Ok(sha256::to_hex(&sha256::digest_file(path)?))
+}
+
+/// Группа из вывода `fdupes`: размер копии, если он указан (`fdupes -S`), и пути
+pub type FdupesGroup = (Option<u64>, Vec<PathBuf>);
+
+/// Разбирает вывод `fdupes`: группы путей, разделенные пустыми строками.
+/// Строка `N bytes each:` перед группой (`fdupes -S`) задает размер копий;
+/// группы из одного пути пропускаются
+pub fn parse_fdupes(text: &str) -> Vec<FdupesGroup> {
+    let mut groups = Vec::new();
+    let mut size = None;
+    let mut paths = Vec::new();
+    for line in text.lines().map(|line| line.strip_suffix('\r').unwrap_or(line)) {
+        if line.is_empty() {
+            if paths.len() > 1 {
+                groups.push((size, std::mem::take(&mut paths)));
+            }
+            paths.clear();
+            size = None;
+            continue;
+        }
+        match line.strip_suffix(" bytes each:").and_then(|n| n.parse().ok()) {
+            Some(n) if paths.is_empty() => size = Some(n),
+            _ => paths.push(PathBuf::from(line)),
+        }
+    }
+    if paths.len() > 1 {
+        groups.push((size, paths));
+    }
+    groups
+}
+
+/// Группы дубликатов из вывода `fdupes` без повторного хеширования, по
+/// убыванию лишнего места. Размер, не указанный в выводе, берется из
+/// метаданных первой доступной копии; группы, где ни одной копии не
+/// осталось, пропускаются. Хеш у групп пустой
+pub fn fdupes_groups(text: &str) -> Vec<DuplicateGroup> {
+    let mut groups: Vec<DuplicateGroup> = parse_fdupes(text).into_iter()
+        .filter_map(|(size, paths)| {
+            let size = size.or_else(|| paths.iter().find_map(|path| fs::metadata(path).ok()).map(|m| m.len()))?;
+            Some(DuplicateGroup { hash: String::new(), size, paths })
+        })
+        .collect();
+    groups.sort_by(|a, b| b.wasted().cmp(&a.wasted()).then_with(|| a.paths.cmp(&b.paths)));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fdupes_output() {
+        let text = "4096 bytes each:\n/a/1\n/a/2\n/a/3\n\n/b/1\r\n/b/2\r\n\r\n/c/lonely\n\n/d/1\n/d/2";
+        let groups = parse_fdupes(text);
+        assert_eq!(groups, vec![
+            (Some(4096), vec![PathBuf::from("/a/1"), "/a/2".into(), "/a/3".into()]),
+            (None, vec![PathBuf::from("/b/1"), "/b/2".into()]),
+            (None, vec![PathBuf::from("/d/1"), "/d/2".into()]),
+        ]);
+        let wasted: u64 = fdupes_groups(text).iter().map(DuplicateGroup::wasted).sum();
+        assert_eq!(wasted, 8192);
+    }
+}
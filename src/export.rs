@@ -0,0 +1,336 @@
+//! Машиночитаемый вывод: JSON, CSV, XML, формат экспорта ncdu, метрики
+//! Prometheus и контекст пользовательских шаблонов отчета
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::format::unix_seconds;
+use crate::report::{display_limit, percent};
+use crate::scan::{DirInfo, ScanResult};
+use crate::template::Value;
+
+/// Встроенный шаблон `--report-template default`: повторяет итог, таблицу
+/// директорий, крупнейшие файлы и типы файлов стандартного отчета
+pub const DEFAULT_TEMPLATE: &str = r#"📊 Общий размер: {{ total_size | size }} ({{ total_files }} файлов)
+
+📁 ТОП ДИРЕКТОРИИ ПО РАЗМЕРУ:
+РАЗМЕР          PCT     ФАЙЛОВ       ПУТЬ
+------------------------------------------------------------
+{% for dir in dirs -%}
+{{ dir.size | size | ljust(width=15) }} {{ dir.pct | round(precision=1) | rjust(width=6) }}% {{ dir.file_count | ljust(width=12) }} {{ dir.path }}
+{% endfor %}
+📄 САМЫЕ БОЛЬШИЕ ФАЙЛЫ:
+РАЗМЕР          ПУТЬ
+------------------------------------------------------------
+{% for file in largest_files -%}
+{{ file.size | size | ljust(width=15) }} {{ file.path }}
+{% endfor %}
+📊 ИСПОЛЬЗОВАНИЕ ПО ТИПАМ ФАЙЛОВ:
+РАЗМЕР          ТИП
+------------------------------------------------------------
+{% for ext in extensions -%}
+{{ ext.size | size | ljust(width=15) }} {% if ext.ext %}{{ ext.ext }}{% else %}[без расширения]{% endif %}
+{% endfor -%}
+"#;
+
+/// Экранирует строку для вставки в JSON (вместе с кавычками)
+pub fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Записывает поля DirInfo в виде пар "ключ": значение (без фигурных скобок)
+fn write_dir_info_json(out: &mut String, info: &DirInfo, indent: &str) {
+    let _ = writeln!(out, "{}\"size_bytes\": {},", indent, info.size);
+    let _ = writeln!(out, "{}\"file_count\": {},", indent, info.file_count);
+    match info.largest_file() {
+        Some((path, size)) => {
+            let _ = writeln!(out, "{}\"largest_file\": {{\"path\": {}, \"bytes\": {}}},",
+                indent, json_escape(&path.to_string_lossy()), size);
+        },
+        None => {
+            let _ = writeln!(out, "{}\"largest_file\": null,", indent);
+        }
+    }
+    let types: Vec<String> = info.file_types.iter()
+        .map(|(ext, size)| format!("{}: {}", json_escape(ext), size))
+        .collect();
+    let _ = writeln!(out, "{}\"file_types\": {{{}}}", indent, types.join(", "));
+}
+
+/// Контекст шаблона `--report-template`: `root`, `total_size`, `total_files`,
+/// `dirs` (`path`, `size`, `file_count`, `pct`) по убыванию размера,
+/// `extensions` (`ext`, `size`) и `largest_files` (`path`, `size`). Списки
+/// ограничены как в стандартном отчете, 0 - без ограничения
+pub fn scan_to_template_context(root: &Path, result: &ScanResult, top_dirs: usize, top_files: usize, top_extensions: usize) -> Value {
+    let total = result.root.size;
+    let mut dirs: Vec<(String, DirInfo)> = result.dir_infos.iter().map(|(path, info)| (path.clone(), info.clone())).collect();
+    dirs.sort_by(|a, b| b.1.size.cmp(&a.1.size).then_with(|| a.0.cmp(&b.0)));
+    let largest_files: Vec<Value> = result.root.top_files.iter()
+        .take(display_limit(top_files))
+        .map(|(path, size)| Value::object([("path", Value::from(path.to_string_lossy().into_owned())), ("size", Value::from(*size))]))
+        .collect();
+    let dir_values: Vec<Value> = dirs.iter()
+        .take(display_limit(top_dirs))
+        .map(|(path, info)| Value::object([
+            ("path", Value::from(path.as_str())),
+            ("size", Value::from(info.size)),
+            ("file_count", Value::from(info.file_count)),
+            ("pct", Value::from(percent(info.size, total))),
+        ]))
+        .collect();
+    let mut extensions: Vec<(&String, &u64)> = result.root.file_types.iter().collect();
+    extensions.sort_by_key(|(_, size)| std::cmp::Reverse(**size));
+    let extensions: Vec<Value> = extensions.into_iter()
+        .take(display_limit(top_extensions))
+        .map(|(ext, size)| Value::object([("ext", Value::from(ext.as_str())), ("size", Value::from(*size))]))
+        .collect();
+    Value::object([
+        ("root", Value::from(root.to_string_lossy().into_owned())),
+        ("total_size", Value::from(total)),
+        ("total_files", Value::from(result.root.file_count)),
+        ("dirs", Value::from(dir_values)),
+        ("extensions", Value::from(extensions)),
+        ("largest_files", Value::from(largest_files)),
+    ])
+}
+
+/// Сериализует результат сканирования в JSON-документ
+pub fn scan_to_json(root: &Path, result: &ScanResult) -> String {
+    let total_info = &result.root;
+    let dir_infos = &result.dir_infos;
+    let mut out = String::new();
+    out.push_str("{\n");
+    let _ = writeln!(out, "  \"path\": {},", json_escape(&root.to_string_lossy()));
+    out.push_str("  \"total\": {\n");
+    write_dir_info_json(&mut out, total_info, "    ");
+    out.push_str("  },\n");
+    out.push_str("  \"directories\": [");
+    for (i, (path, info)) in dir_infos.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        out.push_str("    {\n");
+        let _ = writeln!(out, "      \"path\": {},", json_escape(path));
+        write_dir_info_json(&mut out, info, "      ");
+        out.push_str("    }");
+    }
+    out.push_str(if dir_infos.is_empty() { "]\n" } else { "\n  ]\n" });
+    out.push_str("}\n");
+    out
+}
+
+/// Экранирует поле CSV по правилам RFC 4180
+pub fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Возвращает два CSV-блока: директории (по убыванию размера) и типы файлов
+pub fn scan_to_csv(result: &ScanResult) -> (String, String) {
+    let total_info = &result.root;
+    let dir_infos = &result.dir_infos;
+    let mut dirs: Vec<(&String, &DirInfo)> = dir_infos.iter().collect();
+    dirs.sort_by_key(|b| std::cmp::Reverse(b.1.size));
+
+    let mut dirs_csv = String::from("path,size_bytes,file_count,largest_file_path,largest_file_bytes\r\n");
+    for (path, info) in dirs {
+        let (largest_path, largest_size) = match info.largest_file() {
+            Some((path, size)) => (path.to_string_lossy().into_owned(), size.to_string()),
+            None => (String::new(), String::new()),
+        };
+        let _ = write!(dirs_csv, "{},{},{},{},{}\r\n",
+            csv_field(path), info.size, info.file_count, csv_field(&largest_path), largest_size);
+    }
+
+    let mut types: Vec<(&String, &u64)> = total_info.file_types.iter().collect();
+    types.sort_by_key(|b| std::cmp::Reverse(*b.1));
+
+    let mut types_csv = String::from("extension,size_bytes\r\n");
+    for (ext, size) in types {
+        let _ = write!(types_csv, "{},{}\r\n", csv_field(ext), size);
+    }
+
+    (dirs_csv, types_csv)
+}
+
+/// Экранирует значение метки в текстовом формате Prometheus (вместе с кавычками)
+pub fn prometheus_label(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+// Имя метрики Prometheus, ее описание и значение для директории
+type Metric = (&'static str, &'static str, fn(&DirInfo) -> Option<u64>);
+
+/// Метрики корня и каждой директории в текстовом формате Prometheus:
+/// `diskspace_directory_size_bytes`, `diskspace_file_count` и
+/// `diskspace_largest_file_bytes` с меткой `path`
+pub fn scan_to_prometheus(root: &Path, result: &ScanResult) -> String {
+    let root_path = root.to_string_lossy();
+    let dirs: Vec<(&str, &DirInfo)> = std::iter::once((root_path.as_ref(), &result.root))
+        .chain(result.dir_infos.iter().map(|(path, info)| (path.as_str(), info)))
+        .collect();
+    let metrics: [Metric; 3] = [
+        ("diskspace_directory_size_bytes", "Размер директории с вложенными в байтах", |info| Some(info.size)),
+        ("diskspace_file_count", "Число файлов в директории с вложенными", |info| Some(info.file_count as u64)),
+        ("diskspace_largest_file_bytes", "Размер самого большого файла директории в байтах",
+            |info| info.largest_file().map(|(_, size)| *size)),
+    ];
+
+    let mut out = String::new();
+    for (name, help, value) in metrics {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (path, info) in &dirs {
+            if let Some(value) = value(info) {
+                let _ = writeln!(out, "{}{{path={}}} {}", name, prometheus_label(path), value);
+            }
+        }
+    }
+    out
+}
+
+/// Экранирует строку для значения XML-атрибута
+pub fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => {
+                let _ = write!(escaped, "&#{};", c as u32);
+            },
+            // Остальные управляющие символы в XML 1.0 недопустимы даже в экранированном виде
+            c if (c as u32) < 0x20 => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Записывает дочерние элементы <largest_file> и <file_types>
+fn write_dir_info_xml(out: &mut String, info: &DirInfo, indent: &str) {
+    if let Some((path, size)) = info.largest_file() {
+        let _ = writeln!(out, "{}<largest_file path=\"{}\" size=\"{}\"/>",
+            indent, xml_escape(&path.to_string_lossy()), size);
+    }
+    if info.file_types.is_empty() {
+        let _ = writeln!(out, "{}<file_types/>", indent);
+        return;
+    }
+    let _ = writeln!(out, "{}<file_types>", indent);
+    for (ext, size) in &info.file_types {
+        let _ = writeln!(out, "{}  <type extension=\"{}\" size=\"{}\"/>", indent, xml_escape(ext), size);
+    }
+    let _ = writeln!(out, "{}</file_types>", indent);
+}
+
+/// Сериализует результат сканирования в XML-документ `<diskspace>`
+pub fn scan_to_xml(root: &Path, result: &ScanResult) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(out, "<diskspace path=\"{}\" size=\"{}\" file_count=\"{}\">",
+        xml_escape(&root.to_string_lossy()), result.root.size, result.root.file_count);
+    write_dir_info_xml(&mut out, &result.root, "  ");
+    for (path, info) in &result.dir_infos {
+        let _ = writeln!(out, "  <directory path=\"{}\" size=\"{}\" file_count=\"{}\">",
+            xml_escape(path), info.size, info.file_count);
+        write_dir_info_xml(&mut out, info, "    ");
+        out.push_str("  </directory>\n");
+    }
+    out.push_str("</diskspace>\n");
+    out
+}
+
+// Директория в дереве экспорта ncdu: имена и размеры файлов, пути поддиректорий
+#[derive(Default)]
+struct NcduDir {
+    files: Vec<(String, u64)>,
+    subdirs: Vec<PathBuf>,
+}
+
+/// Сериализует результат сканирования в JSON-формат экспорта ncdu
+/// (`ncdu -f ФАЙЛ`, gdu и веб-просмотрщики). Файлы берутся из
+/// `ScanResult::files`, поэтому сканирование должно собирать все файлы
+/// (`collect_files_min_size = Some(0)`). Сканер хранит один размер файла,
+/// поэтому `asize` и `dsize` совпадают
+pub fn scan_to_ncdu(root: &Path, result: &ScanResult, scanned_at: SystemTime) -> String {
+    let mut dirs: BTreeMap<PathBuf, NcduDir> = BTreeMap::new();
+    dirs.insert(root.to_path_buf(), NcduDir::default());
+    for path in result.dir_infos.keys() {
+        add_ncdu_dir(&mut dirs, root, Path::new(path));
+    }
+    for (path, size) in &result.files {
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            if add_ncdu_dir(&mut dirs, root, parent) {
+                if let Some(dir) = dirs.get_mut(parent) {
+                    dir.files.push((name.to_string_lossy().into_owned(), *size));
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "[1,0,{{\"progname\":\"diskspace\",\"timestamp\":{}}},", unix_seconds(scanned_at));
+    write_ncdu_dir(&mut out, &dirs, root, &root.to_string_lossy());
+    out.push_str("]\n");
+    out
+}
+
+// Добавляет dir и недостающих предков вплоть до root; false, если dir вне root
+fn add_ncdu_dir(dirs: &mut BTreeMap<PathBuf, NcduDir>, root: &Path, dir: &Path) -> bool {
+    if dirs.contains_key(dir) {
+        return true;
+    }
+    let parent = match dir.parent() {
+        Some(parent) if dir.starts_with(root) => parent,
+        _ => return false,
+    };
+    if !add_ncdu_dir(dirs, root, parent) {
+        return false;
+    }
+    if let Some(parent_dir) = dirs.get_mut(parent) {
+        parent_dir.subdirs.push(dir.to_path_buf());
+    }
+    dirs.insert(dir.to_path_buf(), NcduDir::default());
+    true
+}
+
+// Директория ncdu - массив: сначала сведения о ней самой, затем файлы
+// (объекты) и поддиректории (вложенные массивы)
+fn write_ncdu_dir(out: &mut String, dirs: &BTreeMap<PathBuf, NcduDir>, path: &Path, name: &str) {
+    let _ = write!(out, "[{{\"name\":{}}}", json_escape(name));
+    if let Some(dir) = dirs.get(path) {
+        for (file, size) in &dir.files {
+            let _ = write!(out, ",\n{{\"name\":{},\"asize\":{},\"dsize\":{}}}", json_escape(file), size, size);
+        }
+        let mut subdirs = dir.subdirs.clone();
+        subdirs.sort();
+        for subdir in &subdirs {
+            out.push_str(",\n");
+            let name = subdir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            write_ncdu_dir(out, dirs, subdir, &name);
+        }
+    }
+    out.push(']');
+}
@@ -0,0 +1,371 @@
+//! Сведения о файловой системе, на которой находится путь
+
+use std::path::{Path, PathBuf};
+
+/// Использование инодов файловой системы
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InodeUsage {
+    pub total: u64,
+    pub free: u64,
+}
+
+impl InodeUsage {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// Доля занятых инодов в процентах; 0, если файловая система не сообщает их число
+    pub fn used_percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Место на файловой системе в байтах
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpaceUsage {
+    pub total: u64,
+    pub free: u64,
+    /// Доступно непривилегированному пользователю: без блоков, зарезервированных за root
+    pub available: u64,
+    /// Тип файловой системы (ext4, btrfs, nfs...), если его удалось определить
+    pub fs_type: Option<String>,
+}
+
+impl SpaceUsage {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// Доля занятого места в процентах, как в колонке Use% у df: от места,
+    /// доступного пользователю, без резерва root
+    pub fn used_percent(&self) -> f64 {
+        let usable = self.used() + self.available;
+        if usable == 0 {
+            0.0
+        } else {
+            self.used() as f64 / usable as f64 * 100.0
+        }
+    }
+}
+
+/// Число инодов файловой системы, содержащей path (statvfs). `None`, если
+/// вызов не удался или платформа не поддерживается
+pub fn inode_usage(path: &Path) -> Option<InodeUsage> {
+    sys::statvfs(path).map(|stats| InodeUsage { total: stats.files, free: stats.files_free })
+}
+
+/// Размер и свободное место файловой системы, содержащей path (statvfs),
+/// без обхода файлов
+pub fn space_usage(path: &Path) -> Option<SpaceUsage> {
+    sys::statvfs(path).map(|stats| SpaceUsage {
+        total: stats.blocks * stats.block_size,
+        free: stats.blocks_free * stats.block_size,
+        available: stats.blocks_available * stats.block_size,
+        fs_type: filesystem_type(path),
+    })
+}
+
+/// Тип файловой системы, содержащей path: на Linux - по /proc/mounts, на
+/// macOS - по statfs, на Windows - по GetVolumeInformation
+#[cfg(target_os = "linux")]
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    let path = std::fs::canonicalize(path).ok()?;
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    mount_fs_type(&mounts, &path)
+}
+
+// struct statfs с 64-битными номерами инодов (_DARWIN_FEATURE_64_BIT_INODE)
+#[cfg(target_os = "macos")]
+#[repr(C)]
+#[allow(dead_code)]
+struct Statfs {
+    f_bsize: u32,
+    f_iosize: i32,
+    f_blocks: u64,
+    f_bfree: u64,
+    f_bavail: u64,
+    f_files: u64,
+    f_ffree: u64,
+    f_fsid: [i32; 2],
+    f_owner: u32,
+    f_type: u32,
+    f_flags: u32,
+    f_fssubtype: u32,
+    f_fstypename: [std::os::raw::c_char; 16],
+    f_mntonname: [std::os::raw::c_char; 1024],
+    f_mntfromname: [std::os::raw::c_char; 1024],
+    f_flags_ext: u32,
+    f_reserved: [u32; 7],
+}
+
+#[cfg(target_os = "macos")]
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    use std::ffi::{CStr, CString};
+    use std::os::raw::{c_char, c_int};
+    use std::os::unix::ffi::OsStrExt;
+
+    extern "C" {
+        #[cfg_attr(target_arch = "x86_64", link_name = "statfs$INODE64")]
+        fn statfs(path: *const c_char, buf: *mut Statfs) -> c_int;
+    }
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: структура состоит только из целых чисел, нулевое значение допустимо
+    let mut buf: Statfs = unsafe { std::mem::zeroed() };
+    // SAFETY: path - корректная C-строка, statfs записывает только в buf
+    if unsafe { statfs(path.as_ptr(), &mut buf) } != 0 {
+        return None;
+    }
+    // SAFETY: ядро записывает имя типа с завершающим нулем
+    let name = unsafe { CStr::from_ptr(buf.f_fstypename.as_ptr()) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+#[cfg(windows)]
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetVolumePathNameW(file_name: *const u16, volume_path: *mut u16, len: u32) -> i32;
+        fn GetVolumeInformationW(
+            root: *const u16,
+            volume_name: *mut u16,
+            volume_name_len: u32,
+            serial_number: *mut u32,
+            max_component_len: *mut u32,
+            flags: *mut u32,
+            fs_name: *mut u16,
+            fs_name_len: u32,
+        ) -> i32;
+    }
+
+    let path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut root = [0u16; 261];
+    let mut fs_name = [0u16; 261];
+    // SAFETY: строки завершаются нулем, длины буферов переданы явно
+    let ok = unsafe {
+        GetVolumePathNameW(path.as_ptr(), root.as_mut_ptr(), root.len() as u32) != 0
+            && GetVolumeInformationW(root.as_ptr(), std::ptr::null_mut(), 0, std::ptr::null_mut(),
+                std::ptr::null_mut(), std::ptr::null_mut(), fs_name.as_mut_ptr(), fs_name.len() as u32) != 0
+    };
+    let len = fs_name.iter().position(|&c| c == 0).unwrap_or(fs_name.len());
+    ok.then(|| String::from_utf16_lossy(&fs_name[..len]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn filesystem_type(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Точки монтирования системы: на Linux - по /proc/self/mountinfo, на macOS -
+/// по getmntinfo. Пустой список, если их не удалось получить
+#[cfg(target_os = "linux")]
+pub fn mount_points() -> Vec<PathBuf> {
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .map(|mountinfo| parse_mountinfo(&mountinfo))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+pub fn mount_points() -> Vec<PathBuf> {
+    use std::ffi::CStr;
+    use std::os::raw::c_int;
+
+    const MNT_NOWAIT: c_int = 2;
+
+    extern "C" {
+        #[cfg_attr(target_arch = "x86_64", link_name = "getmntinfo$INODE64")]
+        fn getmntinfo(mntbufp: *mut *mut Statfs, flags: c_int) -> c_int;
+    }
+
+    let mut buf: *mut Statfs = std::ptr::null_mut();
+    // SAFETY: getmntinfo записывает в buf указатель на свой массив из count структур
+    let count = unsafe { getmntinfo(&mut buf, MNT_NOWAIT) };
+    if count <= 0 || buf.is_null() {
+        return Vec::new();
+    }
+    // SAFETY: массив остается действительным до следующего вызова getmntinfo
+    let mounts = unsafe { std::slice::from_raw_parts(buf, count as usize) };
+    mounts.iter()
+        // SAFETY: ядро записывает путь с завершающим нулем
+        .map(|mount| unsafe { CStr::from_ptr(mount.f_mntonname.as_ptr()) })
+        .map(|name| PathBuf::from(name.to_string_lossy().into_owned()))
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn mount_points() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+// Точки монтирования из /proc/self/mountinfo. Строки имеют вид
+// `id родитель major:minor корень точка опции ... - тип источник опции`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_mountinfo(mountinfo: &str) -> Vec<PathBuf> {
+    mountinfo.lines()
+        .filter_map(|line| line.split_whitespace().nth(4))
+        .map(|mount_point| PathBuf::from(unescape_mount_point(mount_point)))
+        .collect()
+}
+
+// Тип файловой системы точки монтирования, ближайшей к path. Строки имеют вид
+// `устройство точка тип опции 0 0`; при одинаковой точке действует последняя
+// смонтированная
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn mount_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    let mut best: Option<(usize, &str)> = None;
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(_), Some(mount_point), Some(fs_type)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        let mount_point = unescape_mount_point(mount_point);
+        let depth = Path::new(&mount_point).components().count();
+        if path.starts_with(&mount_point) && !matches!(best, Some((best_depth, _)) if best_depth > depth) {
+            best = Some((depth, fs_type));
+        }
+    }
+    best.map(|(_, fs_type)| fs_type.to_string())
+}
+
+// В /proc/mounts пробелы и другие спецсимволы в путях записаны как \040
+fn unescape_mount_point(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        let code = rest.get(pos + 1..pos + 4).and_then(|octal| u8::from_str_radix(octal, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[pos + 4..];
+            },
+            None => {
+                out.push('\\');
+                rest = &rest[pos + 1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Поля statvfs, приведенные к u64
+#[allow(dead_code)]
+struct FsStats {
+    block_size: u64,
+    blocks: u64,
+    blocks_free: u64,
+    blocks_available: u64,
+    files: u64,
+    files_free: u64,
+}
+
+#[cfg(any(all(target_os = "linux", target_pointer_width = "64"), target_os = "macos"))]
+mod sys {
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_int, c_ulong};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    use super::FsStats;
+
+    // На 64-битном Linux счетчики fsblkcnt_t/fsfilcnt_t имеют размер unsigned long
+    #[cfg(target_os = "linux")]
+    type Count = c_ulong;
+    #[cfg(target_os = "macos")]
+    type Count = u32;
+
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Statvfs {
+        f_bsize: c_ulong,
+        f_frsize: c_ulong,
+        f_blocks: Count,
+        f_bfree: Count,
+        f_bavail: Count,
+        f_files: Count,
+        f_ffree: Count,
+        f_favail: Count,
+        f_fsid: c_ulong,
+        f_flag: c_ulong,
+        f_namemax: c_ulong,
+        // Зарезервированные поля glibc; на macOS - просто запас
+        f_spare: [c_int; 6],
+    }
+
+    extern "C" {
+        #[link_name = "statvfs"]
+        fn statvfs_raw(path: *const c_char, buf: *mut Statvfs) -> c_int;
+    }
+
+    pub fn statvfs(path: &Path) -> Option<FsStats> {
+        let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: структура состоит только из целых чисел, нулевое значение допустимо
+        let mut buf: Statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: path - корректная C-строка, statvfs записывает только в buf
+        let rc = unsafe { self::statvfs_raw(path.as_ptr(), &mut buf) };
+        (rc == 0).then_some(FsStats {
+            block_size: buf.f_frsize as u64,
+            blocks: buf.f_blocks as u64,
+            blocks_free: buf.f_bfree as u64,
+            blocks_available: buf.f_bavail as u64,
+            files: buf.f_files as u64,
+            files_free: buf.f_ffree as u64,
+        })
+    }
+}
+
+#[cfg(not(any(all(target_os = "linux", target_pointer_width = "64"), target_os = "macos")))]
+mod sys {
+    use std::path::Path;
+
+    use super::FsStats;
+
+    pub fn statvfs(_path: &Path) -> Option<FsStats> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_nearest_mount() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+            tmpfs /tmp tmpfs rw 0 0\n\
+            /dev/sdb1 /mnt/my\\040disk btrfs rw 0 0\n\
+            server:/export /home nfs rw 0 0\n\
+            /dev/sdc1 /home xfs rw 0 0\n";
+        assert_eq!(mount_fs_type(mounts, Path::new("/usr/lib")).as_deref(), Some("ext4"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/tmp")).as_deref(), Some("tmpfs"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/tmpfoo")).as_deref(), Some("ext4"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/mnt/my disk/a")).as_deref(), Some("btrfs"));
+        assert_eq!(mount_fs_type(mounts, Path::new("/home/user")).as_deref(), Some("xfs"));
+    }
+
+    #[test]
+    fn parses_mountinfo() {
+        let mountinfo = "22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw\n\
+            41 22 0:36 / /mnt/my\\040share rw - nfs server:/export rw\n\
+            57 22 8:1 /srv/data /var/lib/data rw - ext4 /dev/sda1 rw\n";
+        assert_eq!(parse_mountinfo(mountinfo), vec![
+            PathBuf::from("/"),
+            PathBuf::from("/mnt/my share"),
+            PathBuf::from("/var/lib/data"),
+        ]);
+    }
+
+    #[test]
+    fn used_percent_like_df() {
+        let usage = SpaceUsage { total: 100, free: 10, available: 5, fs_type: None };
+        assert_eq!(usage.used(), 90);
+        assert!((usage.used_percent() - 90.0 / 95.0 * 100.0).abs() < 1e-9);
+    }
+}
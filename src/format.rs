@@ -0,0 +1,572 @@
+//! Форматирование и разбор размеров и времени
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Ошибка разбора размера вида "500M"
+#[derive(Debug, PartialEq)]
+pub enum ParseSizeError {
+    Empty,
+    InvalidNumber(String),
+    InvalidSuffix(String),
+    Overflow,
+}
+
+impl std::fmt::Display for ParseSizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseSizeError::Empty => write!(f, "пустое значение размера"),
+            ParseSizeError::InvalidNumber(s) => write!(f, "некорректное число: {}", s),
+            ParseSizeError::InvalidSuffix(s) => write!(f, "неизвестная единица измерения: {}", s),
+            ParseSizeError::Overflow => write!(f, "слишком большое значение размера"),
+        }
+    }
+}
+
+/// Разбирает размер с необязательным суффиксом: "1024", "1K", "500M", "2G", "1.5T", "10MiB"
+pub fn parse_size_str(s: &str) -> Result<u64, ParseSizeError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(ParseSizeError::Empty);
+    }
+
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split);
+    if number.is_empty() {
+        return Err(ParseSizeError::InvalidNumber(s.to_string()));
+    }
+
+    let multiplier: u64 = match suffix.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        "T" | "TB" | "TIB" => 1024u64.pow(4),
+        "P" | "PB" | "PIB" => 1024u64.pow(5),
+        _ => return Err(ParseSizeError::InvalidSuffix(suffix.to_string())),
+    };
+
+    if let Ok(value) = number.parse::<u64>() {
+        return value.checked_mul(multiplier).ok_or(ParseSizeError::Overflow);
+    }
+
+    let value: f64 = number.parse()
+        .map_err(|_| ParseSizeError::InvalidNumber(number.to_string()))?;
+    let bytes = value * multiplier as f64;
+    if bytes >= u64::MAX as f64 {
+        return Err(ParseSizeError::Overflow);
+    }
+    Ok(bytes.round() as u64)
+}
+
+/// Ошибка разбора даты вида "2024-01-15" или "7d"
+#[derive(Debug, PartialEq)]
+pub enum ParseDateError {
+    Empty,
+    InvalidDate(String),
+    InvalidUnit(String),
+    OutOfRange,
+}
+
+impl std::fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseDateError::Empty => write!(f, "пустое значение даты"),
+            ParseDateError::InvalidDate(s) => write!(f, "некорректная дата: {} (ожидается ГГГГ-ММ-ДД или 7d, 2w, 1y)", s),
+            ParseDateError::InvalidUnit(s) => write!(f, "неизвестная единица времени: {} (допустимы h, d, w, y)", s),
+            ParseDateError::OutOfRange => write!(f, "дата вне допустимого диапазона"),
+        }
+    }
+}
+
+/// Разбирает дату: ISO-8601 "2024-01-15" или "2024-01-15T10:30:00" (UTC)
+/// либо относительный срок назад от текущего момента: "12h", "7d", "2w", "1y"
+pub fn parse_date(s: &str) -> Result<SystemTime, ParseDateError> {
+    parse_date_at(s, SystemTime::now())
+}
+
+// parse_date с заданным текущим моментом для относительных сроков
+fn parse_date_at(s: &str, now: SystemTime) -> Result<SystemTime, ParseDateError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(ParseDateError::Empty);
+    }
+
+    // Относительный срок: число и единица
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    if !number.is_empty() && !unit.starts_with('-') {
+        let count: u64 = number.parse().map_err(|_| ParseDateError::OutOfRange)?;
+        let unit_secs: u64 = match unit {
+            "h" => 3600,
+            "d" => 86400,
+            "w" => 7 * 86400,
+            "y" => 365 * 86400,
+            "" => return Err(ParseDateError::InvalidDate(s.to_string())),
+            _ => return Err(ParseDateError::InvalidUnit(unit.to_string())),
+        };
+        let ago = count.checked_mul(unit_secs).ok_or(ParseDateError::OutOfRange)?;
+        return now.checked_sub(Duration::from_secs(ago)).ok_or(ParseDateError::OutOfRange);
+    }
+
+    let invalid = || ParseDateError::InvalidDate(s.to_string());
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(invalid());
+    };
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let day: u32 = day.parse().map_err(|_| invalid())?;
+    let days = days_from_civil(year, month, day);
+    // Несуществующие даты вроде 2023-02-30 не совпадают сами с собой после обратного перевода
+    if !(1..=12).contains(&month) || civil_from_days(days) != (year, month, day) {
+        return Err(invalid());
+    }
+
+    let secs_of_day = match time {
+        Some(time) => {
+            let parts: Vec<u32> = time.split(':').map(|p| p.parse().map_err(|_| invalid())).collect::<Result<_, _>>()?;
+            match parts[..] {
+                [h, m] if h < 24 && m < 60 => h * 3600 + m * 60,
+                [h, m, sec] if h < 24 && m < 60 && sec < 60 => h * 3600 + m * 60 + sec,
+                _ => return Err(invalid()),
+            }
+        },
+        None => 0,
+    };
+
+    let seconds = days * 86400 + i64::from(secs_of_day);
+    let offset = Duration::from_secs(seconds.unsigned_abs());
+    let time = if seconds >= 0 { UNIX_EPOCH.checked_add(offset) } else { UNIX_EPOCH.checked_sub(offset) };
+    time.ok_or(ParseDateError::OutOfRange)
+}
+
+/// Единица измерения для `format_size_as`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnit {
+    Bytes,
+    KB,
+    MB,
+    GB,
+    TB,
+    PB,
+    /// Подобрать единицу по величине размера
+    Auto,
+}
+
+impl SizeUnit {
+    // Множитель и подпись единицы
+    fn scale(self) -> (u64, &'static str) {
+        match self {
+            SizeUnit::Bytes | SizeUnit::Auto => (1, "Б"),
+            SizeUnit::KB => (1024, "КиБ"),
+            SizeUnit::MB => (1024u64.pow(2), "МиБ"),
+            SizeUnit::GB => (1024u64.pow(3), "ГиБ"),
+            SizeUnit::TB => (1024u64.pow(4), "ТиБ"),
+            SizeUnit::PB => (1024u64.pow(5), "ПиБ"),
+        }
+    }
+
+    /// Наибольшая единица, в которой размер не меньше единицы
+    pub fn for_size(size: u64) -> SizeUnit {
+        [SizeUnit::PB, SizeUnit::TB, SizeUnit::GB, SizeUnit::MB, SizeUnit::KB]
+            .into_iter()
+            .find(|unit| size >= unit.scale().0)
+            .unwrap_or(SizeUnit::Bytes)
+    }
+}
+
+/// Система единиц для `format_size`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeFormat {
+    /// Двоичные единицы: 1 КиБ = 1024 Б
+    #[default]
+    IEC,
+    /// Десятичные единицы: 1 кБ = 1000 Б
+    SI,
+    /// Всегда в байтах
+    Bytes,
+    /// Число блоков заданного размера в байтах, с округлением вверх
+    Blocks(u64),
+}
+
+impl SizeFormat {
+    pub fn parse(s: &str) -> Option<SizeFormat> {
+        match s {
+            "iec" => Some(SizeFormat::IEC),
+            "si" => Some(SizeFormat::SI),
+            "bytes" => Some(SizeFormat::Bytes),
+            _ => None,
+        }
+    }
+}
+
+const IEC_LABELS: [&str; 6] = ["Б", "КиБ", "МиБ", "ГиБ", "ТиБ", "ПиБ"];
+const SI_LABELS: [&str; 6] = ["Б", "кБ", "МБ", "ГБ", "ТБ", "ПБ"];
+
+static SIZE_FORMAT: AtomicU8 = AtomicU8::new(0);
+// Размер блока для SizeFormat::Blocks
+static BLOCK_SIZE: AtomicU64 = AtomicU64::new(1);
+
+/// Задает систему единиц, в которой отчеты выводят размеры (см. [`size_format`])
+pub fn set_size_format(fmt: SizeFormat) {
+    let code = match fmt {
+        SizeFormat::IEC => 0,
+        SizeFormat::SI => 1,
+        SizeFormat::Bytes => 2,
+        SizeFormat::Blocks(block_size) => {
+            BLOCK_SIZE.store(block_size, Ordering::Relaxed);
+            3
+        },
+    };
+    SIZE_FORMAT.store(code, Ordering::Relaxed);
+}
+
+/// Система единиц отчетов, выбранная через [`set_size_format`]; по умолчанию IEC
+pub fn size_format() -> SizeFormat {
+    match SIZE_FORMAT.load(Ordering::Relaxed) {
+        1 => SizeFormat::SI,
+        2 => SizeFormat::Bytes,
+        3 => SizeFormat::Blocks(BLOCK_SIZE.load(Ordering::Relaxed)),
+        _ => SizeFormat::IEC,
+    }
+}
+
+/// Форматирует размер в байтах в человекочитаемый вид: "1.5 МиБ" (IEC),
+/// "1.6 МБ" (SI), "1572864 Б" или "384 бл." (блоки по 4096 Б)
+pub fn format_size(size: u64, fmt: SizeFormat) -> String {
+    match fmt {
+        SizeFormat::IEC => format_scaled(size, 1024, &IEC_LABELS),
+        SizeFormat::SI => format_scaled(size, 1000, &SI_LABELS),
+        SizeFormat::Bytes => format!("{} Б", size),
+        SizeFormat::Blocks(block_size) => format!("{} бл.", size.div_ceil(block_size.max(1))),
+    }
+}
+
+// Размер в наибольшей единице, в которой он не меньше единицы: килобайты и
+// мегабайты с одним знаком после точки, большие единицы - с двумя
+fn format_scaled(size: u64, base: u64, labels: &[&str; 6]) -> String {
+    let mut exp = 0;
+    while exp + 1 < labels.len() && size >= base.pow(exp as u32 + 1) {
+        exp += 1;
+    }
+    loop {
+        if exp == 0 {
+            return format!("{} {}", size, labels[0]);
+        }
+        let decimals = if exp <= 2 { 1 } else { 2 };
+        let value = size as f64 / base.pow(exp as u32) as f64;
+        // 1048575 Б - это 1023.999 КиБ: после округления показываем 1.0 МиБ, а не 1024.0 КиБ
+        let scale = 10f64.powi(decimals as i32);
+        if (value * scale).round() / scale >= base as f64 && exp + 1 < labels.len() {
+            exp += 1;
+            continue;
+        }
+        return format!("{:.*} {}", decimals, value, labels[exp]);
+    }
+}
+
+/// Число с разделением разрядов пробелом: "142 831"
+pub fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    // Первая группа короче, если число цифр не кратно трем
+    let mut end = match digits.len() % 3 {
+        0 => 3,
+        len => len,
+    };
+    let mut out = digits[..end].to_string();
+    while end < digits.len() {
+        out.push(' ');
+        out.push_str(&digits[end..end + 3]);
+        end += 3;
+    }
+    out
+}
+
+/// Форматирует размер в заданной двоичной единице, например чтобы в таблице
+/// сравнения все значения были в ГиБ
+pub fn format_size_as(size: u64, unit: SizeUnit) -> String {
+    let unit = if unit == SizeUnit::Auto { SizeUnit::for_size(size) } else { unit };
+    let (multiplier, label) = unit.scale();
+    match unit {
+        SizeUnit::Bytes | SizeUnit::Auto => format!("{} {}", size, label),
+        SizeUnit::KB | SizeUnit::MB => format!("{:.1} {}", size as f64 / multiplier as f64, label),
+        _ => format!("{:.2} {}", size as f64 / multiplier as f64, label),
+    }
+}
+
+/// Форматирует размер так же, как `du -h`: без пробела, с суффиксами K, M, G...
+/// в степенях 1024 и с округлением вверх ("4.0K", "12K", "1.0M")
+pub fn format_du_size(size: u64) -> String {
+    format_du_scaled(size, 1024, &["K", "M", "G", "T", "P", "E"])
+}
+
+/// Форматирует размер так же, как `du --si`: суффиксы k, M, G... в степенях 1000
+/// ("4.1k", "12k", "1.0M")
+pub fn format_du_size_si(size: u64) -> String {
+    format_du_scaled(size, 1000, &["k", "M", "G", "T", "P", "E"])
+}
+
+fn format_du_scaled(size: u64, base: u128, suffixes: &[&str; 6]) -> String {
+    if u128::from(size) < base {
+        return size.to_string();
+    }
+    let size = u128::from(size);
+    let mut unit = base;
+    let mut exp = 0;
+    while size >= unit * base && exp + 1 < suffixes.len() {
+        unit *= base;
+        exp += 1;
+    }
+    // Меньше 10 единиц - с одним знаком после точки
+    let tenths = (size * 10).div_ceil(unit);
+    if tenths < 100 {
+        return format!("{}.{}{}", tenths / 10, tenths % 10, suffixes[exp]);
+    }
+    let whole = size.div_ceil(unit);
+    if whole >= base && exp + 1 < suffixes.len() {
+        return format!("1.0{}", suffixes[exp + 1]);
+    }
+    format!("{}{}", whole, suffixes[exp])
+}
+
+/// Форматирует изменение размера со знаком: "+1.5 МиБ", "-300 Б"
+pub fn format_size_delta(delta: i64, fmt: SizeFormat) -> String {
+    let sign = if delta < 0 { '-' } else { '+' };
+    format!("{}{}", sign, format_size(delta.unsigned_abs(), fmt))
+}
+
+/// Время в секундах от начала эпохи Unix (с округлением вниз для времени до 1970 года)
+pub fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64) - i64::from(e.duration().subsec_nanos() > 0),
+    }
+}
+
+/// Момент времени, отстоящий от начала эпохи Unix на seconds секунд
+pub fn from_unix_seconds(seconds: i64) -> SystemTime {
+    let offset = Duration::from_secs(seconds.unsigned_abs());
+    if seconds >= 0 { UNIX_EPOCH + offset } else { UNIX_EPOCH - offset }
+}
+
+/// Форматирует время как `YYYY-MM-DD HH:MM:SS` (UTC)
+pub fn format_timestamp(time: SystemTime) -> String {
+    let seconds = unix_seconds(time);
+    let days = seconds.div_euclid(86400);
+    let secs_of_day = seconds.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, secs_of_day / 3600, secs_of_day % 3600 / 60, secs_of_day % 60)
+}
+
+/// Переводит дату григорианского календаря в число дней от 1970-01-01
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Переводит число дней от 1970-01-01 в (год, месяц, день) по григорианскому календарю
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Алгоритм Говарда Хиннанта (days_from_civil в обратную сторону)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_str_suffixes() {
+        assert_eq!(parse_size_str("500M"), Ok(524288000));
+        assert_eq!(parse_size_str("2G"), Ok(2147483648));
+        assert_eq!(parse_size_str("1K"), Ok(1024));
+        assert_eq!(parse_size_str("1024"), Ok(1024));
+        assert_eq!(parse_size_str("1.5K"), Ok(1536));
+    }
+
+    #[test]
+    fn parse_size_str_errors() {
+        assert_eq!(parse_size_str(""), Err(ParseSizeError::Empty));
+        assert_eq!(parse_size_str("M"), Err(ParseSizeError::InvalidNumber("M".to_string())));
+        assert_eq!(parse_size_str("10X"), Err(ParseSizeError::InvalidSuffix("X".to_string())));
+        assert_eq!(parse_size_str("99999999999P"), Err(ParseSizeError::Overflow));
+    }
+
+    #[test]
+    fn format_count_groups_digits() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1204), "1 204");
+        assert_eq!(format_count(142831), "142 831");
+        assert_eq!(format_count(1000000), "1 000 000");
+    }
+
+    #[test]
+    fn format_size_large_units() {
+        assert_eq!(format_size(1024u64.pow(4), SizeFormat::IEC), "1.00 ТиБ");
+        assert_eq!(format_size(1024u64.pow(5) * 3 / 2, SizeFormat::IEC), "1.50 ПиБ");
+        assert_eq!(format_size(u64::MAX, SizeFormat::IEC), "16384.00 ПиБ");
+        assert_eq!(format_size_as(1024u64.pow(4), SizeUnit::GB), "1024.00 ГиБ");
+        assert_eq!(format_size_as(512, SizeUnit::KB), "0.5 КиБ");
+    }
+
+    #[test]
+    fn format_size_iec_boundaries() {
+        let cases = [
+            (0, "0 Б"),
+            (1, "1 Б"),
+            (1023, "1023 Б"),
+            (1024, "1.0 КиБ"),
+            (1025, "1.0 КиБ"),
+            (1536, "1.5 КиБ"),
+            (1048575, "1.0 МиБ"),
+            (1048576, "1.0 МиБ"),
+            (1048577, "1.0 МиБ"),
+            (1073741823, "1.00 ГиБ"),
+            (1073741824, "1.00 ГиБ"),
+            (1099511627775, "1.00 ТиБ"),
+        ];
+        for (size, expected) in cases {
+            assert_eq!(format_size(size, SizeFormat::IEC), expected, "{} байт", size);
+        }
+    }
+
+    #[test]
+    fn format_size_si_boundaries() {
+        let cases = [
+            (0, "0 Б"),
+            (999, "999 Б"),
+            (1000, "1.0 кБ"),
+            (1023, "1.0 кБ"),
+            (1024, "1.0 кБ"),
+            (1500, "1.5 кБ"),
+            (999_949, "999.9 кБ"),
+            (999_999, "1.0 МБ"),
+            (1_000_000, "1.0 МБ"),
+            (1048576, "1.0 МБ"),
+            (1_000_000_000, "1.00 ГБ"),
+            (1_500_000_000_000, "1.50 ТБ"),
+            (2_000_000_000_000_000, "2.00 ПБ"),
+        ];
+        for (size, expected) in cases {
+            assert_eq!(format_size(size, SizeFormat::SI), expected, "{} байт", size);
+        }
+    }
+
+    #[test]
+    fn format_size_bytes() {
+        let cases = [
+            (0, "0 Б"),
+            (1023, "1023 Б"),
+            (1024, "1024 Б"),
+            (1048575, "1048575 Б"),
+            (1048576, "1048576 Б"),
+            (u64::MAX, "18446744073709551615 Б"),
+        ];
+        for (size, expected) in cases {
+            assert_eq!(format_size(size, SizeFormat::Bytes), expected, "{} байт", size);
+        }
+    }
+
+    #[test]
+    fn format_size_delta_sign() {
+        assert_eq!(format_size_delta(1536, SizeFormat::IEC), "+1.5 КиБ");
+        assert_eq!(format_size_delta(-300, SizeFormat::SI), "-300 Б");
+        assert_eq!(format_size_delta(0, SizeFormat::Bytes), "+0 Б");
+    }
+
+    #[test]
+    fn format_size_in_blocks() {
+        assert_eq!(format_size(0, SizeFormat::Blocks(4096)), "0 бл.");
+        assert_eq!(format_size(4096, SizeFormat::Blocks(4096)), "1 бл.");
+        assert_eq!(format_size(4097, SizeFormat::Blocks(4096)), "2 бл.");
+        assert_eq!(format_size(1572864, SizeFormat::Blocks(512)), "3072 бл.");
+    }
+
+    #[test]
+    fn size_format_names() {
+        assert_eq!(SizeFormat::parse("si"), Some(SizeFormat::SI));
+        assert_eq!(SizeFormat::parse("bytes"), Some(SizeFormat::Bytes));
+        assert_eq!(SizeFormat::parse("KiB"), None);
+    }
+
+    #[test]
+    fn format_timestamp_utc() {
+        use std::time::Duration;
+
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(UNIX_EPOCH + Duration::from_secs(951782400 + 3661)), "2000-02-29 01:01:01");
+        assert_eq!(format_timestamp(UNIX_EPOCH - Duration::from_secs(1)), "1969-12-31 23:59:59");
+    }
+
+    #[test]
+    fn unix_seconds_round_trip() {
+        use std::time::Duration;
+
+        assert_eq!(unix_seconds(UNIX_EPOCH + Duration::new(1_700_000_000, 999)), 1_700_000_000);
+        assert_eq!(unix_seconds(UNIX_EPOCH - Duration::new(10, 5)), -11);
+        assert_eq!(from_unix_seconds(-86400), UNIX_EPOCH - Duration::from_secs(86400));
+        assert_eq!(unix_seconds(from_unix_seconds(1_705_276_800)), 1_705_276_800);
+    }
+
+    #[test]
+    fn parse_date_formats() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(parse_date_at("2024-01-15", now), Ok(UNIX_EPOCH + Duration::from_secs(1_705_276_800)));
+        assert_eq!(parse_date_at("2000-02-29T01:01:01", now), Ok(UNIX_EPOCH + Duration::from_secs(951782400 + 3661)));
+        assert_eq!(parse_date_at("1969-12-31", now), Ok(UNIX_EPOCH - Duration::from_secs(86400)));
+        assert_eq!(parse_date_at("7d", now), Ok(now - Duration::from_secs(7 * 86400)));
+        assert_eq!(parse_date_at("1y", now), Ok(now - Duration::from_secs(365 * 86400)));
+
+        assert_eq!(parse_date_at("", now), Err(ParseDateError::Empty));
+        assert_eq!(parse_date_at("2023-02-29", now), Err(ParseDateError::InvalidDate("2023-02-29".to_string())));
+        assert_eq!(parse_date_at("2024-13-01", now), Err(ParseDateError::InvalidDate("2024-13-01".to_string())));
+        assert_eq!(parse_date_at("30x", now), Err(ParseDateError::InvalidUnit("x".to_string())));
+        assert_eq!(parse_date_at("yesterday", now), Err(ParseDateError::InvalidDate("yesterday".to_string())));
+    }
+
+    #[test]
+    fn format_du_size_matches_du() {
+        assert_eq!(format_du_size(0), "0");
+        assert_eq!(format_du_size(1023), "1023");
+        assert_eq!(format_du_size(1024), "1.0K");
+        assert_eq!(format_du_size(1025), "1.1K");
+        assert_eq!(format_du_size(4096), "4.0K");
+        assert_eq!(format_du_size(10239), "10K");
+        assert_eq!(format_du_size(12288), "12K");
+        assert_eq!(format_du_size(1048575), "1.0M");
+        assert_eq!(format_du_size(1572864), "1.5M");
+        assert_eq!(format_du_size(5 * 1024 * 1024 * 1024), "5.0G");
+        assert_eq!(format_du_size(u64::MAX), "16E");
+    }
+
+    #[test]
+    fn format_du_size_si_matches_du_si() {
+        assert_eq!(format_du_size_si(999), "999");
+        assert_eq!(format_du_size_si(1000), "1.0k");
+        assert_eq!(format_du_size_si(4096), "4.1k");
+        assert_eq!(format_du_size_si(12288), "13k");
+        assert_eq!(format_du_size_si(999_999), "1.0M");
+        assert_eq!(format_du_size_si(1_500_000), "1.5M");
+        assert_eq!(format_du_size_si(u64::MAX), "19E");
+    }
+}
@@ -0,0 +1,229 @@
+//! Сопоставление путей с glob-шаблонами (`*`, `?`, `**`, `[a-z]`)
+
+use std::fmt;
+use std::path::Path;
+
+/// Ошибка компиляции шаблона
+#[derive(Debug, PartialEq)]
+pub enum GlobError {
+    /// Незакрытая скобка `[`
+    UnclosedClass(String),
+    Empty,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GlobError::UnclosedClass(pattern) => write!(f, "незакрытая скобка '[' в шаблоне: {}", pattern),
+            GlobError::Empty => write!(f, "пустой шаблон"),
+        }
+    }
+}
+
+/// Скомпилированный glob-шаблон.
+///
+/// Шаблон без `/` сравнивается только с именем файла или директории
+/// (`*.tmp`, `.git`), шаблон с `/` - с путем относительно корня сканирования
+/// (`**/node_modules`, `src/*.rs`).
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: Vec<char>,
+    name_only: bool,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Glob, GlobError> {
+        // Ведущий "./" и завершающий "/" не влияют на смысл шаблона
+        let trimmed = pattern.trim_start_matches("./").trim_end_matches('/');
+        if trimmed.is_empty() {
+            return Err(GlobError::Empty);
+        }
+
+        let chars: Vec<char> = trimmed.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' => i += 1,
+                '[' => {
+                    i = class_end(&chars, i).ok_or_else(|| GlobError::UnclosedClass(pattern.to_string()))?;
+                },
+                _ => {}
+            }
+            i += 1;
+        }
+
+        Ok(Glob {
+            name_only: !trimmed.contains('/'),
+            pattern: chars,
+        })
+    }
+
+    /// Проверяет относительный путь с разделителями `/`
+    pub fn is_match(&self, relative_path: &str) -> bool {
+        let target = if self.name_only {
+            relative_path.rsplit('/').next().unwrap_or(relative_path)
+        } else {
+            relative_path
+        };
+        let target: Vec<char> = target.chars().collect();
+        match_here(&self.pattern, &target)
+    }
+}
+
+/// Набор шаблонов, объединенных по ИЛИ
+#[derive(Debug, Clone, Default)]
+pub struct GlobSet {
+    globs: Vec<Glob>,
+}
+
+impl GlobSet {
+    pub fn new() -> Self {
+        GlobSet { globs: Vec::new() }
+    }
+
+    pub fn add(&mut self, pattern: &str) -> Result<(), GlobError> {
+        self.globs.push(Glob::new(pattern)?);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty()
+    }
+
+    /// Шаблоны набора в порядке добавления (без ведущего "./" и завершающего "/")
+    pub fn patterns(&self) -> Vec<String> {
+        self.globs.iter().map(|glob| glob.pattern.iter().collect()).collect()
+    }
+
+    pub fn is_match(&self, relative_path: &str) -> bool {
+        self.globs.iter().any(|glob| glob.is_match(relative_path))
+    }
+
+    /// Проверяет путь относительно root; пути вне root сравниваются целиком
+    pub fn is_match_path(&self, root: &Path, path: &Path) -> bool {
+        if self.globs.is_empty() {
+            return false;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        self.is_match(&relative_to_slash(relative))
+    }
+}
+
+// Приводит путь к виду с разделителями "/" независимо от платформы
+fn relative_to_slash(path: &Path) -> String {
+    let parts: Vec<String> = path.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    parts.join("/")
+}
+
+// Возвращает индекс закрывающей "]" для класса, начинающегося в start
+fn class_end(p: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if i < p.len() && (p[i] == '!' || p[i] == '^') {
+        i += 1;
+    }
+    // "]" сразу после открывающей скобки - обычный символ
+    if i < p.len() && p[i] == ']' {
+        i += 1;
+    }
+    while i < p.len() {
+        if p[i] == ']' {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!') | Some('^') => (true, &class[1..]),
+        _ => (false, class),
+    };
+
+    let mut matched = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            if class[i] <= c && c <= class[i + 2] {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+    matched != negated
+}
+
+fn match_here(p: &[char], s: &[char]) -> bool {
+    if p.is_empty() {
+        return s.is_empty();
+    }
+
+    match p[0] {
+        '*' if p.get(1) == Some(&'*') => {
+            let rest = &p[2..];
+            if rest.first() == Some(&'/') {
+                // "**/" - ноль или более целых компонентов пути
+                let rest = &rest[1..];
+                if match_here(rest, s) {
+                    return true;
+                }
+                (0..s.len()).any(|i| s[i] == '/' && match_here(rest, &s[i + 1..]))
+            } else {
+                (0..=s.len()).any(|i| match_here(rest, &s[i..]))
+            }
+        },
+        '*' => {
+            let rest = &p[1..];
+            for i in 0..=s.len() {
+                if match_here(rest, &s[i..]) {
+                    return true;
+                }
+                // "*" не выходит за пределы одного компонента пути
+                if i < s.len() && s[i] == '/' {
+                    break;
+                }
+            }
+            false
+        },
+        '?' => !s.is_empty() && s[0] != '/' && match_here(&p[1..], &s[1..]),
+        '[' => {
+            let end = match class_end(p, 0) {
+                Some(end) => end,
+                None => return false,
+            };
+            !s.is_empty() && s[0] != '/' && class_matches(&p[1..end], s[0]) && match_here(&p[end + 1..], &s[1..])
+        },
+        '\\' if p.len() > 1 => !s.is_empty() && s[0] == p[1] && match_here(&p[2..], &s[1..]),
+        c => !s.is_empty() && s[0] == c && match_here(&p[1..], &s[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matching() {
+        let glob = |p: &str| Glob::new(p).unwrap();
+
+        assert!(glob(".git").is_match("project/.git"));
+        assert!(glob(".git").is_match(".git"));
+        assert!(glob("*.tmp").is_match("a/b/file.tmp"));
+        assert!(!glob("*.tmp").is_match("a/b/file.tmpl"));
+        assert!(glob("**/node_modules").is_match("node_modules"));
+        assert!(glob("**/node_modules").is_match("web/app/node_modules"));
+        assert!(glob("src/*.rs").is_match("src/main.rs"));
+        assert!(!glob("src/*.rs").is_match("src/bin/main.rs"));
+        assert!(glob("src/**/*.rs").is_match("src/bin/main.rs"));
+        assert!(glob("file?.[ch]").is_match("file1.c"));
+        assert!(!glob("file?.[!ch]").is_match("file1.c"));
+        assert_eq!(Glob::new("[abc").unwrap_err(), GlobError::UnclosedClass("[abc".to_string()));
+    }
+}
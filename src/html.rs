@@ -0,0 +1,145 @@
+//! Самодостаточный HTML-отчет: стили и скрипт встроены в страницу
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::format::{format_size, format_timestamp, size_format};
+use crate::report::{display_limit, percent};
+use crate::scan::DirInfo;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; margin: 2em auto; max-width: 1100px; color: #222; padding: 0 1em; }
+h1 { font-size: 1.4em; }
+h2 { font-size: 1.15em; margin-top: 2em; border-bottom: 1px solid #ddd; padding-bottom: .3em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: .3em .6em; border-bottom: 1px solid #eee; }
+td.num { text-align: right; white-space: nowrap; font-variant-numeric: tabular-nums; }
+th.sortable { cursor: pointer; user-select: none; }
+th.sortable:hover { background: #f3f3f3; }
+.path { word-break: break-all; font-family: monospace; }
+.bar { background: #eee; height: 1em; min-width: 200px; }
+.bar div { background: #4a90d9; height: 100%; }
+.summary { color: #555; }
+"#;
+
+// Сортировка таблицы по щелчку на заголовке: числовые колонки
+// сравниваются по атрибуту data-value, остальные - как текст
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable").forEach(function (table) {
+  table.querySelectorAll("th.sortable").forEach(function (th, column) {
+    var ascending = false;
+    th.addEventListener("click", function () {
+      var body = table.tBodies[0];
+      var rows = Array.prototype.slice.call(body.rows);
+      ascending = !ascending;
+      rows.sort(function (a, b) {
+        var x = a.cells[column], y = b.cells[column];
+        var vx = x.dataset.value !== undefined ? Number(x.dataset.value) : x.textContent;
+        var vy = y.dataset.value !== undefined ? Number(y.dataset.value) : y.textContent;
+        var order = vx < vy ? -1 : vx > vy ? 1 : 0;
+        return ascending ? order : -order;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+});
+"#;
+
+/// Данные HTML-отчета
+pub struct HtmlReport<'a> {
+    pub root: &'a Path,
+    /// Итоговая статистика корня сканирования
+    pub total: &'a DirInfo,
+    /// Директории, отсортированные по размеру по убыванию
+    pub dirs: &'a [(String, DirInfo)],
+    pub largest_files: &'a [(PathBuf, u64)],
+    pub tips: &'a [String],
+    pub scanned_at: SystemTime,
+    /// Ограничения числа строк, 0 - без ограничения
+    pub top_dirs: usize,
+    pub top_files: usize,
+    pub top_extensions: usize,
+}
+
+impl HtmlReport<'_> {
+    pub fn render(&self) -> String {
+        let root = self.root.to_string_lossy();
+        let timestamp = format_timestamp(self.scanned_at);
+        let mut out = String::new();
+
+        out.push_str("<!DOCTYPE html>\n<html lang=\"ru\">\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(out, "<title>diskspace: {} ({} UTC)</title>", html_escape(&root), timestamp);
+        let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE);
+        let _ = writeln!(out, "<h1>Использование дискового пространства: <span class=\"path\">{}</span></h1>", html_escape(&root));
+        let _ = writeln!(out, "<p class=\"summary\">Сканирование: {} UTC. Общий размер: {} ({} файлов).</p>",
+            timestamp, format_size(self.total.size, size_format()), self.total.file_count);
+
+        self.write_dirs(&mut out);
+        self.write_file_types(&mut out);
+        self.write_largest_files(&mut out);
+
+        out.push_str("<h2>Советы по оптимизации</h2>\n<ul>\n");
+        for tip in self.tips {
+            let _ = writeln!(out, "<li>{}</li>", html_escape(tip));
+        }
+        out.push_str("</ul>\n");
+
+        let _ = writeln!(out, "<script>{}</script>\n</body>\n</html>", SCRIPT);
+        out
+    }
+
+    fn write_dirs(&self, out: &mut String) {
+        out.push_str("<h2>Директории по размеру</h2>\n<table class=\"sortable\">\n<thead><tr>");
+        out.push_str("<th class=\"sortable\">Размер</th><th class=\"sortable\">%</th>");
+        out.push_str("<th class=\"sortable\">Файлов</th><th class=\"sortable\">Путь</th></tr></thead>\n<tbody>\n");
+        for (path, info) in self.dirs.iter().take(display_limit(self.top_dirs)) {
+            let pct = percent(info.size, self.total.size);
+            let _ = writeln!(out,
+                "<tr><td class=\"num\" data-value=\"{}\">{}</td><td class=\"num\" data-value=\"{:.3}\">{:.1}%</td>\
+                 <td class=\"num\" data-value=\"{}\">{}</td><td class=\"path\">{}</td></tr>",
+                info.size, format_size(info.size, size_format()), pct, pct, info.file_count, info.file_count, html_escape(path));
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+
+    fn write_file_types(&self, out: &mut String) {
+        let mut types: Vec<(&String, &u64)> = self.total.file_types.iter().collect();
+        types.sort_by_key(|(_, size)| std::cmp::Reverse(**size));
+        let max_size = types.first().map_or(0, |(_, size)| **size);
+
+        out.push_str("<h2>Типы файлов</h2>\n<table>\n<thead><tr><th>Тип</th><th>Размер</th><th></th></tr></thead>\n<tbody>\n");
+        for (ext, size) in types.iter().take(display_limit(self.top_extensions)) {
+            let name = if ext.is_empty() { "[без расширения]" } else { ext.as_str() };
+            let _ = writeln!(out,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td><div class=\"bar\"><div style=\"width: {:.1}%\"></div></div></td></tr>",
+                html_escape(name), format_size(**size, size_format()), percent(**size, max_size));
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+
+    fn write_largest_files(&self, out: &mut String) {
+        out.push_str("<h2>Самые большие файлы</h2>\n<table>\n<thead><tr><th>Размер</th><th>Путь</th></tr></thead>\n<tbody>\n");
+        for (path, size) in self.largest_files.iter().take(display_limit(self.top_files)) {
+            let _ = writeln!(out, "<tr><td class=\"num\">{}</td><td class=\"path\">{}</td></tr>",
+                format_size(*size, size_format()), html_escape(&path.to_string_lossy()));
+        }
+        out.push_str("</tbody>\n</table>\n");
+    }
+}
+
+/// Экранирует текст для вставки в HTML (в том числе в значения атрибутов)
+pub fn html_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
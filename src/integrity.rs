@@ -0,0 +1,153 @@
+//! Хеши содержимого файлов в формате `sha256sum` и проверка целостности
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::sha256::{digest_file, to_hex};
+
+/// Путь файла и SHA-256 его содержимого
+pub type FileHash = (PathBuf, [u8; 32]);
+
+/// Ошибка разбора файла хешей
+#[derive(Debug, PartialEq)]
+pub enum ParseHashError {
+    /// Номер строки (с единицы) и сама строка
+    InvalidLine(usize, String),
+}
+
+impl fmt::Display for ParseHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseHashError::InvalidLine(line, text) => write!(f, "строка {}: ожидается \"<sha256>  <путь>\": {}", line, text),
+        }
+    }
+}
+
+/// Изменения файлов по сравнению с сохраненными хешами
+#[derive(Debug, Default, PartialEq)]
+pub struct IntegrityReport {
+    /// Файлы, которых не было в файле хешей
+    pub added: Vec<PathBuf>,
+    /// Файлы из файла хешей, которые не найдены при сканировании
+    pub deleted: Vec<PathBuf>,
+    /// Файлы, содержимое которых изменилось
+    pub modified: Vec<PathBuf>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.added.is_empty() && self.deleted.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Вычисляет SHA-256 файлов в jobs потоков; файлы, которые не удалось
+/// прочитать, пропускаются. Результат упорядочен по пути
+pub fn hash_files(files: &[(PathBuf, u64)], jobs: usize) -> Vec<FileHash> {
+    let hashes = Mutex::new(Vec::with_capacity(files.len()));
+    let chunk_size = files.len().div_ceil(jobs.max(1)).max(1);
+    thread::scope(|scope| {
+        for chunk in files.chunks(chunk_size) {
+            let hashes = &hashes;
+            scope.spawn(move || {
+                let chunk_hashes: Vec<FileHash> = chunk.iter()
+                    .filter_map(|(path, _)| digest_file(path).ok().map(|digest| (path.clone(), digest)))
+                    .collect();
+                hashes.lock().unwrap().extend(chunk_hashes);
+            });
+        }
+    });
+    let mut hashes = hashes.into_inner().unwrap();
+    hashes.sort_by(|a, b| a.0.cmp(&b.0));
+    hashes
+}
+
+/// Строки `<hex>  <путь>`, как у `sha256sum`
+pub fn to_sha256sum(hashes: &[FileHash]) -> String {
+    hashes.iter()
+        .map(|(path, digest)| format!("{}  {}\n", to_hex(digest), path.display()))
+        .collect()
+}
+
+/// Разбирает вывод `sha256sum` (в том числе с маркером двоичного режима `*`);
+/// пустые строки пропускаются
+pub fn parse_sha256sum(text: &str) -> Result<Vec<FileHash>, ParseHashError> {
+    let mut hashes = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || ParseHashError::InvalidLine(i + 1, line.to_string());
+        let (hex, rest) = line.get(..64).zip(line.get(64..)).ok_or_else(invalid)?;
+        let path = rest.strip_prefix("  ").or_else(|| rest.strip_prefix(" *")).ok_or_else(invalid)?;
+        if path.is_empty() {
+            return Err(invalid());
+        }
+        hashes.push((PathBuf::from(path), parse_hex(hex).ok_or_else(invalid)?));
+    }
+    Ok(hashes)
+}
+
+fn parse_hex(hex: &str) -> Option<[u8; 32]> {
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// Сравнивает текущие хеши с сохраненными
+pub fn verify(expected: &[FileHash], actual: &[FileHash]) -> IntegrityReport {
+    let expected: BTreeMap<&PathBuf, &[u8; 32]> = expected.iter().map(|(path, digest)| (path, digest)).collect();
+    let actual: BTreeMap<&PathBuf, &[u8; 32]> = actual.iter().map(|(path, digest)| (path, digest)).collect();
+    let mut report = IntegrityReport::default();
+    for (path, digest) in &actual {
+        match expected.get(path) {
+            None => report.added.push((*path).clone()),
+            Some(old) if old != digest => report.modified.push((*path).clone()),
+            Some(_) => {},
+        }
+    }
+    report.deleted = expected.keys().filter(|path| !actual.contains_key(*path)).map(|path| (*path).clone()).collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(byte: u8) -> [u8; 32] {
+        [byte; 32]
+    }
+
+    #[test]
+    fn sha256sum_round_trip() {
+        let hashes = vec![(PathBuf::from("/data/a b.txt"), digest(0xab)), (PathBuf::from("/data/c"), digest(1))];
+        let text = to_sha256sum(&hashes);
+        assert!(text.starts_with(&format!("{}  /data/a b.txt\n", "ab".repeat(32))));
+        assert_eq!(parse_sha256sum(&text), Ok(hashes));
+    }
+
+    #[test]
+    fn parses_binary_marker_and_rejects_garbage() {
+        let line = format!("{} */bin/ls", "00".repeat(32));
+        assert_eq!(parse_sha256sum(&line), Ok(vec![(PathBuf::from("/bin/ls"), digest(0))]));
+        assert_eq!(parse_sha256sum("zz  /a"), Err(ParseHashError::InvalidLine(1, "zz  /a".to_string())));
+        let bad_hex = format!("{}  /a", "g".repeat(64));
+        assert!(parse_sha256sum(&bad_hex).is_err());
+    }
+
+    #[test]
+    fn reports_changes() {
+        let expected = vec![(PathBuf::from("/a"), digest(1)), (PathBuf::from("/b"), digest(2)), (PathBuf::from("/c"), digest(3))];
+        let actual = vec![(PathBuf::from("/a"), digest(1)), (PathBuf::from("/b"), digest(9)), (PathBuf::from("/d"), digest(4))];
+        let report = verify(&expected, &actual);
+        assert_eq!(report.added, vec![PathBuf::from("/d")]);
+        assert_eq!(report.deleted, vec![PathBuf::from("/c")]);
+        assert_eq!(report.modified, vec![PathBuf::from("/b")]);
+        assert!(!report.is_clean());
+        assert!(verify(&expected, &expected).is_clean());
+    }
+}
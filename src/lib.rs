@@ -0,0 +1,49 @@
+//! Библиотека анализа использования дискового пространства.
+//!
+//! Бинарный файл `diskspace` - тонкая обертка над этой библиотекой:
+//! сканирование выполняет [`scan_directory`], а модули [`report`], [`export`]
+//! и [`tips`] формируют вывод.
+
+pub mod async_scan;
+pub mod classify;
+pub mod cleanup;
+pub mod color;
+pub mod completions;
+pub mod compress;
+pub mod config;
+pub mod diff;
+pub mod dot;
+pub mod duplicates;
+pub mod export;
+pub mod filesystem;
+pub mod format;
+pub mod glob;
+pub mod html;
+pub mod integrity;
+pub mod percentiles;
+pub mod pushgateway;
+pub mod redirect;
+pub mod regex;
+pub mod report;
+pub mod scan;
+pub mod scanlog;
+pub mod sha256;
+pub mod signal;
+pub mod snapshot;
+pub mod spans;
+pub mod term;
+pub mod template;
+pub mod tips;
+pub mod trash;
+pub mod tui;
+pub mod users;
+
+pub use format::{
+    format_size, format_size_as, format_size_delta, parse_date, parse_size_str, ParseDateError, ParseSizeError, SizeFormat,
+    SizeUnit,
+};
+pub use scan::{
+    scan_directory, DepthStats, DirInfo, DirSpan, GitRepo, MimeMismatch, MtimeFilter, OwnerUsage, ScanOptions, ScanResult, ScanStats,
+    SizeFilter,
+};
+pub use tips::generate_optimization_tips;
@@ -1,241 +1,2381 @@
+use std::cell::Cell;
 use std::env;
+use std::fmt::Write as _;
 use std::fs;
-use std::io;
+use std::io::{self, BufRead, IsTerminal, Read, Write as _};
 use std::path::{Path, PathBuf};
-use std::collections::BTreeMap;
-use std::time::Instant;
-use std::cmp::Ordering;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-// Структура для хранения информации о директории
-struct DirInfo {
-    size: u64,
-    file_count: usize,
-    largest_file: Option<(PathBuf, u64)>,
-    file_types: BTreeMap<String, u64>,
+use diskspace::async_scan::{block_on, scan_directory_async_with_progress, ASYNC_MIN_JOBS};
+use diskspace::cleanup;
+use diskspace::color::{self, Color};
+use diskspace::completions::{self, Shell};
+use diskspace::config::{default_config_path, Config, ConfigError, EXAMPLE_CONFIG};
+use diskspace::diff::{diff_dirs, growth_rates, relative_dir_infos};
+use diskspace::dot::{dirs_to_dot, DEFAULT_DOT_THRESHOLD};
+use diskspace::duplicates::{fdupes_groups, find_duplicates};
+use diskspace::export::{scan_to_csv, scan_to_json, scan_to_ncdu, scan_to_prometheus, scan_to_template_context, scan_to_xml, DEFAULT_TEMPLATE};
+use diskspace::filesystem::{inode_usage, space_usage};
+use diskspace::html::HtmlReport;
+use diskspace::integrity::{hash_files, parse_sha256sum, to_sha256sum, verify};
+use diskspace::percentiles::compute_percentiles;
+use diskspace::pushgateway::{self, GatewayUrl};
+use diskspace::redirect::StdoutRedirect;
+use diskspace::report::{self, sort_dirs, DateGrouping, FindRecord, SortKey, TopDirs, TreeStyle};
+use diskspace::format::{format_count, format_du_size, format_du_size_si, format_timestamp, set_size_format, size_format, SizeFormat};
+use diskspace::scan::{scan_directory_in_pool, scan_stream, ScanProgress, WorkerPool, DEFAULT_MAX_JOBS, MTIME_FILES};
+use diskspace::scanlog::{LogLevel, ScanLog};
+use diskspace::signal;
+use diskspace::snapshot::Snapshot;
+use diskspace::spans;
+use diskspace::template::Template;
+use diskspace::tips::{self, optimization_tips, Tip};
+use diskspace::trash::{trash_dirs, DEFAULT_TRASH_THRESHOLD};
+use diskspace::users;
+use diskspace::{
+    format_size, format_size_delta, parse_date, parse_size_str, scan_directory, DirInfo, ScanOptions,
+    ScanResult, ScanStats,
+};
+
+// Параметры командной строки
+struct Options {
+    paths: Vec<PathBuf>,
+    /// Разделитель путей, читаемых из stdin (--from-stdin, --from-stdin-null)
+    from_stdin: Option<u8>,
+    scan: ScanOptions,
+    json: bool,
+    csv: bool,
+    csv_types_file: Option<PathBuf>,
+    xml: bool,
+    /// Шаблон отчета (--report-template); "default" - встроенный
+    report_template: Option<PathBuf>,
+    html: Option<PathBuf>,
+    dot: Option<PathBuf>,
+    dot_threshold: u64,
+    /// Не показывать в отчете директории не меньше этого размера
+    exclude_larger_than: Option<u64>,
+    output: Option<PathBuf>,
+    force: bool,
+    save: Option<PathBuf>,
+    quiet: bool,
+    find_format: bool,
+    /// Записи списка файлов завершаются NUL (--print0)
+    print0: bool,
+    /// Вид записей списка файлов (--find-format, --print0)
+    find_record: FindRecord,
+    tree: bool,
+    ascii: bool,
+    ncdu_export: Option<PathBuf>,
+    /// Pushgateway, куда отправляются метрики после сканирования
+    prometheus_push: Option<GatewayUrl>,
+    prometheus_job: String,
+    /// Сканировать через асинхронную точку входа (для сетевых файловых систем)
+    use_async: bool,
+    /// Потоковое сканирование с ограниченной памятью: только итог и топ директорий
+    stream: bool,
+    /// Вычислить SHA-256 всех файлов
+    hash: bool,
+    hash_output: Option<PathBuf>,
+    /// Файл хешей для проверки целостности
+    verify: Option<PathBuf>,
+    /// Вывод в формате `du -s --block-size=1`
+    du: bool,
+    /// По строке "<путь> <байты> <файлов>" на каждый путь и строка TOTAL
+    summary: bool,
+    /// Только место на файловой системе по statvfs, без сканирования
+    free: bool,
+    /// Размеры в --du с суффиксами, как `du -h`
+    human_readable: bool,
+    /// Десятичные единицы, как `df --si`: в отчетах и в --du
+    si: bool,
+    verbose: bool,
+    interactive: bool,
+    watch: Option<u64>,
+    /// Прервать сканирование через столько секунд и показать собранное
+    timeout: Option<u64>,
+    no_color: bool,
+    duplicates: bool,
+    dup_min_size: u64,
+    sort: SortKey,
+    reverse: bool,
+    bar: bool,
+    /// Колонка %DISK: доля директории от объема файловой системы
+    relative: bool,
+    bar_chart: bool,
+    bar_width: usize,
+    /// Таблица распределения по глубине вложенности
+    depth_histogram: bool,
+    /// Раздел с размером корзины перед основным отчетом
+    trash: bool,
+    /// Отмечать директории, синхронизируемые с облачными хранилищами
+    cloud_sync: bool,
+    /// Размер корзины, начиная с которого выводится предупреждение
+    trash_threshold: u64,
+    size_format: SizeFormat,
+    top_dirs: usize,
+    /// Глубина вложенности, начиная с которой выводится предупреждение
+    max_depth_limit: u32,
+    top_files: usize,
+    top_extensions: usize,
+    show_empty: bool,
+    show_zero: bool,
+    delete_empty: bool,
+    clean_temp: bool,
+    /// Правила советов по оптимизации, отключенные через --disable-rule
+    disabled_rules: Vec<String>,
+    /// Завершиться с кодом EXIT_THRESHOLD_EXCEEDED, если размер больше порога
+    exit_code_threshold: Option<u64>,
+    /// Сравнивать с порогом каждую директорию, а не только общий размер
+    exit_code_per_dir: bool,
+    /// Отчет только по числу файлов, без размеров
+    file_counts_only: bool,
+    /// Таблица пользователей вместо топа директорий
+    group_by_owner: bool,
+    /// Таблица по дням или месяцам изменения вместо топа директорий
+    group_by_date: Option<DateGrouping>,
+    /// Проценты считаются от объема файловой системы этого пути
+    anchor: Option<PathBuf>,
+    /// Вывод fdupes, по которому строится отчет о дубликатах без сканирования
+    fdupes_input: Option<PathBuf>,
+    /// Журнал сканирования: ошибки, пропущенные пути и итоговая статистика
+    log_file: Option<PathBuf>,
+    /// Раздел самых медленных директорий по времени обхода
+    tracing: bool,
+    /// Приемник OTLP/HTTP (например, Jaeger) для спанов обхода
+    tracing_endpoint: Option<GatewayUrl>,
 }
 
-impl DirInfo {
-    fn new() -> Self {
-        DirInfo {
-            size: 0,
-            file_count: 0,
-            largest_file: None,
-            file_types: BTreeMap::new(),
-        }
+const VERSION: &str = "0.2.0";
+
+// Код завершения, если суммарный размер превысил --budget
+const EXIT_BUDGET_EXCEEDED: i32 = 1;
+// Код завершения, если размер превысил --exit-code-threshold
+const EXIT_THRESHOLD_EXCEEDED: i32 = 1;
+// Код завершения при сканировании, прерванном по --timeout
+const EXIT_TIMED_OUT: i32 = 3;
+// Код завершения при сканировании, отмененном через Ctrl-C (128 + SIGINT)
+const EXIT_INTERRUPTED: i32 = 130;
+
+// Глубина, после которой --max-depth-limit предупреждает о патологической вложенности
+const DEFAULT_MAX_DEPTH_LIMIT: u32 = 1000;
+
+fn main() -> io::Result<()> {
+    // Отчет по остановленному сканированию выводится как обычно, отличается
+    // только код завершения
+    let exit_code = Cell::new(0);
+    run(&exit_code)?;
+    if exit_code.get() != 0 {
+        std::process::exit(exit_code.get());
     }
+    Ok(())
 }
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let start_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
-    } else {
-        env::current_dir()?
+fn run(exit_code: &Cell<i32>) -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    // "scan" - явная форма обычного запуска, "load" - отчет по сохраненному снимку
+    let (load_snapshot, args) = match args.first().map(String::as_str) {
+        Some("config") => return run_config_command(&args[1..]),
+        Some("config-check") => return run_config_check(&args[1..]),
+        Some("completions") => return run_completions(&args[1..]),
+        Some("compare") => return run_compare(&args[1..]),
+        Some("interactive-clean") => return run_interactive_clean(&args[1..]),
+        Some("scan") => (false, &args[1..]),
+        Some("load") => (true, &args[1..]),
+        _ => (false, &args[..]),
     };
 
-    println!("🔍 Анализ использования дискового пространства для: {:?}", start_path);
-    println!("⏳ Подождите, идет сканирование...");
+    let mut options = load_options(args).unwrap_or_else(|message| usage_error(&message));
+    set_size_format(options.size_format);
+    if let Some(delimiter) = options.from_stdin {
+        let paths = read_stdin_paths(delimiter)?;
+        if paths.is_empty() {
+            usage_error("в stdin нет ни одного пути");
+        }
+        options.paths.extend(paths);
+    }
+    if load_snapshot {
+        match options.paths.len() {
+            0 => usage_error("команде load нужен файл снимка: diskspace load СНИМОК"),
+            1 => {},
+            _ => usage_error(&format!("лишний аргумент: {}", options.paths[1].display())),
+        }
+        if options.interactive || options.watch.is_some() || options.du || options.summary || options.stream {
+            usage_error("снимок нельзя открыть в режимах --interactive, --watch, --du, --summary и --stream");
+        }
+        // Даты изменения и содержимое отдельных файлов в снимке не сохраняются
+        if options.log_file.is_some() || options.tracing {
+            usage_error("снимок не сканируется, и --log-file, --tracing и --tracing-jaeger для него недоступны");
+        }
+        if options.group_by_date.is_some() || options.scan.compress_estimate {
+            usage_error("для снимка недоступны --group-by-day, --group-by-month и --compress-estimate");
+        }
+    }
+    if options.free {
+        return run_free(&options);
+    }
+    // du допускает вложенные пути и выводит каждый как есть
+    if options.du {
+        return run_du(&mut options);
+    }
+    if options.summary {
+        return run_summary(&mut options);
+    }
+    if options.paths.len() > 1 {
+        if options.interactive || options.watch.is_some() {
+            usage_error("режимы --interactive и --watch работают только с одним путем");
+        }
+        if let Some((a, b)) = find_nested_roots(&options.paths) {
+            usage_error(&format!("пути пересекаются: {} и {}", a.display(), b.display()));
+        }
+    }
+
+    let mut roots = std::mem::take(&mut options.paths);
+    if roots.is_empty() {
+        roots.push(env::current_dir()?);
+    }
+    // Для нескольких корней общим корнем отчета служит их ближайший общий предок
+    let start_path = if roots.len() == 1 { roots[0].clone() } else { common_ancestor(&roots) };
+
+    if options.interactive {
+        if let Err(e) = diskspace::tui::run(&start_path, &options.scan) {
+            eprintln!("Ошибка: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Файл отчета проверяем до сканирования, чтобы не спрашивать после долгого ожидания
+    if let Some(output) = &options.output {
+        if output.exists() && !options.force
+            && !confirm(&format!("Файл {} уже существует. Перезаписать?", output.display()))? {
+            eprintln!("Отменено: файл {} не перезаписан", output.display());
+            std::process::exit(1);
+        }
+    }
+
+    // В файле отчета escape-последовательности цветов не нужны
+    color::set_enabled(options.output.is_none() && color::should_colorize(options.no_color));
+
+    if let Some(path) = &options.fdupes_input {
+        return run_fdupes_input(path, &options);
+    }
+    if let Some(interval) = options.watch {
+        return run_watch(&start_path, &options, interval);
+    }
+    if options.stream {
+        // Медиана выводится только в подробной таблице директорий
+        options.scan.file_size_median = options.verbose;
+        return run_stream(&roots, &options);
+    }
+
+    // В машиночитаемых режимах stdout должен содержать только сам документ
+    let machine_output = options.json || options.csv || options.xml || options.quiet || options.find_format
+        || options.report_template.is_some();
     
-    let start_time = Instant::now();
-    let mut dir_infos: BTreeMap<String, DirInfo> = BTreeMap::new();
+    // Шаблон разбираем до сканирования, чтобы сразу сообщить об ошибке в нем
+    let report_template = options.report_template.as_ref().map(|path| {
+        let text = if path.as_os_str() == "default" {
+            Ok(DEFAULT_TEMPLATE.to_string())
+        } else {
+            fs::read_to_string(path).map_err(|e| e.to_string())
+        };
+        text.and_then(|text| Template::parse(&text).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                eprintln!("Ошибка: {}: {}", path.display(), e);
+                std::process::exit(1);
+            })
+    });
+    // Объем файловой системы --anchor тоже узнаем заранее
+    let anchor_total = options.anchor.as_ref().map(|anchor| match space_usage(anchor) {
+        Some(usage) => usage.total,
+        None => {
+            eprintln!("Ошибка: не удалось получить объем файловой системы {}", anchor.display());
+            std::process::exit(1);
+        }
+    });
+    // И журнал сканирования, чтобы ошибка в его пути не стоила целого обхода
+    let mut scan_log = options.log_file.as_ref().map(|path| ScanLog::open(path).unwrap_or_else(|e| {
+        eprintln!("Ошибка: не удалось открыть журнал {}: {}", path.display(), e);
+        std::process::exit(1);
+    }));
     
-    let total_info = scan_directory(&start_path, &mut dir_infos)?;
+    // Файл хешей читаем до сканирования, чтобы не сообщать об ошибке в нем
+    // после долгого ожидания
+    let expected_hashes = options.verify.as_ref().map(|path| {
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| parse_sha256sum(&text).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                eprintln!("Ошибка: {}: {}", path.display(), e);
+                std::process::exit(1);
+            })
+    });
     
-    let elapsed = start_time.elapsed();
-    println!("\n✅ Сканирование завершено за {:.2} секунд", elapsed.as_secs_f32());
-    println!("📊 Общий размер: {} МБ ({} файлов)\n", format_size(total_info.size), total_info.file_count);
+    let mut root_summaries = Vec::new();
+    let (start_path, scanned_at, mut result, completed, stats) = if load_snapshot {
+        let snapshot = Snapshot::load(&start_path).unwrap_or_else(|e| {
+            eprintln!("Ошибка: {}: {}", start_path.display(), e);
+            std::process::exit(1);
+        });
+        let completed = format!("📂 Снимок {} от {} UTC", start_path.display(), format_timestamp(snapshot.scanned_at));
+        if !machine_output {
+            println!("🔍 Анализ использования дискового пространства для: {:?}", snapshot.root);
+        }
+        (snapshot.root, snapshot.scanned_at, snapshot.result, completed, None)
+    } else {
+        if !machine_output {
+            let names: Vec<String> = roots.iter().map(|root| format!("{:?}", root)).collect();
+            println!("🔍 Анализ использования дискового пространства для: {}", names.join(", "));
+            println!("⏳ Подождите, идет сканирование...");
+        }
+        let scanned_at = SystemTime::now();
+        start_scan_watchdog(options.timeout, &mut options.scan);
+        let mut stats = ScanStats::default();
+        let mut results = Vec::with_capacity(roots.len());
+        // Пул потоков общий для всех корней
+        let pool = WorkerPool::new(options.scan.jobs);
+        let budget = options.scan.size_budget;
+        for root in &roots {
+            let (root_result, root_stats) = run_scan(root, &mut options, &pool, machine_output, scan_log.as_mut())?;
+            stats.merge(&root_stats);
+            root_summaries.push((root.to_string_lossy().into_owned(), root_result.root.clone()));
+            // Бюджет общий для всех корней: следующим достается остаток
+            let budget_exceeded = root_result.budget_exceeded;
+            if let Some(remaining) = &mut options.scan.size_budget {
+                *remaining = remaining.saturating_sub(root_result.root.size);
+            }
+            results.push(root_result);
+            if budget_exceeded {
+                break;
+            }
+        }
+        // Ctrl-C во время отчета снова завершает процесс
+        signal::restore_default_handler();
+        let result = merge_scan_results(results);
+        let stop_warning = if result.budget_exceeded {
+            exit_code.set(EXIT_BUDGET_EXCEEDED);
+            Some(format!("⛔ [ПРЕВЫШЕН БЮДЖЕТ {}] Сканирование остановлено, показаны данные, собранные до остановки",
+                format_size(budget.unwrap_or_default(), size_format())))
+        } else if result.cancelled && signal::interrupted() {
+            exit_code.set(EXIT_INTERRUPTED);
+            Some("⚠️  [СКАНИРОВАНИЕ ОТМЕНЕНО] Ниже - частичные результаты, собранные до отмены:".to_string())
+        } else if result.cancelled {
+            exit_code.set(EXIT_TIMED_OUT);
+            Some(format!("⚠️  [СКАНИРОВАНИЕ ПРЕРВАНО ПО ТАЙМАУТУ ЧЕРЕЗ {} с] Показаны данные, собранные до остановки",
+                options.timeout.unwrap_or_default()))
+        } else {
+            None
+        };
+        let completed = if let Some(warning) = stop_warning {
+            // В машиночитаемых режимах строка итогов не выводится
+            if machine_output {
+                eprintln!("{}", warning);
+            }
+            color::paint(&warning, Color::Yellow)
+        } else {
+            format!("✅ Сканирование завершено за {:.2} секунд", stats.elapsed.as_secs_f32())
+        };
+        (start_path, scanned_at, result, completed, Some(stats))
+    };
     
-    // Сортировка по размеру (по убыванию)
-    let mut size_vec: Vec<(String, DirInfo)> = dir_infos.into_iter().collect();
-    size_vec.sort_by(|a, b| b.1.size.cmp(&a.1.size));
+    if let Some(save_path) = &options.save {
+        Snapshot::new(&start_path, scanned_at, result.clone()).save(save_path)?;
+        if !machine_output {
+            println!("💾 Снимок сохранен в {}", save_path.display());
+        }
+    }
     
-    println!("📁 ТОП ДИРЕКТОРИИ ПО РАЗМЕРУ:");
-    println!("{:<15} {:<12} {:<}", "РАЗМЕР", "ФАЙЛОВ", "ПУТЬ");
-    println!("{:-<60}", "");
+    if let Some(ncdu_path) = &options.ncdu_export {
+        fs::write(ncdu_path, scan_to_ncdu(&start_path, &result, scanned_at))?;
+        if !machine_output {
+            println!("📦 Экспорт в формате ncdu сохранен в {}", ncdu_path.display());
+        }
+    }
     
-    // Выводим топ-15 директорий по размеру
-    for (i, (path, info)) in size_vec.iter().take(15).enumerate() {
-        let icon = match i {
-            0 => "🔴",
-            1 => "🟠",
-            2 => "🟡",
-            _ => "🔹",
+    if let Some(gateway) = &options.prometheus_push {
+        match pushgateway::push(gateway, &options.prometheus_job, &scan_to_prometheus(&start_path, &result)) {
+            Ok(()) if !machine_output => println!("📡 Метрики отправлены в Pushgateway {}:{}", gateway.host, gateway.port),
+            Ok(()) => {},
+            Err(e) => {
+                eprintln!("Ошибка: не удалось отправить метрики в {}:{}: {}", gateway.host, gateway.port, e);
+                exit_code.set(1);
+            },
+        }
+    }
+    
+    if let Some(endpoint) = &options.tracing_endpoint {
+        match spans::export(endpoint, &result.spans) {
+            Ok(()) if !machine_output => println!("📡 Спаны обхода отправлены в {}:{}", endpoint.host, endpoint.port),
+            Ok(()) => {},
+            Err(e) => {
+                eprintln!("Ошибка: не удалось отправить спаны в {}:{}: {}", endpoint.host, endpoint.port, e);
+                exit_code.set(1);
+            },
+        }
+    }
+    
+    // Порог проверяется до --exclude-larger-than, которое скрывает крупные директории
+    if let Some(threshold) = options.exit_code_threshold {
+        let exceeded = if options.exit_code_per_dir {
+            std::iter::once((start_path.to_string_lossy().into_owned(), &result.root))
+                .chain(result.dir_infos.iter().map(|(path, info)| (path.clone(), info)))
+                .filter(|(_, info)| info.size > threshold)
+                .max_by_key(|(_, info)| info.size)
+        } else {
+            (result.root.size > threshold).then(|| (start_path.to_string_lossy().into_owned(), &result.root))
         };
-        
-        println!("{} {:<15} {:<12} {:<}", 
-                icon,
-                format_size(info.size), 
-                info.file_count,
-                path);
+        if let Some((path, info)) = exceeded {
+            eprintln!("Размер {} ({}) превышает порог {}", path,
+                format_size(info.size, size_format()), format_size(threshold, size_format()));
+            // Код прерванного сканирования важнее
+            if exit_code.get() == 0 {
+                exit_code.set(EXIT_THRESHOLD_EXCEEDED);
+            }
+        }
     }
     
-    // Анализ самых больших файлов
-    println!("\n📄 САМЫЕ БОЛЬШИЕ ФАЙЛЫ:");
-    println!("{:<15} {:<}", "РАЗМЕР", "ПУТЬ");
-    println!("{:-<60}", "");
+    let hashes = if options.hash { hash_files(&result.files, options.scan.jobs) } else { Vec::new() };
+    if let Some(hash_path) = &options.hash_output {
+        fs::write(hash_path, to_sha256sum(&hashes))?;
+        if !machine_output {
+            println!("🔑 Хеши SHA-256 ({} файлов) сохранены в {}", hashes.len(), hash_path.display());
+        }
+    }
+    
+    // Крупные директории скрываются из всех разделов отчета, но остаются
+    // в общем размере
+    if let Some(threshold) = options.exclude_larger_than {
+        result.dir_infos.retain(|_, info| info.size < threshold);
+    }
+    
+    if options.json {
+        return write_document(&options, &scan_to_json(&start_path, &result));
+    }
+    
+    if options.xml {
+        return write_document(&options, &scan_to_xml(&start_path, &result));
+    }
     
-    let mut largest_files: Vec<(PathBuf, u64)> = Vec::new();
-    for (_, info) in size_vec.iter() {
-        if let Some(file_info) = &info.largest_file {
-            largest_files.push(file_info.clone());
+    if options.csv {
+        let (dirs_csv, types_csv) = scan_to_csv(&result);
+        match &options.csv_types_file {
+            Some(path) => {
+                fs::write(path, types_csv)?;
+                return write_document(&options, &dirs_csv);
+            },
+            None => return write_document(&options, &format!("{}\n{}", dirs_csv, types_csv)),
         }
     }
     
-    largest_files.sort_by(|a, b| b.1.cmp(&a.1));
-    for (path, size) in largest_files.iter().take(5) {
-        println!("{:<15} {:<}", format_size(*size), path.display());
+    let tips = optimization_tips(&result, &options.disabled_rules);
+    
+    if let Some(template) = &report_template {
+        let context = scan_to_template_context(&start_path, &result, options.top_dirs, options.top_files, options.top_extensions);
+        let document = template.render(&context).unwrap_or_else(|e| {
+            eprintln!("Ошибка: {}: {}", options.report_template.as_deref().unwrap_or(Path::new("")).display(), e);
+            std::process::exit(1);
+        });
+        return write_document(&options, &document);
     }
     
-    // Анализ типов файлов
-    let mut file_type_totals: BTreeMap<String, u64> = BTreeMap::new();
-    for (_, info) in size_vec.iter() {
-        for (ext, size) in &info.file_types {
-            *file_type_totals.entry(ext.clone()).or_insert(0) += size;
+    // При фильтре по размеру показываем только директории, в которых нашлись подходящие файлы
+    let filter_active = options.scan.size_filter.is_active() || options.scan.mtime_filter.is_active()
+        || !options.scan.include.is_empty();
+    let mut size_vec: Vec<(String, DirInfo)> = std::mem::take(&mut result.dir_infos).into_iter()
+        .filter(|(_, info)| !filter_active || info.file_count > 0)
+        .collect();
+    sort_dirs(&mut size_vec, options.sort, options.reverse);
+    
+    if let Some(html_path) = &options.html {
+        write_html_report(html_path, &start_path, &result.root, &size_vec, &tips, &options)?;
+        if !options.quiet {
+            println!("\n📝 HTML-отчет сохранен в {}", html_path.display());
         }
     }
     
-    let mut file_types_vec: Vec<(String, u64)> = file_type_totals.into_iter().collect();
-    file_types_vec.sort_by(|a, b| b.1.cmp(&a.1));
+    if let Some(dot_path) = &options.dot {
+        // Корни нескольких путей не входят в dir_infos, поэтому добавляем их сводки
+        let graph_dirs: Vec<(String, DirInfo)> = root_summaries.iter().cloned().chain(size_vec.iter().cloned()).collect();
+        fs::write(dot_path, dirs_to_dot(&start_path, &result.root, &graph_dirs, options.dot_threshold))?;
+        if !machine_output {
+            println!("\n🕸  Граф директорий сохранен в {} (dot -Tpng {} -o граф.png)", dot_path.display(), dot_path.display());
+        }
+    }
     
-    println!("\n📊 ИСПОЛЬЗОВАНИЕ ПО ТИПАМ ФАЙЛОВ:");
-    println!("{:<15} {:<}", "РАЗМЕР", "ТИП");
-    println!("{:-<60}", "");
+    // Дальше печатается только сам отчет: при --output он уходит в файл
+    let _redirect = match &options.output {
+        Some(path) => Some(StdoutRedirect::to_file(&fs::File::create(path)?)?),
+        None => None,
+    };
     
-    for (ext, size) in file_types_vec.iter().take(8) {
-        let ext_name = if ext.is_empty() { "[без расширения]" } else { ext };
-        println!("{:<15} {:<}", format_size(*size), ext_name);
+    if options.quiet {
+        report::print_quiet_dirs(&size_vec, options.top_dirs);
+        return Ok(());
+    }
+    if options.tree {
+        let style = if options.ascii { TreeStyle::ASCII } else { TreeStyle::UNICODE };
+        // Корни нескольких путей не входят в dir_infos, поэтому добавляем их сводки
+        let tree_dirs: Vec<(String, DirInfo)> = root_summaries.iter().cloned().chain(size_vec.iter().cloned()).collect();
+        let min_size = options.scan.size_filter.min.unwrap_or(0);
+        report::print_tree(&start_path.to_string_lossy(), &result.root, &tree_dirs, min_size, style);
+        if let (Some(max_depth), false) = (options.scan.max_depth, load_snapshot) {
+            println!("\n{}", depth_limit_note(max_depth));
+        }
+        return Ok(());
+    }
+    if options.find_format {
+        // Закрытый канал (diskspace --find-format | head) - штатное завершение
+        return match report::print_find_format(&mut result.files, options.find_record) {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            other => other,
+        };
     }
     
-    // Советы по оптимизации
-    generate_optimization_tips(&size_vec, &largest_files);
+    if result.skipped.is_empty() {
+        println!("\n{}", completed);
+    } else {
+        println!("\n{} (пропущено путей из-за ошибок: {})", completed, result.skipped.len());
+    }
+    if options.file_counts_only {
+        print_file_count_report(&start_path, &result, &mut size_vec, &options, load_snapshot);
+        return Ok(());
+    }
+    // Снимок, сохраненный с --disk-usage, узнаем по заполненному allocated_size
+    let size_label = if options.scan.disk_usage || result.root.allocated_size > 0 { "Занято на диске (выделенные блоки)" } else { "Общий размер" };
+    match (result.sample_percent, result.sample_margin()) {
+        (Some(percent), Some(margin)) => println!("📊 {}: ~{} (выборка {}%, погрешность ±{:.0}%) (~{} файлов)", size_label,
+            format_size(result.root.size, size_format()), percent, margin * 100.0, result.root.file_count),
+        _ => println!("📊 {}: {} ({} файлов)", size_label, format_size(result.root.size, size_format()), result.root.file_count),
+    }
+    if let Some(fs_type) = &result.filesystem_type {
+        // Место на диске - текущее, поэтому для снимка выводится только тип; у
+        // нескольких корней общий предок может лежать на другом разделе
+        match space_usage(&start_path).filter(|_| !load_snapshot && root_summaries.len() <= 1) {
+            Some(usage) => println!("📊 Файловая система: {}, всего {}, свободно {}", fs_type,
+                format_size(usage.total, size_format()), format_size(usage.available, size_format())),
+            None => println!("📊 Файловая система: {}", fs_type),
+        }
+    }
+    if let Some(stats) = &stats {
+        println!("⚡ Просканировано {} файлов в {} директориях: {} файлов/с ({}/с)",
+            format_count(stats.files_scanned), format_count(stats.dirs_scanned),
+            format_count(stats.files_per_sec().round() as u64), format_size(stats.bytes_per_sec().round() as u64, size_format()));
+    }
+    let (deepest_path, deepest_depth) = &result.max_depth_path;
+    // В снимке самая глубокая директория не сохраняется
+    if !deepest_path.as_os_str().is_empty() {
+        println!("📏 Самая глубокая директория (уровень {}): {}", deepest_depth, deepest_path.display());
+        if *deepest_depth > options.max_depth_limit {
+            println!("{}", color::paint(&format!("⚠️  Вложенность превышает {} уровней: возможно, это рекурсивно распакованный архив или ошибка копирования",
+                options.max_depth_limit), Color::Yellow));
+        }
+    }
+    if result.root.zero_byte_count > 0 {
+        println!("🕳  Найдено файлов нулевого размера: {}", result.root.zero_byte_count);
+    }
+    if options.scan.count_inodes {
+        println!("🧮 Всего инодов: {}", result.root.inode_count);
+        // Для снимка файловая система могла измениться или находиться на другой машине
+        if !load_snapshot {
+            match inode_usage(&start_path) {
+                Some(usage) => report::print_inode_usage(&usage),
+                None => eprintln!("Предупреждение: не удалось получить сведения об инодах файловой системы"),
+            }
+        }
+    }
+    if let (Some(max_depth), false) = (options.scan.max_depth, load_snapshot) {
+        println!("{}", depth_limit_note(max_depth));
+    }
+    // В снимке размеры уже посчитаны и не округлялись
+    if let (Some(block_size), false) = (options.scan.block_size, load_snapshot) {
+        println!("🧱 Размеры файлов округлены вверх до целого числа блоков по {} Б", block_size);
+    }
+    if let (Some(anchor), Some(total)) = (&options.anchor, anchor_total) {
+        println!("📐 Проценты - доля от объема файловой системы {} ({})", anchor.display(), format_size(total, size_format()));
+    }
+    println!();
+    
+    // Корзина - текущее состояние машины, к снимку оно не относится
+    if options.trash && !load_snapshot {
+        report::print_trash(&scan_trash(&options), options.trash_threshold);
+        println!();
+    }
+    // С --anchor проценты всех таблиц считаются от объема его файловой системы
+    let pct_total = anchor_total.unwrap_or(result.root.size);
+    if root_summaries.len() > 1 {
+        report::print_root_summary(&root_summaries, pct_total);
+    }
+    let cloud_dirs = if options.cloud_sync { report::collect_cloud_dirs(&start_path, &size_vec) } else { Vec::new() };
+    if options.group_by_owner {
+        report::print_owners_table(&result.owners);
+    } else if let Some(grouping) = options.group_by_date {
+        report::print_date_groups(&report::group_by_date(&result.mtime_days, grouping), grouping, options.bar_width);
+    } else {
+        report::print_top_dirs(&size_vec, options.top_dirs, pct_total, options.bar, disk_total(&start_path, &options), options.verbose,
+            &cloud_dirs);
+    }
+    if options.scan.count_inodes {
+        report::print_top_inode_dirs(&size_vec, options.top_dirs, result.root.inode_count);
+    }
+    // В снимке распределение по глубине не сохраняется
+    if options.depth_histogram && !result.depth_stats.is_empty() {
+        report::print_depth_histogram(&result.depth_stats, options.bar_width);
+    }
+    if options.tracing {
+        report::print_slowest_dirs(&result.spans, options.top_dirs);
+    }
+    
+    // Анализ самых больших файлов
+    report::print_largest_files(&result.root.top_files, options.top_files);
+    // В снимке размеры отдельных файлов не сохраняются
+    if !result.file_sizes.is_empty() {
+        report::print_percentiles(&compute_percentiles(&mut result.file_sizes));
+    }
+    
+    // Корневая директория уже содержит суммарную статистику всех поддиректорий
+    report::print_categories(&result.root, options.verbose);
+    let chart_width = options.bar_chart.then_some(options.bar_width);
+    report::print_file_types(&result.root, options.top_extensions, chart_width);
+    if options.scan.compress_estimate {
+        report::print_compress_estimates(&result.compress_estimates, options.top_extensions);
+    }
+    report::print_mime_mismatches(&result.mime_mismatches);
+    
+    let (oldest, newest) = report::collect_mtime_extremes(
+        size_vec.iter().map(|(_, info)| info).chain(std::iter::once(&result.root)), MTIME_FILES);
+    report::print_mtime_extremes(&oldest, &newest);
+    
+    report::print_temp_summary(&result.root);
+    report::print_hidden_files(&result.root, options.scan.show_hidden);
+    report::print_system_files(&result.root);
+    report::print_resource_forks(&result.root);
+    report::print_time_machine_backups(&result.time_machine);
+    if options.clean_temp {
+        clean_temp_files(&result.temp_files)?;
+    }
+    
+    report::print_well_known_dirs(&report::collect_well_known_dirs(&size_vec));
+    
+    // С --group-by-owner владельцы уже выведены вместо топа директорий
+    if options.scan.collect_owners && !options.group_by_owner {
+        report::print_owner_summary(&result.owners, pct_total);
+    }
+    
+    report::print_git_repos(&result.git_repos);
+    report::print_broken_symlinks(&result.broken_symlinks);
+    report::print_circular_symlinks(&result.circular_symlinks);
+    if options.scan.collect_hard_links {
+        report::print_hard_links(&result.hard_links);
+    }
+    report::print_skipped_paths(&result.skipped);
+    if options.verbose {
+        report::print_skipped_mounts(&result.skipped_mounts);
+    }
+    // В Windows нет прав Unix, и раздел просто не выводится
+    if options.scan.collect_security && cfg!(unix) {
+        report::print_security_report(&result.world_writable, &result.suid_files);
+    }
+    
+    if options.show_zero {
+        report::print_zero_byte_files(&result.zero_byte_files);
+    }
+    if options.show_empty || options.delete_empty {
+        report::print_empty_dirs(&result.empty_dirs);
+    }
+    if options.delete_empty {
+        delete_empty_dirs(&result.empty_dirs)?;
+    }
+    
+    // Поиск дубликатов по уже собранному списку файлов
+    if options.duplicates {
+        // Список мог собираться и для других целей, с файлами любого размера
+        let candidates = std::mem::take(&mut result.files).into_iter()
+            .filter(|(_, size)| *size >= options.dup_min_size)
+            .collect();
+        report::print_duplicates(&find_duplicates(candidates));
+    }
+    
+    match &expected_hashes {
+        Some(expected) => report::print_integrity_report(&verify(expected, &hashes)),
+        None if options.hash && options.hash_output.is_none() => report::print_file_hashes(&hashes),
+        None => {},
+    }
+    
+    tips::print_tips(&tips);
     
     Ok(())
 }
 
-fn scan_directory(dir: &Path, dir_infos: &mut BTreeMap<String, DirInfo>) -> io::Result<DirInfo> {
-    let mut current_info = DirInfo::new();
-    
-    if dir.is_dir() {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_dir() {
-                // Рекурсивно обходим поддиректории
-                let subdir_info = scan_directory(&path, dir_infos)?;
-                current_info.size += subdir_info.size;
-                current_info.file_count += subdir_info.file_count;
-                
-                // Обновляем информацию о самом большом файле
-                if let Some(largest) = &subdir_info.largest_file {
-                    match &current_info.largest_file {
-                        Some(current_largest) if largest.1 > current_largest.1 => {
-                            current_info.largest_file = Some(largest.clone());
-                        },
-                        None => current_info.largest_file = Some(largest.clone()),
-                        _ => {}
-                    }
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            paths: Vec::new(),
+            from_stdin: None,
+            // Как du -x: точки монтирования пропускаются, пока нет --follow-mounts
+            scan: ScanOptions::default().one_filesystem(true),
+            json: false,
+            csv: false,
+            csv_types_file: None,
+            xml: false,
+            report_template: None,
+            html: None,
+            output: None,
+            force: false,
+            save: None,
+            quiet: false,
+            find_format: false,
+            print0: false,
+            find_record: FindRecord::Line,
+            tree: false,
+            ascii: false,
+            ncdu_export: None,
+            prometheus_push: None,
+            prometheus_job: pushgateway::DEFAULT_JOB.to_string(),
+            use_async: false,
+            stream: false,
+            hash: false,
+            hash_output: None,
+            verify: None,
+            dot: None,
+            dot_threshold: DEFAULT_DOT_THRESHOLD,
+            exclude_larger_than: None,
+            du: false,
+            summary: false,
+            free: false,
+            human_readable: false,
+            si: false,
+            verbose: false,
+            interactive: false,
+            watch: None,
+            timeout: None,
+            no_color: false,
+            duplicates: false,
+            dup_min_size: 1024 * 1024,
+            sort: SortKey::Size,
+            reverse: false,
+            bar: false,
+            relative: false,
+            max_depth_limit: DEFAULT_MAX_DEPTH_LIMIT,
+            bar_chart: false,
+            depth_histogram: false,
+            trash: false,
+            cloud_sync: false,
+            trash_threshold: DEFAULT_TRASH_THRESHOLD,
+            bar_width: 30,
+            size_format: SizeFormat::IEC,
+            top_dirs: 15,
+            top_files: 5,
+            top_extensions: 8,
+            show_empty: false,
+            show_zero: false,
+            delete_empty: false,
+            clean_temp: false,
+            disabled_rules: Vec::new(),
+            exit_code_threshold: None,
+            exit_code_per_dir: false,
+            file_counts_only: false,
+            group_by_owner: false,
+            group_by_date: None,
+            anchor: None,
+            fdupes_input: None,
+            log_file: None,
+            tracing: false,
+            tracing_endpoint: None,
+        }
+    }
+}
+
+// Сообщает об ошибке в аргументах и завершает процесс с кодом 2
+fn usage_error(message: &str) -> ! {
+    eprintln!("Ошибка: {}", message);
+    eprintln!("Используйте --help для просмотра доступных опций.");
+    std::process::exit(2);
+}
+
+// Собирает параметры: значения по умолчанию, затем файл конфигурации,
+// затем командная строка, которая имеет наивысший приоритет
+fn load_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    let (config_path, explicit) = match config_path_from_args(args)? {
+        ConfigSource::Disabled => (None, false),
+        ConfigSource::Path(path) => (Some(path), true),
+        ConfigSource::Default => (default_config_path(), false),
+    };
+    if let Some(path) = config_path {
+        // Отсутствие файла по пути по умолчанию - не ошибка
+        if explicit || path.exists() {
+            let config = read_config(&path)?;
+            warn_config_keys(&path, &config);
+            apply_config(&mut options, &config)?;
+        }
+    }
+
+    parse_args(args, options)
+}
+
+fn read_config(path: &Path) -> Result<Config, String> {
+    Config::load(path).map_err(|e| match e {
+        ConfigError::Io(..) => e.to_string(),
+        ConfigError::Parse { .. } => format!("{}: {}", path.display(), e),
+    })
+}
+
+fn warn_config_keys(path: &Path, config: &Config) {
+    for key in &config.unknown_keys {
+        eprintln!("Предупреждение: неизвестный ключ в {}: {}", path.display(), key);
+    }
+    for (key, canonical) in &config.deprecated_keys {
+        eprintln!("Предупреждение: устаревшее имя ключа в {}: {}, используйте {}", path.display(), key, canonical);
+    }
+}
+
+// Значения настроек, которые можно задать в файле конфигурации, в порядке config::KEYS
+fn config_settings(options: &Options) -> Vec<(&'static str, String)> {
+    let size = |size: Option<u64>| size.map_or_else(|| "-".to_string(), |size| format_size(size, size_format()));
+    vec![
+        ("exclude", options.scan.exclude.patterns().join(", ")),
+        ("top_dirs", options.top_dirs.to_string()),
+        ("top_files", options.top_files.to_string()),
+        ("top_extensions", options.top_extensions.to_string()),
+        ("sort", options.sort.name().to_string()),
+        ("reverse", options.reverse.to_string()),
+        ("jobs", options.scan.jobs.to_string()),
+        ("max_depth", options.scan.max_depth.map_or_else(|| "-".to_string(), |depth| depth.to_string())),
+        ("min_size", size(options.scan.size_filter.min)),
+        ("max_size", size(options.scan.size_filter.max)),
+        ("follow_symlinks", options.scan.follow_symlinks.to_string()),
+        ("duplicates", options.duplicates.to_string()),
+        ("dup_min_size", size(Some(options.dup_min_size))),
+    ]
+}
+
+enum ConfigSource {
+    Default,
+    Path(PathBuf),
+    Disabled,
+}
+
+// Файл конфигурации нужно выбрать до разбора остальных опций
+fn config_path_from_args(args: &[String]) -> Result<ConfigSource, String> {
+    let mut source = ConfigSource::Default;
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == "--no-config" {
+            source = ConfigSource::Disabled;
+        } else if arg == "--config" {
+            let value = take_value(args, &mut i, arg, None)?;
+            if !matches!(source, ConfigSource::Disabled) {
+                source = ConfigSource::Path(PathBuf::from(value));
+            }
+        } else if let Some(value) = arg.strip_prefix("--config=") {
+            if !matches!(source, ConfigSource::Disabled) {
+                source = ConfigSource::Path(PathBuf::from(value));
+            }
+        }
+        i += 1;
+    }
+    Ok(source)
+}
+
+fn apply_config(options: &mut Options, config: &Config) -> Result<(), String> {
+    for pattern in &config.exclude {
+        options.scan.exclude.add(pattern).map_err(|e| format!("exclude: {}", e))?;
+    }
+    if let Some(n) = config.top_dirs { options.top_dirs = n; }
+    if let Some(n) = config.top_files { options.top_files = n; }
+    if let Some(n) = config.top_extensions { options.top_extensions = n; }
+    if let Some(key) = config.sort { options.sort = key; }
+    if let Some(reverse) = config.reverse { options.reverse = reverse; }
+    if let Some(jobs) = config.jobs { options.scan.jobs = jobs; }
+    if config.max_depth.is_some() { options.scan.max_depth = config.max_depth; }
+    if config.min_size.is_some() { options.scan.size_filter.min = config.min_size; }
+    if config.max_size.is_some() { options.scan.size_filter.max = config.max_size; }
+    if let Some(follow) = config.follow_symlinks { options.scan.follow_symlinks = follow; }
+    if let Some(duplicates) = config.duplicates { options.duplicates = duplicates; }
+    if let Some(size) = config.dup_min_size { options.dup_min_size = size; }
+    Ok(())
+}
+
+fn parse_args(args: &[String], mut options: Options) -> Result<Options, String> {
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        // Поддерживаем как "--jobs 4", так и "--jobs=4"
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+
+        match name {
+            "--help" => {
+                print_help();
+                std::process::exit(0);
+            },
+            "-V" | "--version" => {
+                println!("{}", version_string());
+                std::process::exit(0);
+            },
+            // Файл конфигурации уже выбран в config_path_from_args
+            "--config" => {
+                take_value(args, &mut i, name, inline_value)?;
+            },
+            "--no-config" => {},
+            "--json" => options.json = true,
+            "--csv" => options.csv = true,
+            "--xml" => options.xml = true,
+            "--report-template" => options.report_template = Some(PathBuf::from(take_value(args, &mut i, name, inline_value)?)),
+            "-q" | "--quiet" => options.quiet = true,
+            "--find-format" => options.find_format = true,
+            "--print0" => options.print0 = true,
+            "--tree" => options.tree = true,
+            "--ascii" => options.ascii = true,
+            "--du" => options.du = true,
+            "--summary" => options.summary = true,
+            "--free" => options.free = true,
+            "--fdupes-input" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.fdupes_input = Some(PathBuf::from(value));
+            },
+            "--tracing" => {
+                options.tracing = true;
+                options.scan.collect_spans = true;
+            },
+            "--tracing-jaeger" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.tracing_endpoint = Some(GatewayUrl::parse(&value).map_err(|e| format!("{}: {}", name, e))?);
+                options.tracing = true;
+                options.scan.collect_spans = true;
+            },
+            "--log-file" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.log_file = Some(PathBuf::from(value));
+            },
+            // Обычный отчет и так выводит читаемые размеры, флаг влияет только на --du
+            "-h" | "--human-readable" => options.human_readable = true,
+            "--block-size" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                let block_size = match parse_size_str(&value) {
+                    Ok(size) if size > 0 => size,
+                    _ => return Err(format!("некорректный размер блока: {}", value)),
+                };
+                options.scan.block_size = Some(block_size);
+                options.size_format = SizeFormat::Blocks(block_size);
+            },
+            "--si" => {
+                options.si = true;
+                options.size_format = SizeFormat::SI;
+            },
+            // Каждый -v повышает подробность трассировки обхода в stderr
+            "-v" | "--verbose" | "-vv" => {
+                options.verbose = true;
+                let level = if name == "-vv" { 2 } else { 1 };
+                options.scan.trace = options.scan.trace.saturating_add(level);
+            },
+            "-i" | "--interactive" => options.interactive = true,
+            "--watch" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.watch = match value.parse::<u64>() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => return Err(format!("некорректный интервал наблюдения: {}", value)),
+                };
+            },
+            "--budget" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.scan.size_budget = Some(parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?);
+            },
+            "--exit-code-threshold" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.exit_code_threshold = Some(parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?);
+            },
+            "--exit-code-per-dir" => options.exit_code_per_dir = true,
+            "--sample" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                match value.parse::<u8>() {
+                    Ok(percent @ 1..=100) => options.scan.sample_percent = Some(percent),
+                    _ => return Err(format!("{}: ожидается процент от 1 до 100: {}", name, value)),
+                }
+            },
+            "--timeout" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.timeout = match value.parse::<u64>() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => return Err(format!("некорректный таймаут: {}", value)),
+                };
+            },
+            "--save" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.save = Some(PathBuf::from(value));
+            },
+            "--html" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.html = Some(PathBuf::from(value));
+            },
+            "--dot" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.dot = Some(PathBuf::from(value));
+            },
+            "--trash" => options.trash = true,
+            "--cloud-sync" => options.cloud_sync = true,
+            "--trash-threshold" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.trash_threshold = parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?;
+            },
+            "--dot-threshold" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.dot_threshold = parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?;
+            },
+            "--output" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.output = Some(PathBuf::from(value));
+            },
+            "--force" => options.force = true,
+            "--ncdu-export" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.ncdu_export = Some(PathBuf::from(value));
+            },
+            "--prometheus-push" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.prometheus_push = Some(GatewayUrl::parse(&value).map_err(|e| format!("{}: {}", name, e))?);
+            },
+            "--prometheus-job" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                if value.is_empty() {
+                    return Err(format!("{}: имя задания не может быть пустым", name));
+                }
+                options.prometheus_job = value;
+            },
+            "--no-color" => options.no_color = true,
+            "--csv-types-file" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.csv_types_file = Some(PathBuf::from(value));
+            },
+            "-d" | "--max-depth" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.scan.max_depth = Some(value.parse::<u32>()
+                    .map_err(|_| format!("некорректная глубина: {}", value))?);
+            },
+            "--max-depth-limit" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.max_depth_limit = value.parse::<u32>()
+                    .map_err(|_| format!("некорректная глубина: {}", value))?;
+            },
+            "--exclude-larger-than" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.exclude_larger_than = Some(parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?);
+            },
+            "--min-size" | "--max-size" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                let size = parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?;
+                if name == "--min-size" {
+                    options.scan.size_filter.min = Some(size);
+                } else {
+                    options.scan.size_filter.max = Some(size);
+                }
+            },
+            "--newer-than" | "--older-than" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                let date = parse_date(&value).map_err(|e| format!("{}: {}", name, e))?;
+                if name == "--newer-than" {
+                    options.scan.mtime_filter.newer_than = Some(date);
+                } else {
+                    options.scan.mtime_filter.older_than = Some(date);
                 }
-                
-                // Сохраняем информацию о поддиректории
-                if let Some(path_str) = path.to_str() {
-                    dir_infos.insert(path_str.to_string(), subdir_info);
+            },
+            "--sort" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.sort = SortKey::parse(&value)
+                    .ok_or_else(|| format!("неизвестный ключ сортировки: {} (size, name, count, largest-file, percent, disk)", value))?;
+            },
+            "-r" | "--reverse" => options.reverse = true,
+            "--bar" => options.bar = true,
+            "--relative" => options.relative = true,
+            "--anchor" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.anchor = Some(PathBuf::from(value));
+            },
+            "--bar-chart" => options.bar_chart = true,
+            "--depth-histogram" => options.depth_histogram = true,
+            "--size-format" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.size_format = SizeFormat::parse(&value)
+                    .ok_or_else(|| format!("неизвестная система единиц: {} (допустимы iec, si, bytes)", value))?;
+            },
+            "--bar-width" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.bar_width = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("некорректная ширина полосок: {}", value)),
+                };
+            },
+            "--show-empty" => options.show_empty = true,
+            "--show-zero" => {
+                options.show_zero = true;
+                options.scan.collect_zero_byte_files = true;
+            }
+            "--delete-empty" => options.delete_empty = true,
+            "--clean-temp" => {
+                options.clean_temp = true;
+                options.scan.collect_temp_files = true;
+            },
+            "--exclude" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.scan.exclude.add(&value).map_err(|e| format!("{}: {}", name, e))?;
+            },
+            "--regex-exclude" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.scan.regex_exclude.add(&value).map_err(|e| format!("{}: {}", name, e))?;
+            },
+            "--disable-rule" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                if !tips::builtin_rules().iter().any(|rule| rule.name() == value) {
+                    return Err(format!("{}: неизвестное правило '{}' (доступны: {})", name, value, tips::builtin_rule_names()));
+                }
+                options.disabled_rules.push(value);
+            },
+            "--include" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.scan.include.add(&value).map_err(|e| format!("{}: {}", name, e))?;
+            },
+            "-n" | "--top-dirs" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.top_dirs = parse_count(name, &value)?;
+            },
+            "--top-files" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.top_files = parse_count(name, &value)?;
+            },
+            "--top-extensions" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.top_extensions = parse_count(name, &value)?;
+            },
+            "--follow-symlinks" => options.scan.follow_symlinks = true,
+            "--no-follow-symlinks" => options.scan.follow_symlinks = false,
+            "--skip-git" => options.scan.skip_git = true,
+            "--inodes" => options.scan.count_inodes = true,
+            "--show-file-counts-only" => options.file_counts_only = true,
+            "--disk-usage" => options.scan.disk_usage = true,
+            "--fail-on-error" => options.scan.fail_on_error = true,
+            "-x" | "--one-filesystem" | "--no-follow-mounts" => options.scan.one_filesystem = true,
+            "--follow-mounts" => options.scan.one_filesystem = false,
+            "--include-virtual" => options.scan.include_virtual = true,
+            "--show-hidden" => options.scan.show_hidden = true,
+            "--owner" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                if !cfg!(unix) {
+                    return Err("--owner поддерживается только в Unix".to_string());
                 }
-            } else if path.is_file() {
-                // Получаем размер файла
-                if let Ok(metadata) = fs::metadata(&path) {
-                    let file_size = metadata.len();
-                    current_info.size += file_size;
-                    current_info.file_count += 1;
-                    
-                    // Обновляем информацию о самом большом файле
-                    match &current_info.largest_file {
-                        Some(largest) if file_size > largest.1 => {
-                            current_info.largest_file = Some((path.clone(), file_size));
-                        },
-                        None => current_info.largest_file = Some((path.clone(), file_size)),
-                        _ => {}
-                    }
-                    
-                    // Обновляем статистику по типам файлов
-                    let extension = path.extension()
-                        .and_then(|e| e.to_str())
-                        .unwrap_or("")
-                        .to_lowercase();
-                    
-                    *current_info.file_types.entry(extension).or_insert(0) += file_size;
+                options.scan.owner = Some(users::uid_by_name(&value)
+                    .ok_or_else(|| format!("неизвестный пользователь: {}", value))?);
+            },
+            "--from-stdin" => options.from_stdin = Some(b'\n'),
+            "--from-stdin-null" => options.from_stdin = Some(0),
+            "--async" => options.use_async = true,
+            "--stream" => options.stream = true,
+            "--hash" => options.hash = true,
+            "--hash-output" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.hash_output = Some(PathBuf::from(value));
+                options.hash = true;
+            },
+            "--verify" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.verify = Some(PathBuf::from(value));
+                options.hash = true;
+            },
+            "--mime-classify" => options.scan.mime_classify = true,
+            "--security-report" => options.scan.collect_security = true,
+            "--owner-summary" => {
+                if !cfg!(unix) {
+                    return Err("--owner-summary поддерживается только в Unix".to_string());
                 }
+                options.scan.collect_owners = true;
+            },
+            "--group-by-owner" => {
+                if !cfg!(unix) {
+                    return Err("--group-by-owner поддерживается только в Unix".to_string());
+                }
+                options.scan.collect_owners = true;
+                options.group_by_owner = true;
+            },
+            "--compress-estimate" => options.scan.compress_estimate = true,
+            "--group-by-day" => {
+                options.group_by_date = Some(DateGrouping::Day);
+                options.scan.collect_mtime_days = true;
+            },
+            "--group-by-month" => {
+                options.group_by_date = Some(DateGrouping::Month);
+                options.scan.collect_mtime_days = true;
+            },
+            "--skip-macos-metadata" => options.scan.skip_macos_metadata = true,
+            "--count-hardlinks" => options.scan.count_hardlinks = true,
+            "--show-hardlinks" => options.scan.collect_hard_links = true,
+            "--duplicates" => options.duplicates = true,
+            "--dup-min-size" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.dup_min_size = parse_size_str(&value).map_err(|e| format!("{}: {}", name, e))?;
+            },
+            "-j" | "--jobs" => {
+                let value = take_value(args, &mut i, name, inline_value)?;
+                options.scan.jobs = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("некорректное число потоков: {}", value)),
+                };
+            },
+            _ if name.starts_with('-') && name.len() > 1 => {
+                return Err(format!("неизвестная опция: {}", arg));
+            },
+            _ => {
+                options.paths.push(PathBuf::from(arg));
             }
         }
+        i += 1;
     }
-    
-    Ok(current_info)
+
+    // --print0 тоже выводит список файлов, но без --find-format - только пути,
+    // как find -print0
+    if options.print0 {
+        options.find_record = if options.find_format { FindRecord::SizeNul } else { FindRecord::PathNul };
+        options.find_format = true;
+    }
+    if let (Some(min), Some(max)) = (options.scan.size_filter.min, options.scan.size_filter.max) {
+        if min > max {
+            return Err("значение --min-size больше значения --max-size".to_string());
+        }
+    }
+    if let (Some(newer), Some(older)) = (options.scan.mtime_filter.newer_than, options.scan.mtime_filter.older_than) {
+        if newer >= older {
+            return Err("дата --newer-than не раньше даты --older-than: под фильтр не попадет ни один файл".to_string());
+        }
+    }
+    if [options.json, options.csv, options.xml].iter().filter(|&&on| on).count() > 1 {
+        return Err("опции --json, --csv и --xml нельзя использовать одновременно".to_string());
+    }
+    if options.quiet && (options.json || options.csv || options.xml) {
+        return Err("опцию --quiet нельзя использовать вместе с --json, --csv или --xml".to_string());
+    }
+    if options.find_format && (options.json || options.csv || options.xml || options.quiet) {
+        return Err("опцию --find-format нельзя использовать вместе с --json, --csv, --xml или --quiet".to_string());
+    }
+    if options.report_template.is_some() && (options.json || options.csv || options.xml || options.quiet || options.find_format
+        || options.tree || options.du || options.free || options.interactive || options.watch.is_some() || options.stream) {
+        return Err("опцию --report-template нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.tree && (options.json || options.csv || options.xml || options.quiet || options.find_format) {
+        return Err("опцию --tree нельзя использовать вместе с --json, --csv, --xml, --quiet или --find-format".to_string());
+    }
+    if options.du && (options.json || options.csv || options.xml || options.quiet || options.find_format
+        || options.html.is_some() || options.interactive || options.watch.is_some()) {
+        return Err("опцию --du нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.summary && (options.json || options.csv || options.xml || options.quiet || options.find_format || options.tree
+        || options.html.is_some() || options.interactive || options.watch.is_some() || options.du || options.stream
+        || options.report_template.is_some()) {
+        return Err("опцию --summary нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.free && (options.json || options.csv || options.xml || options.quiet || options.find_format || options.tree
+        || options.html.is_some() || options.output.is_some() || options.interactive || options.watch.is_some() || options.du
+        || options.stream) {
+        return Err("опцию --free нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.interactive && (options.json || options.csv || options.xml || options.quiet || options.find_format
+        || options.html.is_some()) {
+        return Err("опцию --interactive нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.watch.is_some() && (options.json || options.csv || options.xml || options.quiet || options.find_format
+        || options.html.is_some() || options.interactive) {
+        return Err("опцию --watch нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.stream && (options.json || options.csv || options.xml || options.quiet || options.find_format || options.tree
+        || options.html.is_some() || options.output.is_some() || options.interactive || options.watch.is_some() || options.du) {
+        return Err("опцию --stream нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.timeout.is_some() && (options.interactive || options.watch.is_some() || options.du) {
+        return Err("опцию --timeout нельзя использовать вместе с --interactive, --watch и --du".to_string());
+    }
+    if options.scan.size_budget.is_some() && (options.interactive || options.watch.is_some() || options.du) {
+        return Err("опцию --budget нельзя использовать вместе с --interactive, --watch и --du".to_string());
+    }
+    if options.file_counts_only && (options.json || options.csv || options.xml || options.quiet || options.find_format || options.tree
+        || options.html.is_some() || options.report_template.is_some() || options.interactive || options.watch.is_some() || options.du
+        || options.summary || options.free || options.stream) {
+        return Err("опцию --show-file-counts-only нельзя использовать вместе с другими режимами вывода".to_string());
+    }
+    if options.fdupes_input.is_some() && (!options.paths.is_empty() || options.json || options.csv || options.xml || options.quiet
+        || options.find_format || options.tree || options.html.is_some() || options.report_template.is_some() || options.interactive
+        || options.watch.is_some() || options.du || options.summary || options.free || options.stream) {
+        return Err("опция --fdupes-input не сканирует пути и не сочетается с другими режимами вывода".to_string());
+    }
+    if options.anchor.is_some() && (options.interactive || options.watch.is_some() || options.du || options.summary || options.free
+        || options.stream || options.file_counts_only) {
+        return Err("опцию --anchor нельзя использовать вместе с --interactive, --watch, --du, --summary, --free, --stream и --show-file-counts-only".to_string());
+    }
+    if options.group_by_owner && (options.interactive || options.watch.is_some() || options.du || options.summary || options.free
+        || options.stream || options.file_counts_only) {
+        return Err("опцию --group-by-owner нельзя использовать вместе с --interactive, --watch, --du, --summary, --free, --stream и --show-file-counts-only".to_string());
+    }
+    if options.group_by_date.is_some() && (options.group_by_owner || options.interactive || options.watch.is_some() || options.du
+        || options.summary || options.free || options.stream || options.file_counts_only) {
+        return Err("опции --group-by-day и --group-by-month нельзя использовать вместе с --group-by-owner, --interactive, --watch, --du, --summary, --free, --stream и --show-file-counts-only".to_string());
+    }
+    if options.tracing && (options.interactive || options.watch.is_some() || options.du || options.summary || options.free
+        || options.stream || options.fdupes_input.is_some()) {
+        return Err("опции --tracing и --tracing-jaeger нельзя использовать вместе с --interactive, --watch, --du, --summary, --free, --stream и --fdupes-input".to_string());
+    }
+    if options.log_file.is_some() && (options.interactive || options.watch.is_some() || options.du || options.summary || options.free
+        || options.stream || options.fdupes_input.is_some()) {
+        return Err("опцию --log-file нельзя использовать вместе с --interactive, --watch, --du, --summary, --free, --stream и --fdupes-input".to_string());
+    }
+    if options.exit_code_per_dir && options.exit_code_threshold.is_none() {
+        return Err("опция --exit-code-per-dir требует --exit-code-threshold".to_string());
+    }
+    if options.exit_code_threshold.is_some() && (options.interactive || options.watch.is_some() || options.du || options.summary
+        || options.free || options.stream) {
+        return Err("опцию --exit-code-threshold нельзя использовать вместе с --interactive, --watch, --du, --summary, --free и --stream".to_string());
+    }
+    if options.scan.sample_percent.is_some() && (options.hash || options.verify.is_some() || options.duplicates) {
+        return Err("опцию --sample нельзя использовать вместе с --hash, --verify и --duplicates: им нужны все файлы".to_string());
+    }
+    if options.html.is_some() && (options.json || options.csv || options.xml) {
+        return Err("опцию --html нельзя использовать вместе с --json, --csv или --xml".to_string());
+    }
+    if options.output.is_some() && (options.interactive || options.watch.is_some()) {
+        return Err("опцию --output нельзя использовать вместе с --interactive и --watch".to_string());
+    }
+    if options.output.is_some() && (options.delete_empty || options.clean_temp) {
+        return Err("опцию --output нельзя использовать вместе с --delete-empty и --clean-temp".to_string());
+    }
+    if options.from_stdin.is_some() && options.interactive {
+        return Err("опцию --from-stdin нельзя использовать вместе с --interactive".to_string());
+    }
+    if options.csv_types_file.is_some() && !options.csv {
+        return Err("опция --csv-types-file используется только вместе с --csv".to_string());
+    }
+
+    Ok(options)
 }
 
-fn format_size(size: u64) -> String {
-    if size < 1024 {
-        format!("{} Б", size)
-    } else if size < 1024 * 1024 {
-        format!("{:.1} КБ", size as f64 / 1024.0)
-    } else if size < 1024 * 1024 * 1024 {
-        format!("{:.1} МБ", size as f64 / (1024.0 * 1024.0))
+// Возвращает значение опции: из "--opt=value" или из следующего аргумента
+fn take_value(args: &[String], i: &mut usize, name: &str, inline_value: Option<String>) -> Result<String, String> {
+    match inline_value {
+        Some(value) => Ok(value),
+        None => {
+            *i += 1;
+            args.get(*i).cloned().ok_or_else(|| format!("опция {} требует значение", name))
+        }
+    }
+}
+
+fn parse_count(name: &str, value: &str) -> Result<usize, String> {
+    value.parse::<usize>().map_err(|_| format!("{}: некорректное число: {}", name, value))
+}
+
+// "diskspace 0.2.0 (git:abc1234 2025-01-15, rustc 1.77.0)". Хеш коммита, дата
+// сборки и версия компилятора передаются через переменные окружения при
+// компиляции; если их не было, версия выводится без них
+fn version_string() -> String {
+    let source: Vec<String> = [
+        option_env!("DISKSPACE_GIT_HASH").map(|hash| format!("git:{}", hash)),
+        option_env!("DISKSPACE_BUILD_DATE").map(str::to_string),
+    ].into_iter().flatten().collect();
+    let mut build = Vec::new();
+    if !source.is_empty() {
+        build.push(source.join(" "));
+    }
+    if let Some(rustc) = option_env!("DISKSPACE_RUSTC_VERSION") {
+        build.push(format!("rustc {}", rustc));
+    }
+    if build.is_empty() {
+        format!("diskspace {}", VERSION)
     } else {
-        format!("{:.2} ГБ", size as f64 / (1024.0 * 1024.0 * 1024.0))
+        format!("diskspace {} ({})", VERSION, build.join(", "))
+    }
+}
+
+fn print_help() {
+    print!("{}", help_text());
+}
+
+// Текст справки; из него же берется список опций для автодополнения
+fn help_text() -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Использование: diskspace [ОПЦИИ] [ПУТЬ...]");
+    let _ = writeln!(out, "       diskspace scan [ОПЦИИ] [ПУТЬ...]");
+    let _ = writeln!(out, "       diskspace load СНИМОК [ОПЦИИ]");
+    let _ = writeln!(out, "       diskspace compare ЛЕВАЯ ПРАВАЯ [--threshold РАЗМЕР] [ОПЦИИ]");
+    let _ = writeln!(out, "       diskspace config --init");
+    let _ = writeln!(out, "       diskspace config-check [--config ФАЙЛ] [ОПЦИИ]");
+    let _ = writeln!(out, "       diskspace completions ОБОЛОЧКА");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Опции:");
+    let _ = writeln!(out, "  -d, --max-depth ЧИСЛО");
+    let _ = writeln!(out, "                      Ограничить глубину сканирования (0 - только сам путь, 1 - прямые поддиректории)");
+    let _ = writeln!(out, "      --max-depth-limit ЧИСЛО");
+    let _ = writeln!(out, "                      Предупредить о директориях глубже ЧИСЛА уровней (по умолчанию {})", DEFAULT_MAX_DEPTH_LIMIT);
+    let _ = writeln!(out, "      --from-stdin    Прочитать пути для сканирования из stdin, по одному на строку");
+    let _ = writeln!(out, "      --from-stdin-null");
+    let _ = writeln!(out, "                      То же, но пути разделены NUL (find -print0)");
+    let _ = writeln!(out, "  -j, --jobs ЧИСЛО    Число потоков сканирования: 1 - строго последовательно (по умолчанию -");
+    let _ = writeln!(out, "                      число логических ядер, но не больше {})", DEFAULT_MAX_JOBS);
+    let _ = writeln!(out, "      --async         Асинхронное сканирование с {} и более одновременными запросами", ASYNC_MIN_JOBS);
+    let _ = writeln!(out, "                      (для NFS, SMB и других файловых систем с большой задержкой)");
+    let _ = writeln!(out, "      --stream        Потоковое сканирование огромных деревьев с ограниченной памятью:");
+    let _ = writeln!(out, "                      выводятся только общий размер и топ директорий");
+    let _ = writeln!(out, "      --timeout СЕКУНДЫ");
+    let _ = writeln!(out, "                      Прервать сканирование через СЕКУНДЫ и показать собранные данные");
+    let _ = writeln!(out, "                      (код завершения {})", EXIT_TIMED_OUT);
+    let _ = writeln!(out, "      --budget РАЗМЕР Остановить сканирование, как только общий размер превысит РАЗМЕР,");
+    let _ = writeln!(out, "                      и завершиться с кодом {} (проверка квот в CI)", EXIT_BUDGET_EXCEEDED);
+    let _ = writeln!(out, "      --exit-code-threshold РАЗМЕР");
+    let _ = writeln!(out, "                      Завершиться с кодом {}, если общий размер больше РАЗМЕРА", EXIT_THRESHOLD_EXCEEDED);
+    let _ = writeln!(out, "      --exit-code-per-dir");
+    let _ = writeln!(out, "                      Сравнивать с --exit-code-threshold каждую директорию");
+    let _ = writeln!(out, "      --sample ПРОЦЕНТ");
+    let _ = writeln!(out, "                      Оценить размер по случайной выборке ПРОЦЕНТА файлов (1-100) вместо");
+    let _ = writeln!(out, "                      чтения метаданных каждого файла");
+    let _ = writeln!(out, "      --min-size РАЗМЕР");
+    let _ = writeln!(out, "                      Учитывать только файлы не меньше РАЗМЕРА (например, 500M)");
+    let _ = writeln!(out, "      --max-size РАЗМЕР");
+    let _ = writeln!(out, "                      Учитывать только файлы не больше РАЗМЕРА (например, 2G)");
+    let _ = writeln!(out, "      --exclude-larger-than РАЗМЕР");
+    let _ = writeln!(out, "                      Скрыть из отчета директории не меньше РАЗМЕРА (в общем размере");
+    let _ = writeln!(out, "                      они учитываются)");
+    let _ = writeln!(out, "      --newer-than ДАТА");
+    let _ = writeln!(out, "                      Учитывать только файлы, измененные начиная с ДАТЫ (2024-01-15, 7d, 2w, 1y)");
+    let _ = writeln!(out, "      --older-than ДАТА");
+    let _ = writeln!(out, "                      Учитывать только файлы, измененные раньше ДАТЫ");
+    let _ = writeln!(out, "      --size-format ЕДИНИЦЫ");
+    let _ = writeln!(out, "                      Единицы размеров: iec (КиБ, МиБ; по умолчанию), si (кБ, МБ), bytes");
+    let _ = writeln!(out, "      --block-size РАЗМЕР");
+    let _ = writeln!(out, "                      Округлять размер каждого файла вверх до целого числа блоков (кластеров)");
+    let _ = writeln!(out, "                      этого размера и выводить размеры в блоках, как du --block-size");
+    let _ = writeln!(out, "      --si            Десятичные единицы (1 кБ = 1000 Б), как df --si: то же, что");
+    let _ = writeln!(out, "                      --size-format si, а в --du - суффиксы k, M, G, как du --si");
+    let _ = writeln!(out, "      --sort КЛЮЧ     Сортировка директорий: size, name, count, largest-file, percent, disk");
+    let _ = writeln!(out, "  -r, --reverse       Обратный порядок сортировки");
+    let _ = writeln!(out, "      --bar           Показать долю директорий полосками по ширине терминала");
+    let _ = writeln!(out, "      --anchor ПУТЬ   Считать проценты от объема файловой системы ПУТИ, а не от общего размера");
+    let _ = writeln!(out, "      --relative      Колонка %DISK: доля каждой директории от объема файловой системы");
+    let _ = writeln!(out, "      --bar-chart     Показать диаграмму по типам файлов");
+    let _ = writeln!(out, "      --depth-histogram");
+    let _ = writeln!(out, "                      Показать число директорий, файлов и размер на каждом уровне вложенности");
+    let _ = writeln!(out, "      --bar-width ЧИСЛО");
+    let _ = writeln!(out, "                      Ширина полосок диаграммы по типам файлов (по умолчанию 30)");
+    let _ = writeln!(out, "  -n, --top-dirs ЧИСЛО");
+    let _ = writeln!(out, "                      Количество отображаемых директорий (по умолчанию 15, 0 - все)");
+    let _ = writeln!(out, "      --top-files ЧИСЛО");
+    let _ = writeln!(out, "                      Количество отображаемых файлов (по умолчанию 5, 0 - все)");
+    let _ = writeln!(out, "      --top-extensions ЧИСЛО");
+    let _ = writeln!(out, "                      Количество отображаемых типов файлов (по умолчанию 8, 0 - все)");
+    let _ = writeln!(out, "      --show-empty    Показать пустые директории");
+    let _ = writeln!(out, "      --show-zero     Показать файлы нулевого размера");
+    let _ = writeln!(out, "      --delete-empty  Удалить пустые директории (с подтверждением для каждой)");
+    let _ = writeln!(out, "      --clean-temp    Удалить временные файлы (с подтверждением для каждого)");
+    let _ = writeln!(out, "      --exclude ШАБЛОН");
+    let _ = writeln!(out, "                      Пропускать пути по glob-шаблону (можно указать несколько раз)");
+    let _ = writeln!(out, "      --regex-exclude ВЫРАЖЕНИЕ");
+    let _ = writeln!(out, "                      Пропускать пути, абсолютный путь которых совпадает с регулярным");
+    let _ = writeln!(out, "                      выражением (можно указать несколько раз)");
+    let _ = writeln!(out, "      --include ШАБЛОН");
+    let _ = writeln!(out, "                      Учитывать только файлы по glob-шаблону (можно указать несколько раз)");
+    let _ = writeln!(out, "      --follow-symlinks");
+    let _ = writeln!(out, "                      Переходить по символическим ссылкам");
+    let _ = writeln!(out, "      --no-follow-symlinks");
+    let _ = writeln!(out, "                      Не переходить по символическим ссылкам (по умолчанию)");
+    let _ = writeln!(out, "      --skip-git      Не учитывать директории .git в размерах");
+    let _ = writeln!(out, "      --follow-mounts Заходить в точки монтирования других файловых систем");
+    let _ = writeln!(out, "      --no-follow-mounts");
+    let _ = writeln!(out, "                      Пропускать точки монтирования, как du -x (по умолчанию)");
+    let _ = writeln!(out, "  -x, --one-filesystem");
+    let _ = writeln!(out, "                      То же, что --no-follow-mounts");
+    let _ = writeln!(out, "      --include-virtual");
+    let _ = writeln!(out, "                      Обходить /proc, /sys, /dev и /run (по умолчанию пропускаются)");
+    let _ = writeln!(out, "      --show-hidden   Учитывать скрытые файлы Windows (по умолчанию пропускаются)");
+    let _ = writeln!(out, "      --owner ПОЛЬЗОВАТЕЛЬ");
+    let _ = writeln!(out, "                      Учитывать только файлы этого пользователя (имя или uid, только Unix)");
+    let _ = writeln!(out, "      --owner-summary Показать занятое место по владельцам файлов (только Unix)");
+    let _ = writeln!(out, "      --group-by-owner");
+    let _ = writeln!(out, "                      Вместо топа директорий - таблица пользователей: место, число");
+    let _ = writeln!(out, "                      файлов и самый большой файл (только Unix)");
+    let _ = writeln!(out, "      --compress-estimate");
+    let _ = writeln!(out, "                      Оценить экономию от сжатия gzip по типам файлов (по энтропии");
+    let _ = writeln!(out, "                      первых 64 КиБ каждого файла)");
+    let _ = writeln!(out, "      --group-by-day  Вместо топа директорий - размер и число файлов по дням изменения (UTC)");
+    let _ = writeln!(out, "      --group-by-month");
+    let _ = writeln!(out, "                      То же по месяцам изменения");
+    let _ = writeln!(out, "      --hash          Вычислить SHA-256 каждого файла");
+    let _ = writeln!(out, "      --hash-output ФАЙЛ");
+    let _ = writeln!(out, "                      Записать хеши в ФАЙЛ в формате sha256sum");
+    let _ = writeln!(out, "      --verify ФАЙЛ   Сравнить файлы с хешами из ФАЙЛА: новые, удаленные, измененные");
+    let _ = writeln!(out, "      --mime-classify Группировать типы файлов по содержимому (image/, video/...), а не");
+    let _ = writeln!(out, "                      по расширению, и показать файлы с неверным расширением");
+    let _ = writeln!(out, "      --security-report");
+    let _ = writeln!(out, "                      Показать файлы, доступные на запись всем, и файлы с SUID/SGID (только Unix)");
+    let _ = writeln!(out, "      --skip-macos-metadata");
+    let _ = writeln!(out, "                      Пропускать .DS_Store, __MACOSX, .Spotlight-V100, .Trashes и .MobileBackups");
+    let _ = writeln!(out, "      --count-hardlinks");
+    let _ = writeln!(out, "                      Учитывать размер файла для каждой его жесткой ссылки");
+    let _ = writeln!(out, "      --show-hardlinks");
+    let _ = writeln!(out, "                      Показать файлы с несколькими жесткими ссылками");
+    let _ = writeln!(out, "      --fail-on-error Прервать сканирование при первой ошибке доступа");
+    let _ = writeln!(out, "      --disk-usage    Считать размером место, выделенное на диске, а не длину файлов");
+    let _ = writeln!(out, "      --inodes        Подсчитать иноды: топ директорий по инодам и заполнение файловой системы");
+    let _ = writeln!(out, "      --show-file-counts-only");
+    let _ = writeln!(out, "                      Отчет только по числу файлов: директории и типы файлов по количеству,");
+    let _ = writeln!(out, "                      директории с наибольшим числом собственных файлов, без размеров");
+    let _ = writeln!(out, "      --duplicates    Найти дубликаты файлов по содержимому (SHA-256)");
+    let _ = writeln!(out, "      --dup-min-size РАЗМЕР");
+    let _ = writeln!(out, "                      Минимальный размер файла для поиска дубликатов (по умолчанию 1M)");
+    let _ = writeln!(out, "      --json          Вывести результат сканирования в формате JSON");
+    let _ = writeln!(out, "      --csv           Вывести результат сканирования в формате CSV");
+    let _ = writeln!(out, "      --xml           Вывести результат сканирования в формате XML");
+    let _ = writeln!(out, "      --report-template ФАЙЛ");
+    let _ = writeln!(out, "                      Вывести отчет по шаблону в синтаксисе Tera/Jinja2 (default - встроенный)");
+    let _ = writeln!(out, "  -i, --interactive   Интерактивный просмотр директорий (стрелки, Enter, s, /, q)");
+    let _ = writeln!(out, "      --watch СЕКУНДЫ Пересканировать каждые СЕКУНДЫ и показывать изменения размеров");
+    let _ = writeln!(out, "  -v, --verbose       Подробный отчет: расширения категорий, средний и медианный размер файла; пути");
+    let _ = writeln!(out, "                      директорий печатаются в stderr по мере обхода (-vv - и файлов)");
+    let _ = writeln!(out, "  -q, --quiet         Вывести только таблицу директорий в виде \"<байты> <путь>\"");
+    let _ = writeln!(out, "      --du            Вывод как у du -s --block-size=1: \"<байты>\\t<путь>\" для каждого пути");
+    let _ = writeln!(out, "      --summary       Строка \"<путь> <байты> <файлов>\" на каждый путь и строка TOTAL для");
+    let _ = writeln!(out, "                      нескольких путей, без заголовков и единиц");
+    let _ = writeln!(out, "      --free          Быстрая оценка без сканирования: размер, занятое и свободное место");
+    let _ = writeln!(out, "                      файловой системы каждого пути (statvfs)");
+    let _ = writeln!(out, "      --fdupes-input ФАЙЛ");
+    let _ = writeln!(out, "                      Отчет о лишнем месте по выводу fdupes (fdupes -r или -rS) без");
+    let _ = writeln!(out, "                      сканирования и хеширования");
+    let _ = writeln!(out, "  -h, --human-readable");
+    let _ = writeln!(out, "                      Размеры в --du с суффиксами K, M, G, как у du -h");
+    let _ = writeln!(out, "      --tree          Вывести дерево директорий с размерами (глубина - --max-depth,");
+    let _ = writeln!(out, "                      порог размера - --min-size)");
+    let _ = writeln!(out, "      --ascii         Рисовать дерево символами +-- вместо псевдографики");
+    let _ = writeln!(out, "      --find-format   Вывести все файлы в виде \"<байты>\\t<путь>\" по убыванию размера");
+    let _ = writeln!(out, "      --print0        Завершать записи списка файлов символом NUL, как find -print0;");
+    let _ = writeln!(out, "                      без --find-format выводятся только пути (для xargs -0)");
+    let _ = writeln!(out, "      --no-color      Не раскрашивать вывод (также переменная окружения NO_COLOR)");
+    let _ = writeln!(out, "      --disable-rule ПРАВИЛО");
+    let _ = writeln!(out, "                      Не выдавать совет по оптимизации ПРАВИЛА (можно указать несколько");
+    let _ = writeln!(out, "                      раз): {}", tips::builtin_rule_names());
+    let _ = writeln!(out, "      --html ФАЙЛ     Сохранить самодостаточный HTML-отчет в ФАЙЛ");
+    let _ = writeln!(out, "      --dot ФАЙЛ      Сохранить граф директорий в формате Graphviz DOT");
+    let _ = writeln!(out, "      --trash         Показать размер корзины перед основным отчетом");
+    let _ = writeln!(out, "      --cloud-sync    Отметить значком ☁ директории Dropbox, Google Drive, OneDrive и iCloud");
+    let _ = writeln!(out, "      --trash-threshold РАЗМЕР");
+    let _ = writeln!(out, "                      Предупреждать, если корзина больше РАЗМЕРА (по умолчанию 500M)");
+    let _ = writeln!(out, "      --dot-threshold РАЗМЕР");
+    let _ = writeln!(out, "                      Показывать в графе директории не меньше РАЗМЕРА (по умолчанию 100M)");
+    let _ = writeln!(out, "      --output ФАЙЛ   Записать отчет в файл (сообщения о ходе работы остаются в терминале)");
+    let _ = writeln!(out, "      --tracing       Засечь время обхода каждой директории и показать самые медленные");
+    let _ = writeln!(out, "      --tracing-jaeger АДРЕС");
+    let _ = writeln!(out, "                      Отправить спаны обхода в формате OTLP/HTTP (JSON) в Jaeger или");
+    let _ = writeln!(out, "                      другой приемник OpenTelemetry: http://хост:4318");
+    let _ = writeln!(out, "      --log-file ФАЙЛ Дописывать в файл журнал сканирования: начало и конец, ошибки доступа,");
+    let _ = writeln!(out, "                      пропущенные пути и битые ссылки");
+    let _ = writeln!(out, "      --force         Перезаписать файл --output без подтверждения");
+    let _ = writeln!(out, "      --ncdu-export ФАЙЛ");
+    let _ = writeln!(out, "                      Сохранить результат в формате экспорта ncdu (ncdu -f ФАЙЛ)");
+    let _ = writeln!(out, "      --prometheus-push URL");
+    let _ = writeln!(out, "                      Отправить размер, число файлов и самый большой файл каждой директории");
+    let _ = writeln!(out, "                      в Prometheus Pushgateway (http://хост:порт)");
+    let _ = writeln!(out, "      --prometheus-job ИМЯ");
+    let _ = writeln!(out, "                      Метка job для --prometheus-push (по умолчанию diskspace)");
+    let _ = writeln!(out, "      --save ФАЙЛ     Сохранить результат сканирования в снимок для diskspace load");
+    let _ = writeln!(out, "      --csv-types-file ФАЙЛ");
+    let _ = writeln!(out, "                      Записать статистику по типам файлов в отдельный CSV-файл");
+    let _ = writeln!(out, "      --config ФАЙЛ   Загрузить настройки из ФАЙЛА вместо ~/.config/diskspace/config.toml");
+    let _ = writeln!(out, "      --no-config     Не загружать файл конфигурации");
+    let _ = writeln!(out, "      --help          Показать справку и выйти");
+    let _ = writeln!(out, "  -V, --version       Показать версию и сведения о сборке (коммит, дата, rustc)");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Команды:");
+    let _ = writeln!(out, "  scan                То же, что запуск без команды");
+    let _ = writeln!(out, "  load СНИМОК         Показать отчет по снимку, сохраненному через --save");
+    let _ = writeln!(out, "  compare ЛЕВАЯ ПРАВАЯ");
+    let _ = writeln!(out, "                      Сравнить размеры директорий двух деревьев или снимков; --threshold");
+    let _ = writeln!(out, "                      РАЗМЕР задает минимальное изменение для показа (по умолчанию 1M);");
+    let _ = writeln!(out, "                      --top-growth для двух снимков показывает директории, быстрее всего");
+    let _ = writeln!(out, "                      растущие за сутки");
+    let _ = writeln!(out, "  interactive-clean [ПУТЬ]");
+    let _ = writeln!(out, "                      Предложить удалить временные файлы, копии дубликатов, кэши пакетных");
+    let _ = writeln!(out, "                      менеджеров и пустые директории с подтверждением каждой категории;");
+    let _ = writeln!(out, "                      удаления записываются в ~/.local/share/diskspace/cleanup.log,");
+    let _ = writeln!(out, "                      --dry-run только показывает, что было бы удалено");
+    let _ = writeln!(out, "  config --init       Записать пример конфигурации в ~/.config/diskspace/config.toml");
+    let _ = writeln!(out, "  config-check        Проверить файл конфигурации и показать итоговые значения его настроек");
+    let _ = writeln!(out, "                      с учетом опций командной строки; код 1 при ошибке");
+    let _ = writeln!(out, "  completions ОБОЛОЧКА");
+    let _ = writeln!(out, "                      Вывести скрипт автодополнения для {}", Shell::NAMES);
+    out
+}
+
+// Подкоманда "diskspace compare ЛЕВАЯ ПРАВАЯ [--threshold РАЗМЕР] [ОПЦИИ]"
+fn run_compare(args: &[String]) -> io::Result<()> {
+    let mut threshold = 1024 * 1024;
+    let mut top_growth = false;
+    let mut rest = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        if name == "--threshold" {
+            let value = take_value(args, &mut i, name, inline_value).unwrap_or_else(|e| usage_error(&e));
+            threshold = parse_size_str(&value).unwrap_or_else(|e| usage_error(&format!("{}: {}", name, e)));
+        } else if name == "--top-growth" {
+            top_growth = true;
+        } else {
+            rest.push(arg.clone());
+        }
+        i += 1;
+    }
+
+    // Остальные опции (--exclude, --jobs, --config и т.д.) действуют на оба сканирования
+    let options = load_options(&rest).unwrap_or_else(|message| usage_error(&message));
+    let (left, right) = match options.paths.as_slice() {
+        [left, right] => (left, right),
+        _ => usage_error("команде compare нужны две директории: diskspace compare ЛЕВАЯ ПРАВАЯ"),
+    };
+    color::set_enabled(color::should_colorize(options.no_color));
+    set_size_format(options.size_format);
+    if top_growth {
+        return run_top_growth(left, right, &options);
     }
+
+    println!("🔍 Сравнение {:?} и {:?}", left, right);
+    let (left_root, left_result) = scan_or_load(left, &options.scan)?;
+    let (right_root, right_result) = scan_or_load(right, &options.scan)?;
+    println!("📊 Слева: {} ({} файлов), справа: {} ({} файлов)\n",
+        format_size(left_result.root.size, size_format()), left_result.root.file_count,
+        format_size(right_result.root.size, size_format()), right_result.root.file_count);
+
+    let changes = diff_dirs(
+        &relative_dir_infos(&left_result, &left_root),
+        &relative_dir_infos(&right_result, &right_root),
+        threshold);
+    report::print_comparison(&changes, options.top_dirs);
+    Ok(())
 }
 
-fn generate_optimization_tips(dirs: &Vec<(String, DirInfo)>, largest_files: &Vec<(PathBuf, u64)>) {
-    println!("\n💡 СОВЕТЫ ПО ОПТИМИЗАЦИИ:");
+// Подкоманда "diskspace interactive-clean [--dry-run] [ОПЦИИ] [ПУТЬ]"
+fn run_interactive_clean(args: &[String]) -> io::Result<()> {
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let rest: Vec<String> = args.iter().filter(|arg| *arg != "--dry-run").cloned().collect();
+    let mut options = load_options(&rest).unwrap_or_else(|message| usage_error(&message));
+    let root = match options.paths.as_slice() {
+        [] => env::current_dir()?,
+        // В журнал попадают абсолютные пути
+        [root] => env::current_dir()?.join(root),
+        _ => usage_error("команде interactive-clean нужна одна директория"),
+    };
+    color::set_enabled(color::should_colorize(options.no_color));
+    set_size_format(options.size_format);
+
+    options.scan.collect_temp_files = true;
+    options.scan.collect_files_min_size = Some(options.dup_min_size);
+    println!("🔍 Поиск кандидатов на удаление в {:?}...", root);
+    let mut result = scan_directory(&root, &options.scan)?;
+    let duplicates = find_duplicates(std::mem::take(&mut result.files));
+    let groups = cleanup::collect_candidates(&result, &duplicates);
+    if groups.is_empty() {
+        println!("✅ Нечего удалять");
+        return Ok(());
+    }
+
+    println!("\n🧹 КАНДИДАТЫ НА УДАЛЕНИЕ:");
+    println!("{}", color::bold(&format!("{:<30} {:>10} {:>15}", "КАТЕГОРИЯ", "ЭЛЕМЕНТОВ", "ОСВОБОДИТСЯ")));
     println!("{:-<60}", "");
-    
-    // Если есть очень большие директории
-    if !dirs.is_empty() && dirs[0].1.size > 1024 * 1024 * 1024 {
-        println!("🔸 Директория '{}' занимает {}, что составляет значительную часть дискового пространства.", 
-            dirs[0].0, format_size(dirs[0].1.size));
+    for group in &groups {
+        println!("{:<30} {:>10} {:>15}", group.category.label(), group.items.len(), format_size(group.total(), size_format()));
     }
-    
-    // Советы по типам файлов
-    let mut has_large_logs = false;
-    let mut has_large_media = false;
-    let mut has_downloads = false;
-    
-    for (path, info) in dirs.iter().take(5) {
-        if path.to_lowercase().contains("log") && info.size > 100 * 1024 * 1024 {
-            has_large_logs = true;
+
+    let log = cleanup::default_log_path();
+    if log.is_none() && !dry_run {
+        eprintln!("Предупреждение: не удалось определить домашнюю директорию, удаления не будут записаны в журнал");
+    }
+    let (mut freed, mut removed) = (0, 0);
+    for group in &groups {
+        println!();
+        let question = format!("{}: {} шт., {}. Удалить?", group.category.label(), group.items.len(), format_size(group.total(), size_format()));
+        if !confirm(&question)? {
+            continue;
+        }
+        for (path, size) in &group.items {
+            if dry_run {
+                println!("  будет удалено: {} ({})", path.display(), format_size(*size, size_format()));
+                freed += size;
+                continue;
+            }
+            if let Err(e) = cleanup::remove_candidate(group.category, path) {
+                eprintln!("Не удалось удалить {}: {}", path.display(), e);
+                continue;
+            }
+            freed += size;
+            removed += 1;
+            if let Some(log) = &log {
+                if let Err(e) = cleanup::append_log(log, group.category, path, *size) {
+                    eprintln!("Не удалось записать журнал {}: {}", log.display(), e);
+                }
+            }
+        }
+    }
+
+    if dry_run {
+        println!("\n🧪 Пробный запуск: было бы освобождено {}", format_size(freed, size_format()));
+    } else {
+        println!("\n🗑  Удалено элементов: {}, освобождено {}", removed, format_size(freed, size_format()));
+        if let (Some(log), true) = (&log, removed > 0) {
+            println!("Журнал удалений: {}", log.display());
+        }
+    }
+    Ok(())
+}
+
+// compare --top-growth: скорость роста директорий между двумя снимками.
+// Снимки могут быть указаны в любом порядке, более ранний считается исходным
+fn run_top_growth(left: &Path, right: &Path, options: &Options) -> io::Result<()> {
+    let load = |path: &Path| {
+        if !path.is_file() || !Snapshot::is_snapshot_file(path) {
+            usage_error(&format!("--top-growth сравнивает два снимка (--save), а {} - не снимок", path.display()));
         }
-        
-        if path.to_lowercase().contains("download") {
-            has_downloads = true;
+        Snapshot::load(path).unwrap_or_else(|e| {
+            eprintln!("Ошибка: {}: {}", path.display(), e);
+            std::process::exit(1);
+        })
+    };
+    let (mut before, mut after) = (load(left), load(right));
+    if after.scanned_at < before.scanned_at {
+        std::mem::swap(&mut before, &mut after);
+    }
+    let elapsed = after.scanned_at.duration_since(before.scanned_at).unwrap_or_default();
+    if elapsed.is_zero() {
+        usage_error("снимки сделаны в одно и то же время, скорость роста не определена");
+    }
+
+    println!("🔍 Рост директорий с {} по {} UTC\n", format_timestamp(before.scanned_at), format_timestamp(after.scanned_at));
+    let growth = growth_rates(
+        &relative_dir_infos(&before.result, &before.root),
+        &relative_dir_infos(&after.result, &after.root),
+        elapsed);
+    report::print_top_growth(&growth, options.top_dirs);
+    Ok(())
+}
+
+// Объединяет результаты нескольких корней. Ключи dir_infos - полные пути
+// с префиксом своего корня, поэтому у непересекающихся корней они не совпадают
+fn merge_scan_results(results: Vec<ScanResult>) -> ScanResult {
+    let mut results = results.into_iter();
+    let mut combined = results.next().unwrap_or_default();
+    for result in results {
+        combined.merge(result);
+    }
+    combined
+}
+
+// Режим --du: по строке "<размер>\t<путь>" на каждый путь, как
+// `du -s --block-size=1` (или `du -sh` с -h), без другого вывода
+fn run_du(options: &mut Options) -> io::Result<()> {
+    options.scan.disk_usage = true;
+    options.scan.count_dir_sizes = true;
+    let roots = if options.paths.is_empty() { vec![PathBuf::from(".")] } else { std::mem::take(&mut options.paths) };
+    let mut failed = false;
+    for root in &roots {
+        match scan_directory(root, &options.scan) {
+            Ok(result) => {
+                let size = result.root.size;
+                let size = if options.si {
+                    format_du_size_si(size)
+                } else if options.human_readable {
+                    format_du_size(size)
+                } else if let Some(block_size) = options.scan.block_size {
+                    (size / block_size).to_string()
+                } else {
+                    size.to_string()
+                };
+                println!("{}\t{}", size, root.display());
+            },
+            Err(e) => {
+                eprintln!("diskspace: {}: {}", root.display(), e);
+                failed = true;
+            }
         }
-        
-        for (ext, size) in &info.file_types {
-            if (ext == "mp4" || ext == "mov" || ext == "avi") && *size > 500 * 1024 * 1024 {
-                has_large_media = true;
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// Режим --show-file-counts-only: итог и все таблицы по числу файлов, без
+// размеров - для Maildir и деревьев из миллионов мелких файлов, где
+// заканчиваются иноды, а не место
+fn print_file_count_report(start_path: &Path, result: &ScanResult, dirs: &mut [(String, DirInfo)], options: &Options, load_snapshot: bool) {
+    println!("📊 Всего файлов: {} в {} директориях", format_count(result.root.file_count as u64), format_count(dirs.len() as u64));
+    if options.scan.count_inodes {
+        println!("🧮 Всего инодов: {}", result.root.inode_count);
+        if !load_snapshot {
+            match inode_usage(start_path) {
+                Some(usage) => report::print_inode_usage(&usage),
+                None => eprintln!("Предупреждение: не удалось получить сведения об инодах файловой системы"),
             }
         }
     }
+    println!();
     
-    if has_large_logs {
-        println!("🔸 Обнаружены большие лог-файлы. Регулярная очистка логов может освободить значительное пространство.");
+    sort_dirs(dirs, SortKey::Count, options.reverse);
+    report::print_top_count_dirs(dirs, options.top_dirs, result.root.file_count);
+    let own_counts = report::collect_own_file_counts(&start_path.to_string_lossy(), &result.root, dirs);
+    report::print_own_file_counts(&own_counts, options.top_files);
+    report::print_file_type_counts(&result.root, options.top_extensions);
+}
+
+// Режим --summary: строка "<путь> <байты> <файлов>" на каждый путь, для
+// нескольких путей - еще строка TOTAL; без заголовков, цветов и единиц
+fn run_summary(options: &mut Options) -> io::Result<()> {
+    let roots = if options.paths.is_empty() { vec![PathBuf::from(".")] } else { std::mem::take(&mut options.paths) };
+    let (mut total_size, mut total_files) = (0u64, 0usize);
+    let mut failed = false;
+    // Каждый путь выводится как есть, но вложенный в другой путь не учитывается в TOTAL дважды
+    let nested = nested_roots(&roots);
+    for (root, nested) in roots.iter().zip(nested) {
+        match scan_directory(root, &options.scan) {
+            Ok(result) => {
+                println!("{} {} {}", root.display(), result.root.size, result.root.file_count);
+                if !nested {
+                    total_size += result.root.size;
+                    total_files += result.root.file_count;
+                }
+            },
+            Err(e) => {
+                eprintln!("diskspace: {}: {}", root.display(), e);
+                failed = true;
+            }
+        }
     }
-    
-    if has_large_media {
-        println!("🔸 Медиафайлы занимают много места. Рассмотрите возможность переноса видео на внешний носитель или в облачное хранилище.");
+    if roots.len() > 1 {
+        println!("TOTAL {} {}", total_size, total_files);
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// Режим --fdupes-input: лишнее место по готовому выводу fdupes, без
+// сканирования и хеширования
+fn run_fdupes_input(path: &Path, options: &Options) -> io::Result<()> {
+    let text = fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Ошибка: {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+    let _redirect = match &options.output {
+        Some(path) => Some(StdoutRedirect::to_file(&fs::File::create(path)?)?),
+        None => None,
+    };
+    report::print_duplicate_waste(&fdupes_groups(&text));
+    Ok(())
+}
+
+// Режим --free: место на файловой системе каждого пути за один вызов
+// statvfs, без обхода файлов
+fn run_free(options: &Options) -> io::Result<()> {
+    let roots = if options.paths.is_empty() { vec![PathBuf::from(".")] } else { options.paths.clone() };
+    let mut failed = false;
+    for root in &roots {
+        if let Err(e) = fs::metadata(root) {
+            eprintln!("diskspace: {}: {}", root.display(), e);
+            failed = true;
+            continue;
+        }
+        match space_usage(root) {
+            Some(usage) => report::print_space_usage(root, &usage),
+            None => {
+                eprintln!("diskspace: {}: не удалось получить сведения о файловой системе", root.display());
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// Ищет пару корней, один из которых вложен в другой (или совпадает с ним)
+fn find_nested_roots(roots: &[PathBuf]) -> Option<(PathBuf, PathBuf)> {
+    let resolved = resolve_roots(roots);
+    for i in 0..resolved.len() {
+        for j in 0..resolved.len() {
+            if i != j && resolved[i].starts_with(&resolved[j]) {
+                return Some((roots[j].clone(), roots[i].clone()));
+            }
+        }
+    }
+    None
+}
+
+// Для каждого корня - вложен ли он в другой корень или совпадает с одним из
+// предыдущих: его размер уже входит в размер того корня
+fn nested_roots(roots: &[PathBuf]) -> Vec<bool> {
+    let resolved = resolve_roots(roots);
+    resolved.iter().enumerate()
+        .map(|(i, root)| resolved.iter().enumerate()
+            .any(|(j, other)| i != j && root.starts_with(other) && (root != other || j < i)))
+        .collect()
+}
+
+fn resolve_roots(roots: &[PathBuf]) -> Vec<PathBuf> {
+    roots.iter()
+        .map(|root| fs::canonicalize(root).unwrap_or_else(|_| root.clone()))
+        .collect()
+}
+
+// Ближайший общий предок путей; для относительных путей без общей части - "."
+fn common_ancestor(paths: &[PathBuf]) -> PathBuf {
+    let mut ancestor = paths[0].clone();
+    for path in &paths[1..] {
+        while !path.starts_with(&ancestor) {
+            if !ancestor.pop() {
+                break;
+            }
+        }
     }
+    if ancestor.as_os_str().is_empty() { PathBuf::from(".") } else { ancestor }
+}
+
+// Сканирует start_path с индикатором прогресса; возвращает результат
+// и длительность сканирования
+fn run_scan(start_path: &Path, options: &mut Options, pool: &WorkerPool, machine_output: bool, mut log: Option<&mut ScanLog>)
+    -> io::Result<(ScanResult, ScanStats)> {
+    // Для поиска дубликатов сканер собирает список достаточно больших файлов
+    if options.duplicates {
+        options.scan.collect_files_min_size = Some(options.dup_min_size);
+    }
+    // В формате find, в экспорте ncdu и для хешей нужны все учтенные файлы
+    if options.find_format || options.ncdu_export.is_some() || options.hash {
+        options.scan.collect_files_min_size = Some(0);
+    }
+    // Каждая директория хранит не меньше файлов, чем нужно для раздела
+    // самых больших файлов; --top-files 0 - все файлы
+    options.scan.top_files = options.scan.top_files.max(report::display_limit(options.top_files));
+    // Перцентили размеров файлов выводятся только в обычном отчете, медиана -
+    // в подробной таблице директорий и в снимке
+    options.scan.collect_file_sizes = !machine_output && !options.tree;
+    options.scan.file_size_median = options.verbose && options.scan.collect_file_sizes || options.save.is_some();
     
-    if has_downloads {
-        println!("🔸 Директория загрузок содержит много файлов. Очистка временных и ненужных загрузок может освободить пространство.");
+    if let Some(log) = &mut log {
+        log.write(LogLevel::Info, &format!("начало сканирования {}", start_path.display()))?;
     }
+    let start_time = Instant::now();
     
-    // Советы по крупным файлам
-    if !largest_files.is_empty() {
-        let (path, size) = &largest_files[0];
-        if *size > 1024 * 1024 * 1024 {
-            println!("🔸 Файл '{}' занимает {}. Удаление или архивация этого файла значительно освободит место.", 
-                path.display(), format_size(*size));
+    // Прогресс выводится в stderr и только если это терминал
+    let show_progress = !machine_output && options.scan.trace == 0 && io::stderr().is_terminal();
+    let progress = Arc::new(ScanProgress::default());
+    let scan_done = AtomicBool::new(false);
+    let result = thread::scope(|scope| {
+        let progress_thread = if show_progress {
+            Some(scope.spawn(|| run_progress(&progress, &scan_done)))
+        } else {
+            None
+        };
+        let result = if options.use_async {
+            block_on(scan_directory_async_with_progress(start_path, &options.scan, Arc::clone(&progress)))
+        } else {
+            scan_directory_in_pool(start_path, &options.scan, &progress, pool)
+        };
+        scan_done.store(true, Ordering::Release);
+        if let Some(progress_thread) = progress_thread {
+            progress_thread.thread().unpark();
         }
+        result
+    })?;
+    let stats = progress.stats(result.skipped.len() as u64, start_time.elapsed());
+    if let Some(log) = &mut log {
+        log.write_result(start_path, &result, &stats)?;
     }
-    
-    println!("🔸 Рассмотрите использование инструментов сжатия для регулярно используемых файлов.");
-    println!("🔸 Для системных файлов используйте команды очистки, специфичные для вашей ОС.");
-}
\ No newline at end of file
+    Ok((result, stats))
+}
+
+// Выставляет флаг прерывания сканирования по Ctrl-C или через timeout секунд.
+// Поток не останавливается: флаг, выставленный после конца сканирования, ни
+// на что не влияет
+fn start_scan_watchdog(timeout: Option<u64>, scan: &mut ScanOptions) {
+    signal::install_interrupt_handler();
+    let cancel = Arc::new(AtomicBool::new(false));
+    scan.cancel = Some(Arc::clone(&cancel));
+    let deadline = timeout.map(|timeout| Instant::now() + Duration::from_secs(timeout));
+    thread::spawn(move || {
+        loop {
+            let timed_out = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            if timed_out || signal::interrupted() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        cancel.store(true, Ordering::Relaxed);
+    });
+}
+
+// Сторона сравнения: файл снимка загружается, директория сканируется.
+// Возвращает корень дерева и результат
+fn scan_or_load(path: &Path, options: &ScanOptions) -> io::Result<(PathBuf, ScanResult)> {
+    if path.is_file() && Snapshot::is_snapshot_file(path) {
+        let snapshot = Snapshot::load(path).unwrap_or_else(|e| {
+            eprintln!("Ошибка: {}: {}", path.display(), e);
+            std::process::exit(1);
+        });
+        return Ok((snapshot.root, snapshot.result));
+    }
+    Ok((path.to_path_buf(), scan_directory(path, options)?))
+}
+
+// Объем файловой системы для колонки %DISK при --relative
+fn disk_total(path: &Path, options: &Options) -> Option<u64> {
+    if !options.relative {
+        return None;
+    }
+    let usage = space_usage(path);
+    if usage.is_none() {
+        eprintln!("Предупреждение: не удалось получить объем файловой системы, колонка %DISK не выводится");
+    }
+    usage.map(|usage| usage.total)
+}
+
+// Режим --stream: директории приходят по мере сканирования, а в памяти
+// остаются только самые большие из них
+fn run_stream(roots: &[PathBuf], options: &Options) -> io::Result<()> {
+    let names: Vec<String> = roots.iter().map(|root| format!("{:?}", root)).collect();
+    println!("🔍 Анализ использования дискового пространства для: {}", names.join(", "));
+    println!("⏳ Подождите, идет сканирование...");
+    let start_time = Instant::now();
+    let mut total = DirInfo::new();
+    let mut top = TopDirs::new(options.top_dirs);
+    for root in roots {
+        for item in scan_stream(root, &options.scan) {
+            let (path, info) = item?;
+            // Последним приходит сам корень
+            if Path::new(&path) == root {
+                total.merge_limited(&info, options.scan.top_files);
+            } else {
+                top.push(path, info);
+            }
+        }
+    }
+    println!("\n✅ Сканирование завершено за {:.2} секунд", start_time.elapsed().as_secs_f32());
+    println!("📊 Общий размер: {} ({} файлов)\n", format_size(total.size, size_format()), total.file_count);
+    let disk_total = roots.first().and_then(|root| disk_total(root, options));
+    let top = top.into_sorted_vec();
+    let cloud_dirs = if options.cloud_sync { report::collect_cloud_dirs(&roots[0], &top) } else { Vec::new() };
+    report::print_top_dirs(&top, options.top_dirs, total.size, options.bar, disk_total, options.verbose, &cloud_dirs);
+    Ok(())
+}
+
+// Режим --watch: периодическое пересканирование, экран обновляется на месте
+fn run_watch(start_path: &Path, options: &Options, interval: u64) -> io::Result<()> {
+    signal::install_interrupt_handler();
+    let in_place = io::stdout().is_terminal();
+    let mut previous: Option<ScanResult> = None;
+    let pool = WorkerPool::new(options.scan.jobs);
+
+    while !signal::interrupted() {
+        let result = scan_directory_in_pool(start_path, &options.scan, &ScanProgress::default(), &pool)?;
+
+        let mut frame = String::new();
+        if in_place {
+            frame.push_str("\x1b[H\x1b[2J");
+        }
+        let _ = writeln!(frame, "👀 Наблюдение за {:?}: сканирование каждые {} с, Ctrl-C - выход", start_path, interval);
+        let total_delta = previous.as_ref()
+            .map(|prev| format!(" {}", format_size_delta(result.root.size as i64 - prev.root.size as i64, size_format())))
+            .unwrap_or_default();
+        let _ = writeln!(frame, "🕒 {} UTC  📊 Общий размер: {} ({} файлов){}\n",
+            format_timestamp(SystemTime::now()), format_size(result.root.size, size_format()), result.root.file_count, total_delta);
+
+        match &previous {
+            None => frame.push_str("Первое сканирование: изменения появятся после следующего.\n"),
+            Some(prev) => {
+                let changes = diff_dirs(&prev.dir_infos, &result.dir_infos, 1);
+                frame.push_str("📈 ИЗМЕНЕНИЯ С ПРОШЛОГО СКАНИРОВАНИЯ:\n");
+                let _ = writeln!(frame, "{:-<60}", "");
+                if changes.is_empty() {
+                    frame.push_str("Изменений нет\n");
+                }
+                for change in changes.iter().take(report::display_limit(options.top_dirs)) {
+                    let delta = format!("{:<15}", format_size_delta(change.delta(), size_format()));
+                    let delta = if change.delta() > 0 {
+                        color::paint(&delta, Color::Green)
+                    } else {
+                        color::paint(&delta, Color::Red)
+                    };
+                    let size = change.new_size.map_or_else(|| "удалена".to_string(), |size| format_size(size, size_format()));
+                    let _ = writeln!(frame, "{} {:<15} {}", delta, size, change.path);
+                }
+            }
+        }
+        print!("{}", frame);
+        io::stdout().flush()?;
+        previous = Some(result);
+
+        // Ждем короткими интервалами, чтобы быстро реагировать на Ctrl-C
+        let deadline = Instant::now() + Duration::from_secs(interval);
+        while Instant::now() < deadline && !signal::interrupted() {
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    println!("\n👋 Наблюдение остановлено");
+    Ok(())
+}
+
+// Сканирует директории корзины без фильтров основного сканирования
+fn scan_trash(options: &Options) -> Vec<(PathBuf, DirInfo)> {
+    let trash_options = ScanOptions::default().jobs(options.scan.jobs);
+    let mut trash = Vec::new();
+    for dir in trash_dirs() {
+        match scan_directory(&dir, &trash_options) {
+            Ok(result) => trash.push((dir, result.root)),
+            Err(e) => eprintln!("Предупреждение: не удалось просканировать корзину {}: {}", dir.display(), e),
+        }
+    }
+    trash
+}
+
+// HTML-отчет строится по директориям, отсортированным по размеру,
+// независимо от --sort: таблицу можно пересортировать на странице
+fn write_html_report(path: &Path, root: &Path, total: &DirInfo, dirs: &[(String, DirInfo)], tips: &[Tip], options: &Options) -> io::Result<()> {
+    let mut by_size = dirs.to_vec();
+    sort_dirs(&mut by_size, SortKey::Size, false);
+    let largest_files = total.top_n_files(options.top_files);
+    let tips: Vec<String> = tips.iter().map(|tip| tip.message.clone()).collect();
+    let page = HtmlReport {
+        root,
+        total,
+        dirs: &by_size,
+        largest_files: &largest_files,
+        tips: &tips,
+        scanned_at: SystemTime::now(),
+        top_dirs: options.top_dirs,
+        top_files: options.top_files,
+        top_extensions: options.top_extensions,
+    };
+    fs::write(path, page.render())
+}
+
+// Подкоманды для автодополнения первого аргумента
+const SUBCOMMANDS: &[&str] = &["scan", "load", "compare", "interactive-clean", "config", "config-check", "completions"];
+
+// Подкоманда "diskspace completions ОБОЛОЧКА"
+fn run_completions(args: &[String]) -> io::Result<()> {
+    let shell = match args {
+        [name] => Shell::parse(name),
+        _ => None,
+    };
+    let Some(shell) = shell else {
+        eprintln!("Использование: diskspace completions ОБОЛОЧКА ({})", Shell::NAMES);
+        std::process::exit(2);
+    };
+    print!("{}", completions::generate(shell, "diskspace", SUBCOMMANDS, &completions::parse_help(&help_text())));
+    Ok(())
+}
+
+// Подкоманда "diskspace config"
+fn run_config_command(args: &[String]) -> io::Result<()> {
+    if args.len() != 1 || args[0] != "--init" {
+        eprintln!("Использование: diskspace config --init");
+        std::process::exit(2);
+    }
+
+    let path = match default_config_path() {
+        Some(path) => path,
+        None => {
+            eprintln!("Ошибка: не удалось определить домашнюю директорию");
+            std::process::exit(1);
+        }
+    };
+    // Существующие настройки пользователя не перезаписываем
+    if path.exists() {
+        eprintln!("Ошибка: файл конфигурации уже существует: {}", path.display());
+        std::process::exit(1);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, EXAMPLE_CONFIG)?;
+    println!("✅ Пример конфигурации записан в {}", path.display());
+    Ok(())
+}
+
+// Подкоманда "diskspace config-check [ОПЦИИ]": проверяет файл конфигурации и
+// выводит итоговые значения его настроек с учетом опций командной строки.
+// Код завершения 1, если файл или опции содержат ошибку
+fn run_config_check(args: &[String]) -> io::Result<()> {
+    let fail = |message: String| -> ! {
+        eprintln!("❌ {}", message);
+        std::process::exit(1);
+    };
+    let path = match config_path_from_args(args).unwrap_or_else(|message| fail(message)) {
+        ConfigSource::Disabled => None,
+        ConfigSource::Path(path) => Some(path),
+        ConfigSource::Default => default_config_path().filter(|path| path.exists()),
+    };
+
+    let mut options = Options::default();
+    match &path {
+        Some(path) => {
+            let config = read_config(path).unwrap_or_else(|message| fail(message));
+            println!("📄 Файл конфигурации: {}", path.display());
+            warn_config_keys(path, &config);
+            apply_config(&mut options, &config).unwrap_or_else(|message| fail(format!("{}: {}", path.display(), message)));
+        },
+        None => println!("📄 Файл конфигурации не используется, действуют значения по умолчанию"),
+    }
+    let from_config = config_settings(&options);
+    let options = parse_args(args, options).unwrap_or_else(|message| fail(message));
+    set_size_format(options.size_format);
+    let defaults = config_settings(&Options::default());
+
+    println!("\n⚙️  ИТОГОВЫЕ НАСТРОЙКИ:");
+    println!("{}", color::bold(&format!("{:<18} {:<24} {:<}", "КЛЮЧ", "ЗНАЧЕНИЕ", "ИСТОЧНИК")));
+    println!("{:-<60}", "");
+    for (((key, value), (_, configured)), (_, default)) in config_settings(&options).into_iter().zip(from_config).zip(defaults) {
+        let source = if value != configured {
+            "командная строка"
+        } else if value != default {
+            "файл конфигурации"
+        } else {
+            "по умолчанию"
+        };
+        println!("{:<18} {:<24} {}", key, if value.is_empty() { "-" } else { &value }, source);
+    }
+    println!("\n✅ Конфигурация корректна");
+    Ok(())
+}
+
+// Пометка об ограничении глубины: итоги без вложенных глубже директорий неполные
+fn depth_limit_note(max_depth: u32) -> String {
+    let note = if max_depth == 0 {
+        "✂️  Глубина ограничена (-d 0): содержимое пути не сканировалось, размеры не посчитаны".to_string()
+    } else {
+        format!("✂️  Глубина ограничена (-d {}): содержимое директорий глубже уровня {} не учтено, размеры и итог неполные", max_depth, max_depth)
+    };
+    color::paint(&note, Color::Yellow)
+}
+
+// Обновляет строку прогресса в stderr каждые 250 мс, пока не завершится сканирование
+fn run_progress(progress: &ScanProgress, done: &AtomicBool) {
+    const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    let started = Instant::now();
+    let mut stderr = io::stderr();
+    let mut frame = 0;
+
+    while !done.load(Ordering::Acquire) {
+        let _ = write!(stderr, "\r\x1b[2K{} Директорий: {}, файлов: {}, объем: {} ({:.0} с)",
+            SPINNER[frame % SPINNER.len()],
+            progress.dirs_scanned(),
+            progress.files_scanned(),
+            format_size(progress.bytes_scanned(), size_format()),
+            started.elapsed().as_secs_f32());
+        let _ = stderr.flush();
+        frame += 1;
+        thread::park_timeout(Duration::from_millis(250));
+    }
+
+    // Стираем строку прогресса, чтобы она не смешивалась с отчетом
+    let _ = write!(stderr, "\r\x1b[2K");
+    let _ = stderr.flush();
+}
+
+// Печатает машиночитаемый документ в stdout или в файл --output
+fn write_document(options: &Options, document: &str) -> io::Result<()> {
+    match &options.output {
+        Some(path) => fs::write(path, document),
+        None => {
+            print!("{}", document);
+            Ok(())
+        },
+    }
+}
+
+// Пути из stdin, разделенные delimiter (перевод строки или NUL); пустые строки пропускаются
+fn read_stdin_paths(delimiter: u8) -> io::Result<Vec<PathBuf>> {
+    let mut input = Vec::new();
+    io::stdin().lock().read_to_end(&mut input)?;
+    Ok(input
+        .split(|&b| b == delimiter)
+        .map(|line| if delimiter == b'\n' { line.strip_suffix(b"\r").unwrap_or(line) } else { line })
+        .filter(|line| !line.is_empty())
+        .map(path_from_bytes)
+        .collect())
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+// Задает вопрос в терминале и возвращает true, если пользователь ответил "y"
+fn confirm(question: &str) -> io::Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes" || answer == "д" || answer == "да")
+}
+
+fn delete_empty_dirs(empty_dirs: &[PathBuf]) -> io::Result<()> {
+    if empty_dirs.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    let mut removed = 0;
+    for path in empty_dirs {
+        if !confirm(&format!("Удалить пустую директорию {}?", path.display()))? {
+            continue;
+        }
+        // remove_dir удаляет только пустые директории, поэтому появившиеся
+        // после сканирования файлы не пострадают
+        match fs::remove_dir(path) {
+            Ok(()) => removed += 1,
+            Err(e) => eprintln!("Не удалось удалить {}: {}", path.display(), e),
+        }
+    }
+    println!("🗑  Удалено пустых директорий: {} из {}", removed, empty_dirs.len());
+    Ok(())
+}
+
+fn clean_temp_files(temp_files: &[(PathBuf, u64)]) -> io::Result<()> {
+    if temp_files.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    let mut freed = 0;
+    let mut removed = 0;
+    for (path, size) in temp_files {
+        if !confirm(&format!("Удалить {} ({})?", path.display(), format_size(*size, size_format())))? {
+            continue;
+        }
+        match fs::remove_file(path) {
+            Ok(()) => {
+                removed += 1;
+                freed += size;
+            },
+            Err(e) => eprintln!("Не удалось удалить {}: {}", path.display(), e),
+        }
+    }
+    println!("🗑  Удалено временных файлов: {} из {}, освобождено {}", removed, temp_files.len(), format_size(freed, size_format()));
+    Ok(())
+}
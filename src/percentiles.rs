@@ -0,0 +1,70 @@
+//! Перцентили размеров файлов: «90% файлов не больше X»
+
+/// Перцентили по методу ближайшего ранга: pN - наименьший размер, не меньше
+/// которого N% файлов
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Percentiles {
+    pub p25: u64,
+    pub p50: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub p999: u64,
+}
+
+impl Percentiles {
+    /// Пары (подпись, размер) в порядке возрастания
+    pub fn entries(&self) -> [(&'static str, u64); 6] {
+        [
+            ("P25", self.p25),
+            ("P50", self.p50),
+            ("P75", self.p75),
+            ("P90", self.p90),
+            ("P99", self.p99),
+            ("P99.9", self.p999),
+        ]
+    }
+}
+
+/// Сортирует sizes и вычисляет перцентили; для пустого списка - нули
+pub fn compute_percentiles(sizes: &mut [u64]) -> Percentiles {
+    sizes.sort_unstable();
+    // Ранг в тысячных долях, чтобы P99.9 считался без вещественной арифметики
+    let at = |permille: usize| {
+        if sizes.is_empty() {
+            return 0;
+        }
+        let rank = (permille * sizes.len()).div_ceil(1000).max(1);
+        sizes[rank - 1]
+    };
+    Percentiles {
+        p25: at(250),
+        p50: at(500),
+        p75: at(750),
+        p90: at(900),
+        p99: at(990),
+        p999: at(999),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank() {
+        let mut sizes: Vec<u64> = (1..=1000).rev().collect();
+        let percentiles = compute_percentiles(&mut sizes);
+        assert_eq!(percentiles, Percentiles { p25: 250, p50: 500, p75: 750, p90: 900, p99: 990, p999: 999 });
+        assert_eq!(sizes[0], 1);
+    }
+
+    #[test]
+    fn small_and_empty_inputs() {
+        assert_eq!(compute_percentiles(&mut []), Percentiles::default());
+        let single = compute_percentiles(&mut [42]);
+        assert_eq!((single.p25, single.p999), (42, 42));
+        let pair = compute_percentiles(&mut [10, 1]);
+        assert_eq!((pair.p50, pair.p75), (1, 10));
+    }
+}
@@ -0,0 +1,143 @@
+//! Отправка метрик в Prometheus Pushgateway и других данных методом POST по HTTP
+
+use std::fmt::{self, Write as _};
+use std::io::{self, Read, Write as _};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Имя задания (метка `job`) по умолчанию
+pub const DEFAULT_JOB: &str = "diskspace";
+
+// Ожидание соединения и ответа шлюза
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ошибка отправки метрик
+#[derive(Debug)]
+pub enum PushError {
+    /// Адрес не вида `http://хост[:порт][/путь]`
+    InvalidUrl(String),
+    Io(io::Error),
+    /// Шлюз ответил кодом, отличным от 2xx; строка состояния ответа
+    Status(String),
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PushError::InvalidUrl(message) => write!(f, "{}", message),
+            PushError::Io(err) => write!(f, "{}", err),
+            PushError::Status(status) => write!(f, "шлюз ответил {}", status),
+        }
+    }
+}
+
+impl From<io::Error> for PushError {
+    fn from(err: io::Error) -> Self {
+        PushError::Io(err)
+    }
+}
+
+/// Адрес Pushgateway или другого приемника
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GatewayUrl {
+    pub host: String,
+    pub port: u16,
+    /// Путь без завершающей косой черты (пустой для корня)
+    pub path: String,
+}
+
+impl GatewayUrl {
+    /// Разбирает `http://хост[:порт][/путь]`; порт по умолчанию 80.
+    /// HTTPS не поддерживается
+    pub fn parse(url: &str) -> Result<GatewayUrl, PushError> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            PushError::InvalidUrl(format!("поддерживаются только адреса http://: {}", url))
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, ""),
+        };
+        // Двоеточия IPv6-адреса в квадратных скобках - не разделитель порта
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !authority.ends_with(']') => {
+                let port = port.parse().map_err(|_| PushError::InvalidUrl(format!("неверный порт: {}", port)))?;
+                (host, port)
+            },
+            _ => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(PushError::InvalidUrl(format!("в адресе нет хоста: {}", url)));
+        }
+        Ok(GatewayUrl { host: host.to_string(), port, path: path.trim_end_matches('/').to_string() })
+    }
+
+    /// Путь группы метрик задания: `<путь>/metrics/job/<job>`
+    pub fn job_path(&self, job: &str) -> String {
+        format!("{}/metrics/job/{}", self.path, percent_encode(job))
+    }
+}
+
+/// Отправляет метрики в текстовом формате Prometheus методом POST: метрики
+/// с теми же именами в группе задания заменяются, остальные сохраняются
+pub fn push(url: &GatewayUrl, job: &str, body: &str) -> Result<(), PushError> {
+    post(url, &url.job_path(job), "text/plain; version=0.0.4", body)
+}
+
+/// Отправляет body по пути path (от корня хоста, вместе с путем адреса) и
+/// ждет ответа с кодом 2xx
+pub fn post(url: &GatewayUrl, path: &str, content_type: &str, body: &str) -> Result<(), PushError> {
+    let address = (url.host.trim_start_matches('[').trim_end_matches(']'), url.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| PushError::InvalidUrl(format!("не удалось найти адрес хоста {}", url.host)))?;
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        path, url.host, url.port, content_type, body.len());
+    stream.write_all(request.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    let response = String::from_utf8_lossy(&response);
+    let status = response.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(PushError::Status(status.to_string())),
+    }
+}
+
+// Кодирует сегмент пути URL: все, кроме незарезервированных символов, - %XX
+fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gateway_url() {
+        let url = GatewayUrl::parse("http://push.local:9091/").unwrap();
+        assert_eq!(url, GatewayUrl { host: "push.local".to_string(), port: 9091, path: String::new() });
+        assert_eq!(url.job_path("disk usage"), "/metrics/job/disk%20usage");
+
+        let url = GatewayUrl::parse("http://[::1]/prefix").unwrap();
+        assert_eq!((url.host.as_str(), url.port, url.job_path("a").as_str()), ("[::1]", 80, "/prefix/metrics/job/a"));
+
+        assert!(GatewayUrl::parse("https://push.local").is_err());
+        assert!(GatewayUrl::parse("http://push.local:port").is_err());
+        assert!(GatewayUrl::parse("http:///metrics").is_err());
+    }
+}
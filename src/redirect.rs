@@ -0,0 +1,81 @@
+//! Перенаправление stdout процесса в файл (опция `--output`)
+
+use std::fs::File;
+use std::io::{self, Write};
+
+/// Пока значение живо, все, что печатается в stdout (в том числе через
+/// `println!`), записывается в файл. При уничтожении stdout восстанавливается
+pub struct StdoutRedirect {
+    saved: sys::SavedStdout,
+}
+
+impl StdoutRedirect {
+    pub fn to_file(file: &File) -> io::Result<StdoutRedirect> {
+        io::stdout().flush()?;
+        Ok(StdoutRedirect { saved: sys::redirect(file)? })
+    }
+}
+
+impl Drop for StdoutRedirect {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        sys::restore(&self.saved);
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::fs::File;
+    use std::io;
+    use std::os::raw::c_int;
+    use std::os::unix::io::AsRawFd;
+
+    const STDOUT_FILENO: c_int = 1;
+
+    extern "C" {
+        fn dup(fd: c_int) -> c_int;
+        fn dup2(old: c_int, new: c_int) -> c_int;
+        fn close(fd: c_int) -> c_int;
+    }
+
+    /// Копия исходного дескриптора stdout
+    pub struct SavedStdout(c_int);
+
+    pub fn redirect(file: &File) -> io::Result<SavedStdout> {
+        // SAFETY: dup и dup2 работают только с дескрипторами, ошибки проверяются
+        unsafe {
+            let saved = dup(STDOUT_FILENO);
+            if saved < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if dup2(file.as_raw_fd(), STDOUT_FILENO) < 0 {
+                let err = io::Error::last_os_error();
+                close(saved);
+                return Err(err);
+            }
+            Ok(SavedStdout(saved))
+        }
+    }
+
+    pub fn restore(saved: &SavedStdout) {
+        // SAFETY: saved.0 - дескриптор, полученный от dup и еще не закрытый
+        unsafe {
+            dup2(saved.0, STDOUT_FILENO);
+            close(saved.0);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::fs::File;
+    use std::io;
+
+    pub struct SavedStdout;
+
+    pub fn redirect(_file: &File) -> io::Result<SavedStdout> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "перенаправление вывода в файл не поддерживается на этой платформе"))
+    }
+
+    pub fn restore(_saved: &SavedStdout) {}
+}
@@ -0,0 +1,495 @@
+//! Регулярные выражения для `--regex-exclude`.
+//!
+//! Поддерживается распространенное подмножество синтаксиса: `.`, классы
+//! `[a-z]`, `[^/]`, `\d`, `\w`, `\s` (и `\D`, `\W`, `\S`), якоря `^` и `$`,
+//! группы `( )` с альтернативой `|`, квантификаторы `*`, `+`, `?`, `{m}`,
+//! `{m,}`, `{m,n}`. Шаблон компилируется в НКА и проверяется одним проходом
+//! по строке (как в Pike VM), поэтому время сопоставления линейно и не
+//! взрывается на шаблонах вроде `(a*)*b`
+
+use std::fmt;
+
+// Ограничение на {m,n}: каждое повторение разворачивается в копию выражения
+const MAX_REPEAT: u32 = 1000;
+
+/// Ошибка компиляции регулярного выражения
+#[derive(Debug, PartialEq)]
+pub enum RegexError {
+    /// Незакрытая скобка `(` или `[`
+    Unclosed(String),
+    /// Лишняя закрывающая скобка `)`
+    UnmatchedParen(String),
+    /// Квантификатор без выражения перед ним
+    NothingToRepeat(String),
+    /// Некорректный или слишком большой квантификатор `{m,n}`
+    InvalidRepeat(String),
+    /// Шаблон заканчивается на `\`
+    TrailingBackslash(String),
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegexError::Unclosed(pattern) => write!(f, "незакрытая скобка в регулярном выражении: {}", pattern),
+            RegexError::UnmatchedParen(pattern) => write!(f, "лишняя ')' в регулярном выражении: {}", pattern),
+            RegexError::NothingToRepeat(pattern) => write!(f, "квантификатору нечего повторять: {}", pattern),
+            RegexError::InvalidRepeat(pattern) => {
+                write!(f, "некорректный квантификатор {{m,n}} (не больше {}): {}", MAX_REPEAT, pattern)
+            },
+            RegexError::TrailingBackslash(pattern) => write!(f, "'\\' в конце регулярного выражения: {}", pattern),
+        }
+    }
+}
+
+// Класс символов: диапазоны включительно
+#[derive(Debug, Clone)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    fn matches(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != self.negated
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat { node: Box<Node>, min: u32, max: Option<u32> },
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// Скомпилированное регулярное выражение. Совпадение ищется в любом месте
+/// строки; для совпадения целиком используйте `^...$`
+#[derive(Debug, Clone)]
+pub struct Regex {
+    program: Vec<Inst>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        let mut parser = Parser { pattern, chars: pattern.chars().collect(), pos: 0 };
+        let node = parser.parse_alternate()?;
+        if parser.pos < parser.chars.len() {
+            return Err(RegexError::UnmatchedParen(pattern.to_string()));
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program);
+        program.push(Inst::Match);
+        Ok(Regex { program })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        // seen[pc] - номер позиции (с единицы), на которой инструкция уже в списке
+        let mut seen = vec![0; self.program.len()];
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        for pos in 0..=chars.len() {
+            // Поиск без якоря: новый поток стартует в каждой позиции
+            if self.add_thread(&mut current, &mut seen, 0, pos, &chars) {
+                return true;
+            }
+            let Some(&c) = chars.get(pos) else {
+                break;
+            };
+            for &pc in &current {
+                let consumed = match &self.program[pc] {
+                    Inst::Char(expected) => *expected == c,
+                    Inst::Any => true,
+                    Inst::Class(class) => class.matches(c),
+                    _ => false,
+                };
+                if consumed && self.add_thread(&mut next, &mut seen, pc + 1, pos + 1, &chars) {
+                    return true;
+                }
+            }
+            current.clear();
+            std::mem::swap(&mut current, &mut next);
+        }
+        false
+    }
+
+    // Добавляет поток pc в list, проходя по переходам без чтения символа;
+    // true, если достигнуто совпадение
+    fn add_thread(&self, list: &mut Vec<usize>, seen: &mut [usize], pc: usize, pos: usize, chars: &[char]) -> bool {
+        if seen[pc] == pos + 1 {
+            return false;
+        }
+        seen[pc] = pos + 1;
+        match &self.program[pc] {
+            Inst::Match => true,
+            Inst::Jump(target) => self.add_thread(list, seen, *target, pos, chars),
+            Inst::Split(first, second) => {
+                self.add_thread(list, seen, *first, pos, chars) || self.add_thread(list, seen, *second, pos, chars)
+            },
+            Inst::Start => pos == 0 && self.add_thread(list, seen, pc + 1, pos, chars),
+            Inst::End => pos == chars.len() && self.add_thread(list, seen, pc + 1, pos, chars),
+            _ => {
+                list.push(pc);
+                false
+            },
+        }
+    }
+}
+
+/// Набор регулярных выражений, объединенных по ИЛИ
+#[derive(Debug, Clone, Default)]
+pub struct RegexSet {
+    regexes: Vec<Regex>,
+}
+
+impl RegexSet {
+    pub fn new() -> Self {
+        RegexSet { regexes: Vec::new() }
+    }
+
+    pub fn add(&mut self, pattern: &str) -> Result<(), RegexError> {
+        self.regexes.push(Regex::new(pattern)?);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regexes.is_empty()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regexes.iter().any(|regex| regex.is_match(text))
+    }
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn parse_alternate(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            branches.push(self.parse_concat()?);
+        }
+        Ok(if branches.len() == 1 { branches.pop().unwrap() } else { Node::Alternate(branches) })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            items.push(self.parse_repeat(atom)?);
+        }
+        Ok(match items.len() {
+            0 => Node::Empty,
+            1 => items.pop().unwrap(),
+            _ => Node::Concat(items),
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let c = self.chars[self.pos];
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                // Группа без захвата (?:...) ничем не отличается от обычной
+                if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                }
+                let node = self.parse_alternate()?;
+                if self.peek() != Some(')') {
+                    return Err(RegexError::Unclosed(self.pattern.to_string()));
+                }
+                self.pos += 1;
+                node
+            },
+            '[' => Node::Class(self.parse_class()?),
+            '\\' => self.parse_escape()?,
+            '*' | '+' | '?' => return Err(RegexError::NothingToRepeat(self.pattern.to_string())),
+            '{' if self.repeat_bounds_ahead() => return Err(RegexError::NothingToRepeat(self.pattern.to_string())),
+            c => Node::Char(c),
+        })
+    }
+
+    // После '{' идет квантификатор, а не обычная фигурная скобка
+    fn repeat_bounds_ahead(&self) -> bool {
+        self.peek().is_some_and(|c| c.is_ascii_digit())
+    }
+
+    fn parse_repeat(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let mut node = atom;
+        loop {
+            let (min, max) = match self.peek() {
+                Some('{') if self.chars.get(self.pos + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                    self.pos += 1;
+                    self.parse_bounds()?
+                },
+                Some(c @ ('*' | '+' | '?')) => {
+                    self.pos += 1;
+                    match c {
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        _ => (0, Some(1)),
+                    }
+                },
+                _ => return Ok(node),
+            };
+            if matches!(node, Node::Start | Node::End | Node::Empty) {
+                return Err(RegexError::NothingToRepeat(self.pattern.to_string()));
+            }
+            node = Node::Repeat { node: Box::new(node), min, max };
+        }
+    }
+
+    // {m}, {m,}, {m,n}; pos - сразу после '{', в конце - после '}'
+    fn parse_bounds(&mut self) -> Result<(u32, Option<u32>), RegexError> {
+        let invalid = || RegexError::InvalidRepeat(self.pattern.to_string());
+        let end = self.chars[self.pos..].iter().position(|&c| c == '}').ok_or_else(invalid)? + self.pos;
+        let body: String = self.chars[self.pos..end].iter().collect();
+        self.pos = end + 1;
+        let parse = |s: &str| s.parse::<u32>().ok().filter(|&n| n <= MAX_REPEAT);
+        let (min, max) = match body.split_once(',') {
+            None => {
+                let n = parse(&body).ok_or_else(invalid)?;
+                (n, Some(n))
+            },
+            Some((min, "")) => (parse(min).ok_or_else(invalid)?, None),
+            Some((min, max)) => (parse(min).ok_or_else(invalid)?, Some(parse(max).ok_or_else(invalid)?)),
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(invalid());
+        }
+        Ok((min, max))
+    }
+
+    fn parse_escape(&mut self) -> Result<Node, RegexError> {
+        let c = self.peek().ok_or_else(|| RegexError::TrailingBackslash(self.pattern.to_string()))?;
+        self.pos += 1;
+        Ok(match escape_class(c) {
+            Some(class) => Node::Class(class),
+            None => Node::Char(escape_char(c)),
+        })
+    }
+
+    // pos - сразу после '['
+    fn parse_class(&mut self) -> Result<Class, RegexError> {
+        let unclosed = || RegexError::Unclosed(self.pattern.to_string());
+        let mut class = Class { ranges: Vec::new(), negated: false };
+        if self.peek() == Some('^') {
+            class.negated = true;
+            self.pos += 1;
+        }
+        let mut first = true;
+        loop {
+            let c = self.peek().ok_or_else(unclosed)?;
+            self.pos += 1;
+            // "]" сразу после открывающей скобки - обычный символ
+            if c == ']' && !first {
+                return Ok(class);
+            }
+            first = false;
+            let lo = if c == '\\' {
+                let escaped = self.peek().ok_or_else(unclosed)?;
+                self.pos += 1;
+                match escape_class(escaped) {
+                    Some(inner) if !inner.negated => {
+                        class.ranges.extend(inner.ranges);
+                        continue;
+                    },
+                    _ => escape_char(escaped),
+                }
+            } else {
+                c
+            };
+            // "a-z", но "-" в конце класса - обычный символ
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&next| next != ']') {
+                self.pos += 1;
+                let mut hi = self.chars[self.pos];
+                self.pos += 1;
+                if hi == '\\' {
+                    hi = escape_char(self.peek().ok_or_else(unclosed)?);
+                    self.pos += 1;
+                }
+                class.ranges.push((lo, hi));
+            } else {
+                class.ranges.push((lo, lo));
+            }
+        }
+    }
+}
+
+// \d, \w, \s и их отрицания
+fn escape_class(c: char) -> Option<Class> {
+    let (ranges, negated) = match c {
+        'd' | 'D' => (vec![('0', '9')], c == 'D'),
+        'w' | 'W' => (vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')], c == 'W'),
+        's' | 'S' => (vec![(' ', ' '), ('\t', '\r')], c == 'S'),
+        _ => return None,
+    };
+    Some(Class { ranges, negated })
+}
+
+fn escape_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        c => c,
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Empty => {},
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(class) => program.push(Inst::Class(class.clone())),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Concat(items) => {
+            for item in items {
+                compile(item, program);
+            }
+        },
+        Node::Alternate(branches) => {
+            // Split на каждую ветку, кроме последней; ветки прыгают в конец
+            let mut jumps = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                if i + 1 < branches.len() {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(branch, program);
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    let next = program.len();
+                    program[split] = Inst::Split(split + 1, next);
+                } else {
+                    compile(branch, program);
+                }
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        },
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program);
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program);
+                    program.push(Inst::Jump(split));
+                    let end = program.len();
+                    program[split] = Inst::Split(split + 1, end);
+                },
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile(node, program);
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                },
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn literals_classes_and_anchors() {
+        assert!(matches("target", "/home/user/project/target/debug"));
+        assert!(!matches("^target", "/home/user/project/target"));
+        assert!(matches(r"\.(log|tmp)$", "/var/log/syslog.log"));
+        assert!(!matches(r"\.(log|tmp)$", "/var/log/syslog.log.gz"));
+        assert!(matches(r"/build/[^/]+\.o$", "/src/build/main.o"));
+        assert!(!matches(r"/build/[^/]+\.o$", "/src/build/sub/main.o"));
+        assert!(matches(r"\d{4}-\d{2}", "/backup/2024-01-15"));
+        assert!(!matches(r"^\d{4}$", "12345"));
+        assert!(matches("[]a]", "]"));
+        assert!(matches("[a-]", "-"));
+    }
+
+    #[test]
+    fn repetition_and_alternation() {
+        assert!(matches("^a(bc)*d$", "ad"));
+        assert!(matches("^a(bc)*d$", "abcbcd"));
+        assert!(!matches("^a(bc)+d$", "ad"));
+        assert!(matches("^colou?r$", "color"));
+        assert!(matches("^x{2,3}$", "xxx"));
+        assert!(!matches("^x{2,3}$", "xxxx"));
+        assert!(matches("^x{2,}$", "xxxxx"));
+        assert!(matches("^(cat|dog|)$", ""));
+        assert!(matches("^(?:a|b)c$", "bc"));
+        // Шаблон с вложенными звездочками не должен зависать
+        let long = "a".repeat(5000);
+        assert!(!matches("^(a*)*b$", &long));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert_eq!(Regex::new("(ab").unwrap_err(), RegexError::Unclosed("(ab".to_string()));
+        assert_eq!(Regex::new("ab)").unwrap_err(), RegexError::UnmatchedParen("ab)".to_string()));
+        assert_eq!(Regex::new("*a").unwrap_err(), RegexError::NothingToRepeat("*a".to_string()));
+        assert_eq!(Regex::new("[abc").unwrap_err(), RegexError::Unclosed("[abc".to_string()));
+        assert_eq!(Regex::new("a{3,1}").unwrap_err(), RegexError::InvalidRepeat("a{3,1}".to_string()));
+        assert_eq!(Regex::new("a\\").unwrap_err(), RegexError::TrailingBackslash("a\\".to_string()));
+        assert!(matches("a{b", "a{b"));
+        assert!(matches("^x}?$", "x"));
+    }
+
+    #[test]
+    fn set_matches_any() {
+        let mut set = RegexSet::new();
+        assert!(!set.is_match("/a"));
+        set.add("/node_modules$").unwrap();
+        set.add(r"\.cache/").unwrap();
+        assert!(set.is_match("/p/node_modules"));
+        assert!(set.is_match("/home/u/.cache/x"));
+        assert!(!set.is_match("/home/u/cache"));
+    }
+}
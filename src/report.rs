@@ -0,0 +1,1179 @@
+//! Текстовый отчет для терминала
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashSet};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::classify::{categorize_extension, classify_well_known_dir, detect_cloud_sync, CloudService, FileCategory, WellKnownDir};
+use crate::color::{self, Color};
+use crate::compress::{savings_percent, SAMPLE_LEN};
+use crate::diff::{DirChange, DirGrowth};
+use crate::duplicates::DuplicateGroup;
+use crate::filesystem::{InodeUsage, SpaceUsage};
+use crate::format::{civil_from_days, format_size, format_timestamp, size_format};
+use crate::integrity::{to_sha256sum, FileHash, IntegrityReport};
+use crate::percentiles::Percentiles;
+use crate::term::terminal_width;
+use crate::scan::{DepthStats, DirInfo, DirSpan, GitRepo, HardLink, MimeMismatch, OwnerUsage};
+use crate::users::UserNames;
+
+/// Ключ сортировки таблицы директорий
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Size,
+    Name,
+    Count,
+    LargestFile,
+    /// Доля от общего размера; порядок совпадает с сортировкой по размеру
+    Percent,
+    /// Доля от объема файловой системы (колонка %DISK); порядок тоже по размеру
+    Disk,
+}
+
+impl SortKey {
+    /// Разбирает имя ключа из командной строки
+    pub fn parse(s: &str) -> Option<SortKey> {
+        match s {
+            "size" => Some(SortKey::Size),
+            "name" => Some(SortKey::Name),
+            "count" => Some(SortKey::Count),
+            "largest-file" => Some(SortKey::LargestFile),
+            "percent" => Some(SortKey::Percent),
+            "disk" => Some(SortKey::Disk),
+            _ => None,
+        }
+    }
+
+    /// Имя ключа, как его принимает `parse`
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Size => "size",
+            SortKey::Name => "name",
+            SortKey::Count => "count",
+            SortKey::LargestFile => "largest-file",
+            SortKey::Percent => "percent",
+            SortKey::Disk => "disk",
+        }
+    }
+}
+
+/// Период группировки файлов по дате изменения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateGrouping {
+    Day,
+    Month,
+}
+
+/// Ограничение числа строк в таблице: 0 означает "показать все"
+pub fn display_limit(n: usize) -> usize {
+    if n == 0 { usize::MAX } else { n }
+}
+
+/// Самые большие директории из потока (`scan_stream`): в памяти держится не
+/// больше limit директорий (0 - все)
+#[derive(Debug)]
+pub struct TopDirs {
+    limit: usize,
+    heap: BinaryHeap<Reverse<BySize>>,
+}
+
+// Порядок по размеру; при равенстве "больше" путь, который раньше по алфавиту
+#[derive(Debug)]
+struct BySize(String, DirInfo);
+
+impl Ord for BySize {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.1.size.cmp(&other.1.size).then_with(|| other.0.cmp(&self.0))
+    }
+}
+
+impl PartialOrd for BySize {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for BySize {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BySize {}
+
+impl TopDirs {
+    pub fn new(limit: usize) -> Self {
+        TopDirs { limit: display_limit(limit), heap: BinaryHeap::new() }
+    }
+
+    pub fn push(&mut self, path: String, info: DirInfo) {
+        let entry = BySize(path, info);
+        if self.heap.len() < self.limit {
+            self.heap.push(Reverse(entry));
+        } else if self.heap.peek().is_some_and(|Reverse(smallest)| entry > *smallest) {
+            self.heap.pop();
+            self.heap.push(Reverse(entry));
+        }
+    }
+
+    /// Директории по убыванию размера
+    pub fn into_sorted_vec(self) -> Vec<(String, DirInfo)> {
+        self.heap.into_sorted_vec().into_iter().map(|Reverse(BySize(path, info))| (path, info)).collect()
+    }
+}
+
+/// Сортирует директории по ключу. Размеры и счетчики по умолчанию идут по убыванию,
+/// имена - по алфавиту; reverse меняет порядок на противоположный
+pub fn sort_dirs(dirs: &mut [(String, DirInfo)], key: SortKey, reverse: bool) {
+    let largest = |info: &DirInfo| info.largest_file().map_or(0, |(_, size)| *size);
+    dirs.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Size | SortKey::Percent | SortKey::Disk => b.1.size.cmp(&a.1.size),
+            SortKey::Name => a.0.cmp(&b.0),
+            SortKey::Count => b.1.file_count.cmp(&a.1.file_count),
+            SortKey::LargestFile => largest(&b.1).cmp(&largest(&a.1)),
+        };
+        // При равенстве ключа упорядочиваем по пути, чтобы вывод был воспроизводимым
+        let ordering = ordering.then_with(|| a.0.cmp(&b.0));
+        if reverse { ordering.reverse() } else { ordering }
+    });
+}
+
+/// Печатает таблицу директорий (первые limit строк). Если известен объем
+/// файловой системы disk_total, добавляется колонка %DISK - доля от него, а с
+/// file_size_stats - средний и медианный размер файла
+pub fn print_top_dirs(
+    dirs: &[(String, DirInfo)],
+    limit: usize,
+    total: u64,
+    bar: bool,
+    disk_total: Option<u64>,
+    file_size_stats: bool,
+    cloud_dirs: &[(String, CloudService)],
+) {
+    println!("📁 ТОП ДИРЕКТОРИИ ПО РАЗМЕРУ:");
+    if let Some(disk_total) = disk_total {
+        println!("💽 %DISK - доля от объема файловой системы ({})", format_size(disk_total, size_format()));
+    }
+    // Полоска занимает место, оставшееся после колонок и ~30 символов на путь
+    let disk_width = if disk_total.is_some() { 8 } else { 0 };
+    let stats_width = if file_size_stats { 24 } else { 0 };
+    let bar_width = terminal_width().saturating_sub(15 + 1 + 12 + 1 + 9 + disk_width + stats_width + 30).clamp(10, 50);
+    let mut pct_header = if bar { format!("{:<w$}", "PCT", w = bar_width + 9) } else { format!("{:<7}", "PCT") };
+    if disk_total.is_some() {
+        pct_header.push_str(&format!(" {:<7}", "%DISK"));
+    }
+    let mut count_header = format!("{:<12}", "ФАЙЛОВ");
+    if file_size_stats {
+        count_header.push_str(&format!(" {:<11} {:<11}", "СРЕДНИЙ", "МЕДИАНА"));
+    }
+    println!("{}", color::bold(&format!("{:<15} {} {} {:<}", "РАЗМЕР", pct_header, count_header, "ПУТЬ")));
+    println!("{:-<60}", "");
+    
+    for (i, (path, info)) in dirs.iter().take(display_limit(limit)).enumerate() {
+        let pct = percent(info.size, total);
+        let mut pct_cell = if bar {
+            format!("{} {:>5.1}%", percent_bar(pct, bar_width), pct)
+        } else {
+            format!("{:>6.1}%", pct)
+        };
+        if let Some(disk_total) = disk_total {
+            pct_cell.push_str(&format!(" {:>6.1}%", percent(info.size, disk_total)));
+        }
+        let mut count_cell = format!("{:<12}", info.file_count);
+        if file_size_stats {
+            count_cell.push_str(&format!(" {:<11} {:<11}",
+                format_size(info.average_file_size, size_format()), format_size(info.median_file_size, size_format())));
+        }
+        let synced = cloud_dirs.iter().any(|(root, _)| Path::new(path).starts_with(root));
+        let path_cell = if synced { format!("☁ {}", path) } else { path.clone() };
+        let row = format!("{:<15} {} {} {:<}", 
+                format_size(info.size, size_format()), 
+                pct_cell,
+                count_cell,
+                path_cell);
+        // Самая большая директория - красным, следующие четыре - желтым
+        match i {
+            0 => println!("{}", color::paint(&row, Color::Red)),
+            1..=4 => println!("{}", color::paint(&row, Color::Yellow)),
+            _ => println!("{}", row),
+        }
+    }
+    for (root, service) in cloud_dirs {
+        println!("☁ {} синхронизируется с {}: удаленные локально файлы могут остаться в облаке и быть восстановлены оттуда",
+            root, service.label());
+    }
+}
+
+/// Корни синхронизируемых с облаком директорий среди root и dirs, по
+/// возрастанию пути; вложенные в уже найденный корень не проверяются
+pub fn collect_cloud_dirs(root: &Path, dirs: &[(String, DirInfo)]) -> Vec<(String, CloudService)> {
+    let mut paths: Vec<&str> = dirs.iter().map(|(path, _)| path.as_str()).collect();
+    paths.push(root.to_str().unwrap_or_default());
+    paths.sort_unstable();
+    
+    let mut found: Vec<(String, CloudService)> = Vec::new();
+    for path in paths {
+        if path.is_empty() || found.iter().any(|(parent, _)| Path::new(path).starts_with(parent)) {
+            continue;
+        }
+        if let Some(service) = detect_cloud_sync(Path::new(path)) {
+            found.push((path.to_string(), service));
+        }
+    }
+    found
+}
+
+/// Таблица директорий с наибольшим числом инодов
+pub fn print_top_inode_dirs(dirs: &[(String, DirInfo)], limit: usize, total: u64) {
+    let mut sorted: Vec<&(String, DirInfo)> = dirs.iter().collect();
+    sorted.sort_by(|a, b| b.1.inode_count.cmp(&a.1.inode_count).then_with(|| a.0.cmp(&b.0)));
+    
+    println!("\n🧮 ТОП ДИРЕКТОРИИ ПО ИНОДАМ:");
+    println!("{}", color::bold(&format!("{:<15} {:<7} {:<15} {:<}", "ИНОДОВ", "PCT", "РАЗМЕР", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for (path, info) in sorted.into_iter().take(display_limit(limit)) {
+        println!("{:<15} {:>6.1}% {:<15} {:<}",
+            info.inode_count, percent(info.inode_count, total), format_size(info.size, size_format()), path);
+    }
+}
+
+/// Таблица директорий по числу файлов вместе с вложенными, без размеров
+pub fn print_top_count_dirs(dirs: &[(String, DirInfo)], limit: usize, total: usize) {
+    println!("📁 ТОП ДИРЕКТОРИИ ПО ЧИСЛУ ФАЙЛОВ:");
+    println!("{}", color::bold(&format!("{:<12} {:<7} {:<}", "ФАЙЛОВ", "PCT", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for (i, (path, info)) in dirs.iter().take(display_limit(limit)).enumerate() {
+        let row = format!("{:<12} {:>6.1}% {:<}", info.file_count, percent(info.file_count as u64, total as u64), path);
+        match i {
+            0 => println!("{}", color::paint(&row, Color::Red)),
+            1..=4 => println!("{}", color::paint(&row, Color::Yellow)),
+            _ => println!("{}", row),
+        }
+    }
+}
+
+/// Число файлов непосредственно в каждой директории (без вложенных): из
+/// file_count директории вычитаются file_count ее прямых потомков. По убыванию
+/// числа файлов, директории без собственных файлов не включаются
+pub fn collect_own_file_counts(root: &str, total: &DirInfo, dirs: &[(String, DirInfo)]) -> Vec<(String, usize)> {
+    let mut nested: BTreeMap<&Path, usize> = BTreeMap::new();
+    for (path, info) in dirs {
+        if let Some(parent) = Path::new(path).parent() {
+            *nested.entry(parent).or_insert(0) += info.file_count;
+        }
+    }
+    let mut counts: Vec<(String, usize)> = std::iter::once((root, total))
+        .chain(dirs.iter().map(|(path, info)| (path.as_str(), info)))
+        .map(|(path, info)| (path.to_string(), info.file_count.saturating_sub(nested.get(Path::new(path)).copied().unwrap_or(0))))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Директории с наибольшим числом собственных файлов (см. [`collect_own_file_counts`])
+pub fn print_own_file_counts(counts: &[(String, usize)], limit: usize) {
+    println!("\n📂 ДИРЕКТОРИИ С НАИБОЛЬШИМ ЧИСЛОМ ФАЙЛОВ (БЕЗ ВЛОЖЕННЫХ):");
+    println!("{}", color::bold(&format!("{:<12} {:<}", "ФАЙЛОВ", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for (path, count) in counts.iter().take(display_limit(limit)) {
+        println!("{:<12} {:<}", count, path);
+    }
+}
+
+/// Статистика по расширениям, отсортированная по числу файлов
+pub fn print_file_type_counts(info: &DirInfo, limit: usize) {
+    let mut counts: Vec<(&String, &usize)> = info.file_type_counts.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    
+    println!("\n📊 ТИПЫ ФАЙЛОВ ПО КОЛИЧЕСТВУ:");
+    println!("{}", color::bold(&format!("{:<12} {:<7} {:<}", "ФАЙЛОВ", "PCT", "ТИП")));
+    println!("{:-<60}", "");
+    for (ext, count) in counts.into_iter().take(display_limit(limit)) {
+        let ext_name = if ext.is_empty() { "[без расширения]" } else { ext.as_str() };
+        println!("{:<12} {:>6.1}% {:<}", count, percent(*count as u64, info.file_count as u64), ext_name);
+    }
+}
+
+/// Иноды файловой системы корня сканирования с предупреждением при заполнении
+pub fn print_inode_usage(usage: &InodeUsage) {
+    println!("🧮 Иноды файловой системы: занято {} из {} ({:.1}%), свободно {}",
+        usage.used(), usage.total, usage.used_percent(), usage.free);
+    if usage.used_percent() > 90.0 {
+        println!("{}", color::paint("⚠️  Занято более 90% инодов: новые файлы скоро нельзя будет создать, даже при свободном месте", Color::Red));
+    }
+}
+
+/// Раздел с корзиной: размер каждой найденной директории корзины и
+/// предупреждение, если вместе они больше threshold
+pub fn print_trash(trash: &[(PathBuf, DirInfo)], threshold: u64) {
+    println!("🗑  КОРЗИНА:");
+    println!("{}", color::bold(&format!("{:<15} {:<12} {:<}", "РАЗМЕР", "ФАЙЛОВ", "ПУТЬ")));
+    println!("{:-<60}", "");
+    if trash.is_empty() {
+        println!("Корзина не найдена.");
+        return;
+    }
+    for (path, info) in trash {
+        println!("{:<15} {:<12} {}", format_size(info.size, size_format()), info.file_count, path.display());
+    }
+    let total: u64 = trash.iter().map(|(_, info)| info.size).sum();
+    if total > threshold {
+        println!("{}", color::paint(&format!("⚠️  Корзина занимает {} (больше {}): очистите ее, чтобы освободить место",
+            format_size(total, size_format()), format_size(threshold, size_format())), Color::Yellow));
+    }
+}
+
+/// Распределение директорий, файлов и размера по уровням вложенности;
+/// полоска - доля уровня от общего размера
+pub fn print_depth_histogram(depth_stats: &[DepthStats], bar_width: usize) {
+    let total: u64 = depth_stats.iter().map(|level| level.size).sum();
+    println!("\n📶 РАСПРЕДЕЛЕНИЕ ПО ГЛУБИНЕ:");
+    println!("{}", color::bold(&format!("{:<8} {:<12} {:<12} {:<15} {:<}", "ГЛУБИНА", "ДИРЕКТОРИЙ", "ФАЙЛОВ", "РАЗМЕР", "PCT")));
+    println!("{:-<60}", "");
+    for (depth, level) in depth_stats.iter().enumerate() {
+        let pct = percent(level.size, total);
+        println!("{:<8} {:<12} {:<12} {:<15} {} {:>5.1}%", depth, level.dir_count, level.file_count,
+            format_size(level.size, size_format()), fill_bar(pct, bar_width), pct);
+    }
+}
+
+/// Сводит размеры по дням (`ScanResult::mtime_days`) к периодам grouping:
+/// (`YYYY-MM-DD` или `YYYY-MM`, размер, число файлов) по возрастанию даты
+pub fn group_by_date(days: &BTreeMap<i64, (u64, usize)>, grouping: DateGrouping) -> Vec<(String, u64, usize)> {
+    let mut groups: Vec<(String, u64, usize)> = Vec::new();
+    for (&day, &(size, count)) in days {
+        let (year, month, day) = civil_from_days(day);
+        let label = match grouping {
+            DateGrouping::Day => format!("{:04}-{:02}-{:02}", year, month, day),
+            DateGrouping::Month => format!("{:04}-{:02}", year, month),
+        };
+        // Дни упорядочены, поэтому дни одного месяца идут подряд
+        match groups.last_mut() {
+            Some(last) if last.0 == label => {
+                last.1 += size;
+                last.2 += count;
+            },
+            _ => groups.push((label, size, count)),
+        }
+    }
+    groups
+}
+
+/// Размер и число файлов по дате изменения; полоска - доля периода от
+/// общего размера
+pub fn print_date_groups(groups: &[(String, u64, usize)], grouping: DateGrouping, bar_width: usize) {
+    let (title, column) = match grouping {
+        DateGrouping::Day => ("ПО ДНЯМ", "ДЕНЬ"),
+        DateGrouping::Month => ("ПО МЕСЯЦАМ", "МЕСЯЦ"),
+    };
+    let total: u64 = groups.iter().map(|(_, size, _)| size).sum();
+    println!("\n📅 ИЗМЕНЕННЫЕ ФАЙЛЫ {} (UTC):", title);
+    println!("{}", color::bold(&format!("{:<12} {:<15} {:<12} {:<}", column, "РАЗМЕР", "ФАЙЛОВ", "PCT")));
+    println!("{:-<60}", "");
+    if groups.is_empty() {
+        println!("Файлы не найдены.");
+    }
+    for (label, size, count) in groups {
+        let pct = percent(*size, total);
+        println!("{:<12} {:<15} {:<12} {} {:>5.1}%", label, format_size(*size, size_format()), count, fill_bar(pct, bar_width), pct);
+    }
+}
+
+/// Директории, дольше всего читавшиеся сами по себе (без поддиректорий), по
+/// спанам --tracing
+pub fn print_slowest_dirs(spans: &[DirSpan], limit: usize) {
+    let mut slowest: Vec<&DirSpan> = spans.iter().collect();
+    slowest.sort_by(|a, b| b.own_duration().cmp(&a.own_duration()).then_with(|| a.path.cmp(&b.path)));
+
+    println!("\n⏱  САМЫЕ МЕДЛЕННЫЕ ДИРЕКТОРИИ:");
+    println!("{}", color::bold(&format!("{:<12} {:<12} {:<10} {:<}", "СВОЕ ВРЕМЯ", "ВСЕГО", "ЭЛЕМЕНТОВ", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for span in slowest.into_iter().take(display_limit(limit)) {
+        let millis = |duration: std::time::Duration| format!("{:.2} мс", duration.as_secs_f64() * 1000.0);
+        println!("{:<12} {:<12} {:<10} {}", millis(span.own_duration()), millis(span.duration()), span.child_count, span.path.display());
+    }
+    println!("💡 Свое время - чтение элементов директории без обхода поддиректорий, всего - вместе с ними");
+}
+
+/// Место на файловой системе пути без сканирования (режим --free)
+pub fn print_space_usage(path: &Path, usage: &SpaceUsage) {
+    let fs_type = usage.fs_type.as_deref().unwrap_or("неизвестная ФС");
+    println!("{}", color::bold(&format!("💽 {} ({})", path.display(), fs_type)));
+    println!("{:-<60}", "");
+    println!("Всего:     {}", format_size(usage.total, size_format()));
+    println!("Занято:    {} ({:.1}%)", format_size(usage.used(), size_format()), usage.used_percent());
+    println!("Доступно:  {}", format_size(usage.available, size_format()));
+    let bar = percent_bar(usage.used_percent(), 40);
+    let bar = if usage.used_percent() > 90.0 { color::paint(&bar, Color::Red) } else { bar };
+    println!("{}\n", bar);
+}
+
+/// Сводка по корням сканирования при нескольких путях в командной строке
+pub fn print_root_summary(roots: &[(String, DirInfo)], total: u64) {
+    println!("🗂  СВОДКА ПО КОРНЯМ:");
+    println!("{}", color::bold(&format!("{:<15} {:<7} {:<12} {:<}", "РАЗМЕР", "PCT", "ФАЙЛОВ", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for (path, info) in roots {
+        println!("{:<15} {:>6.1}% {:<12} {:<}", format_size(info.size, size_format()), percent(info.size, total), info.file_count, path);
+    }
+    println!("{:<15} {:>6.1}% {:<12} {:<}\n", format_size(total, size_format()), 100.0, roots.iter().map(|(_, i)| i.file_count).sum::<usize>(), "ИТОГО");
+}
+
+/// Доля size от total в процентах; для пустого сканирования - 0
+pub fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { size as f64 / total as f64 * 100.0 }
+}
+
+/// Полоска вида `[████░░░░]` шириной width символов внутри скобок
+pub fn percent_bar(pct: f64, width: usize) -> String {
+    format!("[{}]", fill_bar(pct, width))
+}
+
+/// Полоска `████░░░░` из width символов, заполненная на pct процентов
+pub fn fill_bar(pct: f64, width: usize) -> String {
+    let filled = ((pct.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// Использование по категориям файлов. В подробном режиме под каждой
+/// категорией перечисляются ее расширения
+pub fn print_categories(info: &DirInfo, verbose: bool) {
+    let mut categories: Vec<(FileCategory, u64)> = info.categories.iter().map(|(c, s)| (*c, *s)).collect();
+    categories.sort_by_key(|(category, size)| (std::cmp::Reverse(*size), *category));
+    
+    println!("\n🏷  ИСПОЛЬЗОВАНИЕ ПО КАТЕГОРИЯМ:");
+    println!("{}", color::bold(&format!("{:<15} {:<7} {:<}", "РАЗМЕР", "PCT", "КАТЕГОРИЯ")));
+    println!("{:-<60}", "");
+    
+    for (category, size) in categories {
+        println!("{:<15} {:>6.1}% {}", format_size(size, size_format()), percent(size, info.size), category);
+        if verbose {
+            let mut extensions: Vec<(&String, &u64)> = info.file_types.iter()
+                .filter(|(ext, _)| categorize_extension(ext) == category)
+                .collect();
+            extensions.sort_by_key(|(_, size)| std::cmp::Reverse(**size));
+            for (ext, ext_size) in extensions {
+                let ext_name = if ext.is_empty() { "[без расширения]" } else { ext.as_str() };
+                println!("    {:<15} {}", format_size(*ext_size, size_format()), ext_name);
+            }
+        }
+    }
+}
+
+/// Символы ветвей дерева директорий
+#[derive(Debug, Clone, Copy)]
+pub struct TreeStyle {
+    branch: &'static str,
+    last: &'static str,
+    pipe: &'static str,
+    space: &'static str,
+}
+
+impl TreeStyle {
+    pub const UNICODE: TreeStyle = TreeStyle { branch: "├── ", last: "└── ", pipe: "│   ", space: "    " };
+    pub const ASCII: TreeStyle = TreeStyle { branch: "+-- ", last: "`-- ", pipe: "|   ", space: "    " };
+}
+
+/// Дерево директорий с размерами, как у `tree`. Показываются только
+/// директории не меньше min_size; дочерние - по убыванию размера
+pub fn print_tree(root: &str, total: &DirInfo, dirs: &[(String, DirInfo)], min_size: u64, style: TreeStyle) {
+    // Родителем считаем ближайшего предка, который есть в списке: при
+    // нескольких корнях между общим предком и корнями бывают пропуски
+    let known: HashSet<&Path> = dirs.iter().map(|(path, _)| Path::new(path.as_str())).collect();
+    let mut children: BTreeMap<&Path, Vec<(&str, &DirInfo)>> = BTreeMap::new();
+    for (path, info) in dirs {
+        if info.size < min_size {
+            continue;
+        }
+        let mut parent = Path::new(path.as_str()).parent();
+        while let Some(dir) = parent {
+            if dir == Path::new(root) || known.contains(dir) {
+                children.entry(dir).or_default().push((path, info));
+                break;
+            }
+            parent = dir.parent();
+        }
+    }
+    for list in children.values_mut() {
+        list.sort_by(|a, b| b.1.size.cmp(&a.1.size).then_with(|| a.0.cmp(b.0)));
+    }
+
+    println!("{} ({})", root, format_size(total.size, size_format()));
+    print_tree_children(Path::new(root), &children, "", style);
+}
+
+fn print_tree_children(dir: &Path, children: &BTreeMap<&Path, Vec<(&str, &DirInfo)>>, prefix: &str, style: TreeStyle) {
+    let Some(list) = children.get(dir) else {
+        return;
+    };
+    for (i, (path, info)) in list.iter().enumerate() {
+        let is_last = i + 1 == list.len();
+        let path = Path::new(path);
+        let name = path.file_name().map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+        println!("{}{}{} ({})", prefix, if is_last { style.last } else { style.branch }, name, format_size(info.size, size_format()));
+        let child_prefix = format!("{}{}", prefix, if is_last { style.space } else { style.pipe });
+        print_tree_children(path, children, &child_prefix, style);
+    }
+}
+
+/// Краткая таблица для скриптов: "<байты> <путь>" на строку, как у `du -sb`
+pub fn print_quiet_dirs(dirs: &[(String, DirInfo)], limit: usize) {
+    for (path, info) in dirs.iter().take(display_limit(limit)) {
+        println!("{} {}", info.size, path);
+    }
+}
+
+/// Все файлы в виде `<байты>\t<путь>`, по убыванию размера (как
+/// `find -printf '%s\t%p\n' | sort -rn`). Файлов может быть очень много,
+/// поэтому вывод буферизуется, а ошибка записи (например, закрытый канал
+/// после `| head`) возвращается вызывающему
+pub fn print_find_format(files: &mut [(PathBuf, u64)], record: FindRecord) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    write_find_format(&mut out, files, record)?;
+    out.flush()
+}
+
+/// Запись списка файлов --find-format и --print0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindRecord {
+    /// `<байты>\t<путь>` и перевод строки
+    Line,
+    /// `<байты>\t<путь>` и NUL (--find-format --print0)
+    SizeNul,
+    /// Только путь и NUL, как у `find -print0` (--print0)
+    PathNul,
+}
+
+/// Записывает файлы по убыванию размера; с NUL-разделителем пути выводятся
+/// байт в байт, без замены недопустимых символов
+pub fn write_find_format(out: &mut impl Write, files: &mut [(PathBuf, u64)], record: FindRecord) -> io::Result<()> {
+    files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (path, size) in files.iter() {
+        match record {
+            FindRecord::Line => writeln!(out, "{}\t{}", size, path.display())?,
+            FindRecord::SizeNul => {
+                write!(out, "{}\t", size)?;
+                out.write_all(&path_bytes(path))?;
+                out.write_all(b"\0")?;
+            },
+            FindRecord::PathNul => {
+                out.write_all(&path_bytes(path))?;
+                out.write_all(b"\0")?;
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn path_bytes(path: &Path) -> std::borrow::Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    std::borrow::Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_bytes(path: &Path) -> std::borrow::Cow<'_, [u8]> {
+    std::borrow::Cow::Owned(path.to_string_lossy().into_owned().into_bytes())
+}
+
+pub fn print_largest_files(largest_files: &[(PathBuf, u64)], limit: usize) {
+    println!("\n📄 САМЫЕ БОЛЬШИЕ ФАЙЛЫ:");
+    println!("{}", color::bold(&format!("{:<15} {:<}", "РАЗМЕР", "ПУТЬ")));
+    println!("{:-<60}", "");
+    
+    for (path, size) in largest_files.iter().take(display_limit(limit)) {
+        println!("{:<15} {:<}", format_size(*size, size_format()), path.display());
+    }
+}
+
+/// Распределение размеров файлов по перцентилям
+pub fn print_percentiles(percentiles: &Percentiles) {
+    println!("\n📐 РАСПРЕДЕЛЕНИЕ РАЗМЕРОВ ФАЙЛОВ:");
+    println!("{}", color::bold(&format!("{:<11} {:<}", "ПЕРЦЕНТИЛЬ", "РАЗМЕР")));
+    println!("{:-<60}", "");
+    for (label, size) in percentiles.entries() {
+        println!("{:<11} {:<}", label, format_size(size, size_format()));
+    }
+    println!("90% файлов не больше {}", format_size(percentiles.p90, size_format()));
+}
+
+/// Печатает статистику по расширениям, отсортированную по размеру
+pub fn print_file_types(info: &DirInfo, limit: usize, chart_width: Option<usize>) {
+    let mut file_types_vec: Vec<(&String, &u64)> = info.file_types.iter().collect();
+    file_types_vec.sort_by_key(|b| std::cmp::Reverse(*b.1));
+    
+    println!("\n📊 ИСПОЛЬЗОВАНИЕ ПО ТИПАМ ФАЙЛОВ:");
+    println!("{}", color::bold(&format!("{:<15} {:<}", "РАЗМЕР", "ТИП")));
+    println!("{:-<60}", "");
+    
+    // Длина полосок считается относительно самого объемного типа
+    let max_size = file_types_vec.first().map_or(0, |(_, size)| **size);
+    for (i, (ext, size)) in file_types_vec.iter().take(display_limit(limit)).enumerate() {
+        let ext_name = if ext.is_empty() { "[без расширения]" } else { ext.as_str() };
+        match chart_width {
+            Some(width) => {
+                let bar = fill_bar(percent(**size, max_size), width);
+                let bar = match i {
+                    0 => color::paint(&bar, Color::Red),
+                    1..=4 => color::paint(&bar, Color::Yellow),
+                    _ => color::paint(&bar, Color::Green),
+                };
+                println!("{:<15} {:<18} {}", format_size(**size, size_format()), ext_name, bar);
+            },
+            None => println!("{:<15} {:<}", format_size(**size, size_format()), ext_name),
+        }
+    }
+}
+
+/// Оценка экономии от сжатия gzip по типам файлов, по убыванию экономии
+pub fn print_compress_estimates(estimates: &BTreeMap<String, (u64, u64)>, limit: usize) {
+    let mut rows: Vec<(&String, u64, u64)> = estimates.iter().map(|(ext, &(original, compressed))| (ext, original, compressed)).collect();
+    rows.sort_by_key(|&(ext, original, compressed)| (Reverse(original.saturating_sub(compressed)), ext));
+
+    println!("\n🗜  ОЦЕНКА СЖАТИЯ ПО ТИПАМ ФАЙЛОВ:");
+    println!("{}", color::bold(&format!("{:<15} {:<15} {:<10} {:<}", "РАЗМЕР", "ПОСЛЕ СЖАТИЯ", "ЭКОНОМИЯ", "ТИП")));
+    println!("{:-<60}", "");
+    for (ext, original, compressed) in rows.iter().take(display_limit(limit)) {
+        let ext_name = if ext.is_empty() { "[без расширения]" } else { ext.as_str() };
+        println!("{:<15} {:<15} {:>8.1}%  {}", format_size(*original, size_format()), format_size(*compressed, size_format()),
+            savings_percent(*original, *compressed), ext_name);
+    }
+    let original: u64 = rows.iter().map(|(_, original, _)| original).sum();
+    let compressed: u64 = rows.iter().map(|(_, _, compressed)| compressed).sum();
+    println!("{:-<60}", "");
+    println!("{:<15} {:<15} {:>8.1}%  всего", format_size(original, size_format()), format_size(compressed, size_format()),
+        savings_percent(original, compressed));
+    println!("{}", color::paint(&format!("⚠️  Это оценка по энтропии первых {} каждого файла, а не гарантия: реальный результат gzip может отличаться",
+        format_size(SAMPLE_LEN as u64, size_format())), Color::Yellow));
+}
+
+/// Файл и время его изменения
+pub type FileTime = (PathBuf, SystemTime);
+
+/// Возвращает до limit самых старых и самых новых файлов среди всех директорий
+pub fn collect_mtime_extremes<'a, I>(infos: I, limit: usize) -> (Vec<FileTime>, Vec<FileTime>)
+where
+    I: IntoIterator<Item = &'a DirInfo>,
+{
+    let mut oldest: Vec<FileTime> = Vec::new();
+    let mut newest: Vec<FileTime> = Vec::new();
+    for info in infos {
+        oldest.extend(info.oldest_files.iter().cloned());
+        newest.extend(info.newest_files.iter().cloned());
+    }
+
+    // Один и тот же файл может быть крайним сразу для нескольких вложенных директорий
+    oldest.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    oldest.dedup_by(|a, b| a.0 == b.0);
+    oldest.truncate(limit);
+
+    newest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    newest.dedup_by(|a, b| a.0 == b.0);
+    newest.truncate(limit);
+
+    (oldest, newest)
+}
+
+pub fn print_mtime_extremes(oldest: &[FileTime], newest: &[FileTime]) {
+    if oldest.is_empty() && newest.is_empty() {
+        return;
+    }
+
+    println!("\n🕒 ВРЕМЯ ИЗМЕНЕНИЯ ФАЙЛОВ (UTC):");
+    println!("{}", color::bold(&format!("{:<20} {:<}", "ИЗМЕНЕН", "ПУТЬ")));
+    println!("{:-<60}", "");
+    println!("Самые старые:");
+    for (path, modified) in oldest {
+        println!("{:<20} {}", format_timestamp(*modified), path.display());
+    }
+    println!("Самые новые:");
+    for (path, modified) in newest {
+        println!("{:<20} {}", format_timestamp(*modified), path.display());
+    }
+}
+
+/// Печатает строку с суммарным объемом временных файлов
+pub fn print_temp_summary(info: &DirInfo) {
+    println!("\n🗑  ВРЕМЕННЫЕ ФАЙЛЫ: {} файлов, {} (*.tmp, *.swp, *.bak, *~, Thumbs.db и т.п.)",
+        info.temp_count, format_size(info.temp_size, size_format()));
+}
+
+/// Системные файлы Windows; на других платформах раздел не выводится
+pub fn print_system_files(info: &DirInfo) {
+    if info.system_count == 0 {
+        return;
+    }
+    println!("\n🛡  СИСТЕМНЫЕ ФАЙЛЫ: {} файлов, {} (удалять их вручную почти никогда не безопасно)",
+        info.system_count, format_size(info.system_size, size_format()));
+}
+
+/// Скрытые файлы Windows и то, учтены ли они в размерах
+pub fn print_hidden_files(info: &DirInfo, shown: bool) {
+    if info.hidden_count == 0 {
+        return;
+    }
+    let note = if shown { "учтены в размерах" } else { "не учтены в размерах, см. --show-hidden" };
+    println!("\n👁  СКРЫТЫЕ ФАЙЛЫ: {} файлов, {} ({})", info.hidden_count, format_size(info.hidden_size, size_format()), note);
+}
+
+/// Объем файлов ресурсных вилок macOS (`._имя`)
+pub fn print_resource_forks(info: &DirInfo) {
+    if info.resource_fork_count == 0 {
+        return;
+    }
+    println!("\n🍏 РЕСУРСНЫЕ ВИЛКИ MACOS: {} файлов ._*, {} служебных данных",
+        info.resource_fork_count, format_size(info.resource_fork_size, size_format()));
+}
+
+/// Хеши SHA-256 файлов в формате `sha256sum`
+pub fn print_file_hashes(hashes: &[FileHash]) {
+    println!("\n🔑 SHA-256 ФАЙЛОВ ({}):", hashes.len());
+    println!("{:-<60}", "");
+    print!("{}", to_sha256sum(hashes));
+}
+
+/// Результат проверки целостности по сохраненным хешам
+pub fn print_integrity_report(report: &IntegrityReport) {
+    println!("\n🔍 ПРОВЕРКА ЦЕЛОСТНОСТИ:");
+    println!("{:-<60}", "");
+    if report.is_clean() {
+        println!("{}", color::paint("✅ Изменений нет", Color::Green));
+        return;
+    }
+    for (title, paths, color) in [
+        ("Новые файлы", &report.added, Color::Green),
+        ("Удаленные файлы", &report.deleted, Color::Red),
+        ("Измененные файлы", &report.modified, Color::Yellow),
+    ] {
+        if paths.is_empty() {
+            continue;
+        }
+        println!("{} ({}):", title, paths.len());
+        for path in paths {
+            println!("  {}", color::paint(&path.display().to_string(), color));
+        }
+    }
+}
+
+/// Файлы, у которых расширение не соответствует содержимому
+pub fn print_mime_mismatches(mismatches: &[MimeMismatch]) {
+    if mismatches.is_empty() {
+        return;
+    }
+    
+    println!("\n🎭 РАСШИРЕНИЕ НЕ СООТВЕТСТВУЕТ СОДЕРЖИМОМУ ({}):", mismatches.len());
+    println!("{}", color::bold(&format!("{:<12} {:<28} {:<}", "РАСШИРЕНИЕ", "ФАКТИЧЕСКИЙ ТИП", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for mismatch in mismatches {
+        println!("{:<12} {:<28} {}", format!(".{}", mismatch.extension), mismatch.mime, mismatch.path.display());
+    }
+}
+
+/// Файлы, доступные на запись всем, и файлы с битом SUID/SGID
+pub fn print_security_report(world_writable: &[PathBuf], suid_files: &[PathBuf]) {
+    println!("\n🔐 АУДИТ БЕЗОПАСНОСТИ:");
+    println!("{:-<60}", "");
+    println!("Доступны на запись всем ({}):", world_writable.len());
+    for path in world_writable {
+        println!("  {}", color::paint(&path.display().to_string(), Color::Red));
+    }
+    println!("С битом SUID/SGID ({}):", suid_files.len());
+    for path in suid_files {
+        println!("  {}", color::paint(&path.display().to_string(), Color::Yellow));
+    }
+    if !world_writable.is_empty() {
+        println!("💡 Уберите право записи для всех: chmod o-w ФАЙЛ");
+    }
+}
+
+/// Занятое место по владельцам файлов; total - общий размер для процентов
+pub fn print_owner_summary(owners: &[OwnerUsage], total: u64) {
+    if owners.is_empty() {
+        return;
+    }
+    
+    println!("\n👤 ВЛАДЕЛЬЦЫ ФАЙЛОВ:");
+    println!("{}", color::bold(&format!("{:<15} {:<7} {:<12} {:<}", "РАЗМЕР", "PCT", "ФАЙЛОВ", "ВЛАДЕЛЕЦ")));
+    println!("{:-<60}", "");
+    let mut names = UserNames::default();
+    for owner in owners {
+        println!("{:<15} {:>6.1}% {:<12} {}", format_size(owner.size, size_format()), percent(owner.size, total), owner.file_count,
+            names.name(owner.uid));
+    }
+}
+
+/// Таблица для --group-by-owner: место, число файлов и самый большой файл
+/// каждого пользователя
+pub fn print_owners_table(owners: &[OwnerUsage]) {
+    println!("\n👤 МЕСТО ПО ПОЛЬЗОВАТЕЛЯМ:");
+    println!("{}", color::bold(&format!("{:<16} {:<15} {:<12} {:<}", "ПОЛЬЗОВАТЕЛЬ", "РАЗМЕР", "ФАЙЛОВ", "САМЫЙ БОЛЬШОЙ ФАЙЛ")));
+    println!("{:-<60}", "");
+    if owners.is_empty() {
+        println!("Файлы не найдены.");
+    }
+    let mut names = UserNames::default();
+    for owner in owners {
+        let largest = match &owner.largest_file {
+            Some((path, size)) => format!("{} ({})", path.display(), format_size(*size, size_format())),
+            None => "-".to_string(),
+        };
+        println!("{:<16} {:<15} {:<12} {}", names.name(owner.uid), format_size(owner.size, size_format()), owner.file_count, largest);
+    }
+}
+
+/// Резервные копии Time Machine, найденные при сканировании
+pub fn print_time_machine_backups(backups: &[(PathBuf, u64)]) {
+    if backups.is_empty() {
+        return;
+    }
+    
+    println!("\n⏱  РЕЗЕРВНЫЕ КОПИИ TIME MACHINE:");
+    println!("{}", color::bold(&format!("{:<15} {:<}", "РАЗМЕР", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for (path, size) in backups {
+        println!("{:<15} {}", format_size(*size, size_format()), path.display());
+    }
+    println!("💡 Удаляйте старые копии через Time Machine или tmutil delete, а не вручную");
+}
+
+pub fn print_zero_byte_files(files: &[PathBuf]) {
+    println!("\n🕳  ФАЙЛЫ НУЛЕВОГО РАЗМЕРА ({}):", files.len());
+    println!("{:-<60}", "");
+    if files.is_empty() {
+        println!("Файлы нулевого размера не найдены.");
+    }
+    for path in files {
+        println!("{}", path.display());
+    }
+}
+
+pub fn print_empty_dirs(empty_dirs: &[PathBuf]) {
+    println!("\n📭 ПУСТЫЕ ДИРЕКТОРИИ ({}):", empty_dirs.len());
+    println!("{:-<60}", "");
+    if empty_dirs.is_empty() {
+        println!("Пустые директории не найдены.");
+    }
+    for path in empty_dirs {
+        println!("{}", path.display());
+    }
+}
+
+pub fn print_broken_symlinks(broken_symlinks: &[PathBuf]) {
+    if broken_symlinks.is_empty() {
+        return;
+    }
+    
+    println!("\n🔗 БИТЫЕ СИМВОЛИЧЕСКИЕ ССЫЛКИ ({}):", broken_symlinks.len());
+    println!("{:-<60}", "");
+    for path in broken_symlinks {
+        match fs::read_link(path) {
+            Ok(target) => println!("{} -> {}", path.display(), target.display()),
+            Err(_) => println!("{}", path.display()),
+        }
+    }
+}
+
+/// Ссылки на директорию-предка, пропущенные при обходе с --follow-symlinks
+pub fn print_circular_symlinks(circular_symlinks: &[PathBuf]) {
+    if circular_symlinks.is_empty() {
+        return;
+    }
+    
+    println!("\n🔁 ЦИКЛИЧЕСКИЕ СИМВОЛИЧЕСКИЕ ССЫЛКИ ({}):", circular_symlinks.len());
+    println!("{:-<60}", "");
+    for path in circular_symlinks {
+        match fs::read_link(path) {
+            Ok(target) => println!("{} -> {}", path.display(), target.display()),
+            Err(_) => println!("{}", path.display()),
+        }
+    }
+}
+
+/// Файлы с несколькими жесткими ссылками: размер учтен один раз
+pub fn print_hard_links(links: &[HardLink]) {
+    if links.is_empty() {
+        return;
+    }
+    
+    println!("\n🔗 ЖЕСТКИЕ ССЫЛКИ ({}):", links.len());
+    println!("{}", color::bold(&format!("{:<15} {:<8} {:<}", "РАЗМЕР", "ССЫЛОК", "ПУТИ")));
+    println!("{:-<60}", "");
+    for link in links {
+        let mut paths = link.paths.iter();
+        if let Some(first) = paths.next() {
+            println!("{:<15} {:<8} {}", format_size(link.size, size_format()), link.nlink, first.display());
+        }
+        for path in paths {
+            println!("{:<15} {:<8} {}", "", "", path.display());
+        }
+    }
+}
+
+/// Пути, пропущенные при сканировании из-за ошибок, с текстом ошибки
+pub fn print_skipped_paths(skipped: &[(PathBuf, String)]) {
+    if skipped.is_empty() {
+        return;
+    }
+    
+    println!("\n⛔ ПРОПУЩЕННЫЕ ПУТИ ({}):", skipped.len());
+    println!("{:-<60}", "");
+    for (path, error) in skipped {
+        println!("{}: {}", path.display(), error);
+    }
+}
+
+/// Точки монтирования, в которые обход не заходил
+pub fn print_skipped_mounts(mounts: &[PathBuf]) {
+    if mounts.is_empty() {
+        return;
+    }
+    
+    println!("\n🔌 ПРОПУЩЕННЫЕ ТОЧКИ МОНТИРОВАНИЯ ({}):", mounts.len());
+    println!("{:-<60}", "");
+    for path in mounts {
+        println!("{} (используйте --follow-mounts, чтобы учитывать)", path.display());
+    }
+}
+
+pub fn print_duplicates(groups: &[DuplicateGroup]) {
+    println!("\n🧬 ДУБЛИКАТЫ ФАЙЛОВ:");
+    println!("{}", color::bold(&format!("{:<18} {:<8} {:<15} {:<}", "ХЕШ", "КОПИЙ", "ЛИШНЕЕ", "ПУТИ")));
+    println!("{:-<60}", "");
+
+    if groups.is_empty() {
+        println!("Дубликаты не найдены.");
+        return;
+    }
+
+    let mut total_wasted = 0;
+    for group in groups {
+        total_wasted += group.wasted();
+        println!("{:<18} {:<8} {:<15} {}",
+            &group.hash[..16],
+            group.paths.len(),
+            format_size(group.wasted(), size_format()),
+            group.paths[0].display());
+        for path in &group.paths[1..] {
+            println!("{:<44}{}", "", path.display());
+        }
+    }
+    println!("Всего можно освободить: {}", format_size(total_wasted, size_format()));
+}
+
+/// Лишнее место по группам дубликатов из вывода `fdupes` (без хешей)
+pub fn print_duplicate_waste(groups: &[DuplicateGroup]) {
+    println!("🧬 АНАЛИЗ ЛИШНЕГО МЕСТА ОТ ДУБЛИКАТОВ:");
+    println!("{}", color::bold(&format!("{:<15} {:<8} {:<15} {:<}", "РАЗМЕР", "КОПИЙ", "ЛИШНЕЕ", "ПУТИ")));
+    println!("{:-<60}", "");
+
+    if groups.is_empty() {
+        println!("Дубликаты не найдены.");
+        return;
+    }
+
+    for group in groups {
+        println!("{:<15} {:<8} {:<15} {}",
+            format_size(group.size, size_format()),
+            group.paths.len(),
+            format_size(group.wasted(), size_format()),
+            group.paths[0].display());
+        for path in &group.paths[1..] {
+            println!("{:<41}{}", "", path.display());
+        }
+    }
+    let copies: usize = groups.iter().map(|group| group.paths.len() - 1).sum();
+    let wasted: u64 = groups.iter().map(DuplicateGroup::wasted).sum();
+    println!("Групп: {}, лишних копий: {}, можно освободить: {}", groups.len(), copies, format_size(wasted, size_format()));
+}
+
+/// Таблица сравнения двух деревьев: новые директории - зеленым,
+/// удаленные - красным, изменившиеся - желтым
+pub fn print_comparison(changes: &[DirChange], limit: usize) {
+    println!("🔀 РАЗЛИЧИЯ ДИРЕКТОРИЙ:");
+    if changes.is_empty() {
+        println!("{:-<60}", "");
+        println!("Различий выше порога не найдено");
+        return;
+    }
+
+    let shown = &changes[..changes.len().min(display_limit(limit))];
+    let path_width = shown.iter().map(|c| c.path.chars().count()).max().unwrap_or(0).clamp(4, 60);
+    println!("{}", color::bold(&format!("{:<w$} {:<15} {:<15}", "ПУТЬ", "СЛЕВА", "СПРАВА", w = path_width)));
+    println!("{:-<60}", "");
+
+    let side = |size: Option<u64>| size.map_or_else(|| "-".to_string(), |size| format_size(size, size_format()));
+    for change in shown {
+        let row = format!("{:<w$} {:<15} {:<15}", change.path, side(change.old_size), side(change.new_size), w = path_width);
+        let row = match (change.old_size, change.new_size) {
+            (None, _) => color::paint(&row, Color::Green),
+            (_, None) => color::paint(&row, Color::Red),
+            _ => color::paint(&row, Color::Yellow),
+        };
+        println!("{}", row);
+    }
+    if shown.len() < changes.len() {
+        println!("... и еще {} (используйте -n 0, чтобы показать все)", changes.len() - shown.len());
+    }
+}
+
+/// Директории с наибольшей скоростью роста между двумя снимками
+pub fn print_top_growth(growth: &[DirGrowth], limit: usize) {
+    println!("📈 БЫСТРЕЕ ВСЕГО РАСТУЩИЕ ДИРЕКТОРИИ:");
+    if growth.is_empty() {
+        println!("{:-<60}", "");
+        println!("Ни одна директория не выросла");
+        return;
+    }
+
+    let shown = &growth[..growth.len().min(display_limit(limit))];
+    println!("{}", color::bold(&format!("{:<17} {:<15} {:<15} {:<}", "РОСТ В ДЕНЬ", "БЫЛО", "СТАЛО", "ПУТЬ")));
+    println!("{:-<60}", "");
+    let side = |size: Option<u64>| size.map_or_else(|| "-".to_string(), |size| format_size(size, size_format()));
+    for entry in shown {
+        let rate = format!("+{}", format_size(entry.bytes_per_day.round() as u64, size_format()));
+        println!("{:<17} {:<15} {:<15} {}", rate, side(entry.change.old_size), side(entry.change.new_size), entry.change.path);
+    }
+    if shown.len() < growth.len() {
+        println!("... и еще {} (используйте -n 0, чтобы показать все)", growth.len() - shown.len());
+    }
+}
+
+/// Найденный кэш пакетного менеджера: путь, вид и размер
+pub type WellKnownEntry = (String, WellKnownDir, u64);
+
+/// Директории кэшей пакетных менеджеров среди всех директорий (не только топа).
+/// Вложенные в уже найденную директорию (`node_modules` внутри `node_modules`)
+/// не учитываются повторно
+pub fn collect_well_known_dirs(dirs: &[(String, DirInfo)]) -> Vec<WellKnownEntry> {
+    let mut sorted: Vec<&(String, DirInfo)> = dirs.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    
+    let mut found: Vec<WellKnownEntry> = Vec::new();
+    for (path, info) in sorted {
+        let nested = found.iter().any(|(parent, _, _)| Path::new(path).starts_with(parent));
+        if nested {
+            continue;
+        }
+        if let Some(kind) = classify_well_known_dir(Path::new(path)) {
+            found.push((path.clone(), kind, info.size));
+        }
+    }
+    found.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    found
+}
+
+/// Раздел с кэшами пакетных менеджеров: суммарный размер по каждому виду,
+/// команда очистки и самые большие директории
+pub fn print_well_known_dirs(found: &[WellKnownEntry]) {
+    if found.is_empty() {
+        return;
+    }
+    
+    let mut kinds: Vec<(WellKnownDir, u64, Vec<&WellKnownEntry>)> = Vec::new();
+    for entry in found {
+        match kinds.iter_mut().find(|(kind, _, _)| *kind == entry.1) {
+            Some((_, total, entries)) => {
+                *total += entry.2;
+                entries.push(entry);
+            },
+            None => kinds.push((entry.1, entry.2, vec![entry])),
+        }
+    }
+    kinds.sort_by_key(|(kind, total, _)| (std::cmp::Reverse(*total), *kind));
+    
+    println!("\n📦 КЭШИ ПАКЕТНЫХ МЕНЕДЖЕРОВ:");
+    println!("{}", color::bold(&format!("{:<15} {:<8} {:<}", "РАЗМЕР", "ДИР.", "ТИП")));
+    println!("{:-<60}", "");
+    for (kind, total, entries) in kinds {
+        println!("{:<15} {:<8} {}", format_size(total, size_format()), entries.len(), kind.label());
+        for (path, _, size) in entries.iter().take(3) {
+            println!("    {:<15} {}", format_size(*size, size_format()), path);
+        }
+        if entries.len() > 3 {
+            println!("    ... и еще {}", entries.len() - 3);
+        }
+        println!("    💡 {}", kind.cleanup_command());
+    }
+}
+
+/// Раздел с Git-репозиториями: размер `.git` и его доля в размере репозитория
+pub fn print_git_repos(repos: &[GitRepo]) {
+    if repos.is_empty() {
+        return;
+    }
+    
+    let mut sorted: Vec<&GitRepo> = repos.iter().collect();
+    sorted.sort_by_key(|repo| std::cmp::Reverse(repo.git_size));
+    
+    println!("\n🌿 GIT-РЕПОЗИТОРИИ:");
+    println!("{}", color::bold(&format!("{:<15} {:<15} {:>6}  {:<}", ".GIT", "РЕПОЗИТОРИЙ", "ДОЛЯ", "ПУТЬ")));
+    println!("{:-<60}", "");
+    for repo in sorted {
+        println!("{:<15} {:<15} {:>5.1}%  {}",
+            format_size(repo.git_size, size_format()), format_size(repo.repo_size, size_format()), repo.git_percent(), repo.path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(size: u64) -> DirInfo {
+        DirInfo { size, ..DirInfo::default() }
+    }
+
+    #[test]
+    fn find_format_records() {
+        let mut files = vec![(PathBuf::from("/p/a b"), 10), (PathBuf::from("/p/new\nline"), 20)];
+        let write = |files: &mut Vec<(PathBuf, u64)>, record| {
+            let mut out = Vec::new();
+            write_find_format(&mut out, files, record).unwrap();
+            out
+        };
+        assert_eq!(write(&mut files, FindRecord::Line), b"20\t/p/new\nline\n10\t/p/a b\n");
+        assert_eq!(write(&mut files, FindRecord::SizeNul), b"20\t/p/new\nline\x0010\t/p/a b\x00");
+        assert_eq!(write(&mut files, FindRecord::PathNul), b"/p/new\nline\x00/p/a b\x00");
+    }
+
+    #[test]
+    fn groups_days_by_month() {
+        // 2024-01-31, 2024-02-01 и 2024-02-29
+        let days = BTreeMap::from([(19753, (10, 1)), (19754, (20, 2)), (19782, (5, 1))]);
+        assert_eq!(group_by_date(&days, DateGrouping::Day), vec![
+            ("2024-01-31".to_string(), 10, 1), ("2024-02-01".to_string(), 20, 2), ("2024-02-29".to_string(), 5, 1)]);
+        assert_eq!(group_by_date(&days, DateGrouping::Month), vec![("2024-01".to_string(), 10, 1), ("2024-02".to_string(), 25, 3)]);
+    }
+
+    #[test]
+    fn top_dirs_keeps_largest() {
+        let mut top = TopDirs::new(3);
+        for (path, size) in [("a", 5), ("b", 50), ("c", 1), ("d", 20), ("e", 20), ("f", 7)] {
+            top.push(path.to_string(), dir(size));
+        }
+        let paths: Vec<String> = top.into_sorted_vec().into_iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec!["b", "d", "e"]);
+
+        let mut all = TopDirs::new(0);
+        all.push("x".to_string(), dir(1));
+        all.push("y".to_string(), dir(2));
+        assert_eq!(all.into_sorted_vec().len(), 2);
+    }
+
+    #[test]
+    fn own_file_counts_exclude_nested() {
+        let counted = |file_count| DirInfo { file_count, ..DirInfo::default() };
+        let dirs = vec![
+            ("/m/a".to_string(), counted(7)),
+            ("/m/a/cur".to_string(), counted(5)),
+            ("/m/a/new".to_string(), counted(2)),
+            ("/m/b".to_string(), counted(3)),
+        ];
+        let counts = collect_own_file_counts("/m", &counted(11), &dirs);
+        assert_eq!(counts, vec![("/m/a/cur".to_string(), 5), ("/m/b".to_string(), 3), ("/m/a/new".to_string(), 2), ("/m".to_string(), 1)]);
+    }
+}
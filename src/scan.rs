@@ -0,0 +1,2098 @@
+//! Рекурсивное сканирование директорий
+
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::classify::{
+    categorize_extension, classify_temp_file, detect_mime, extension_mismatch, is_macos_metadata, is_resource_fork,
+    mime_group, FileCategory, MIME_HEADER_LEN,
+};
+use crate::compress;
+use crate::filesystem::{filesystem_type, mount_points};
+use crate::format::unix_seconds;
+use crate::glob::{GlobError, GlobSet};
+use crate::regex::{RegexError, RegexSet};
+
+/// Точки монтирования виртуальных файловых систем, которые не обходятся
+/// без `ScanOptions::include_virtual`
+pub const VIRTUAL_FILESYSTEMS: &[&str] = &["/proc", "/sys", "/dev", "/run", "/sys/fs/cgroup"];
+
+/// Верхняя граница числа потоков по умолчанию: на одном диске больше
+/// потоков почти не ускоряют сканирование
+pub const DEFAULT_MAX_JOBS: usize = 8;
+
+/// Сколько первых файлов каждой директории учитывается при выборке
+/// (`ScanOptions::sample_percent`) всегда: небольшие директории считаются точно
+pub const SAMPLE_MIN_FILES: usize = 16;
+
+/// Сколько самых больших файлов хранит каждая директория
+/// (`ScanOptions::top_files`) по умолчанию
+pub const DEFAULT_TOP_FILES: usize = 10;
+
+/// Сколько самых старых и самых новых файлов хранит каждая директория
+pub const MTIME_FILES: usize = 5;
+
+/// Информация о директории (с учетом всех вложенных поддиректорий)
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirInfo {
+    pub size: u64,
+    pub file_count: usize,
+    /// Самые большие файлы директории вместе с вложенными, по убыванию
+    /// размера (при равенстве - по пути); не больше `ScanOptions::top_files`
+    pub top_files: Vec<(PathBuf, u64)>,
+    /// Расширение (в нижнем регистре) -> суммарный размер файлов
+    pub file_types: BTreeMap<String, u64>,
+    /// Расширение (ключ как в `file_types`) -> число файлов
+    pub file_type_counts: BTreeMap<String, usize>,
+    /// Категория файлов (см. `categorize_extension`) -> суммарный размер
+    pub categories: BTreeMap<FileCategory, u64>,
+    /// Файлы с самым ранним временем изменения, от самого старого (при
+    /// равенстве - по пути); не больше `MTIME_FILES`
+    #[cfg_attr(feature = "serde", serde(with = "file_time_serde"))]
+    pub oldest_files: Vec<(PathBuf, SystemTime)>,
+    /// Файлы с самым поздним временем изменения, от самого нового; не больше `MTIME_FILES`
+    #[cfg_attr(feature = "serde", serde(with = "file_time_serde"))]
+    pub newest_files: Vec<(PathBuf, SystemTime)>,
+    /// Суммарный размер и количество временных файлов (см. `classify_temp_file`)
+    pub temp_size: u64,
+    pub temp_count: usize,
+    /// Число занятых инодов (сама директория и все элементы в ней), если
+    /// включен `ScanOptions::count_inodes`; жесткие ссылки учитываются один раз
+    pub inode_count: u64,
+    /// Место, фактически выделенное файлам на диске, если включен
+    /// `ScanOptions::disk_usage` (тогда с ним совпадает и `size`)
+    pub allocated_size: u64,
+    /// Файлы с атрибутом «скрытый» (только Windows); учитываются здесь,
+    /// даже если пропущены без `ScanOptions::show_hidden`
+    pub hidden_size: u64,
+    pub hidden_count: usize,
+    /// Файлы с атрибутом «системный» (только Windows)
+    pub system_size: u64,
+    pub system_count: usize,
+    /// Файлы ресурсных вилок macOS (`._имя`, см. `is_resource_fork`)
+    pub resource_fork_size: u64,
+    pub resource_fork_count: usize,
+    /// Файлы нулевого размера
+    pub zero_byte_count: usize,
+    /// Средний размер файла: `size / file_count`
+    pub average_file_size: u64,
+    /// Медиана размеров файлов директории вместе с поддиректориями. `merge`
+    /// ее не пересчитывает: для этого нужны размеры всех файлов
+    pub median_file_size: u64,
+}
+
+impl DirInfo {
+    pub fn new() -> Self {
+        DirInfo {
+            size: 0,
+            file_count: 0,
+            top_files: Vec::new(),
+            file_types: BTreeMap::new(),
+            file_type_counts: BTreeMap::new(),
+            categories: BTreeMap::new(),
+            oldest_files: Vec::new(),
+            newest_files: Vec::new(),
+            temp_size: 0,
+            temp_count: 0,
+            inode_count: 0,
+            allocated_size: 0,
+            hidden_size: 0,
+            hidden_count: 0,
+            system_size: 0,
+            system_count: 0,
+            resource_fork_size: 0,
+            resource_fork_count: 0,
+            zero_byte_count: 0,
+            average_file_size: 0,
+            median_file_size: 0,
+        }
+    }
+
+    /// Самый большой файл
+    pub fn largest_file(&self) -> Option<&(PathBuf, u64)> {
+        self.top_files.first()
+    }
+
+    /// Файл с самым ранним временем изменения
+    pub fn oldest_file(&self) -> Option<&(PathBuf, SystemTime)> {
+        self.oldest_files.first()
+    }
+
+    /// Файл с самым поздним временем изменения
+    pub fn newest_file(&self) -> Option<&(PathBuf, SystemTime)> {
+        self.newest_files.first()
+    }
+
+    /// Первые n самых больших файлов (0 - все)
+    pub fn top_n_files(&self, n: usize) -> Vec<(PathBuf, u64)> {
+        self.top_files.iter().take(if n == 0 { usize::MAX } else { n }).cloned().collect()
+    }
+
+    /// Добавляет файл в `top_files`, оставляя не больше limit самых больших
+    pub fn add_top_file(&mut self, path: &Path, size: u64, limit: usize) {
+        let at = self.top_files.partition_point(|(p, s)| *s > size || *s == size && p.as_path() < path);
+        if at >= limit {
+            return;
+        }
+        self.top_files.insert(at, (path.to_path_buf(), size));
+        self.top_files.truncate(limit);
+    }
+
+    /// Добавляет к текущей информации данные поддиректории; `top_files`
+    /// объединяются целиком (см. [`DirInfo::merge_limited`])
+    pub fn merge(&mut self, other: &DirInfo) {
+        self.merge_limited(other, usize::MAX);
+    }
+
+    /// Как [`DirInfo::merge`], но в `top_files` остается не больше
+    /// top_files самых больших файлов
+    pub fn merge_limited(&mut self, other: &DirInfo, top_files: usize) {
+        self.size += other.size;
+        self.file_count += other.file_count;
+        self.temp_size += other.temp_size;
+        self.temp_count += other.temp_count;
+        self.inode_count += other.inode_count;
+        self.allocated_size += other.allocated_size;
+        self.hidden_size += other.hidden_size;
+        self.hidden_count += other.hidden_count;
+        self.system_size += other.system_size;
+        self.system_count += other.system_count;
+        self.resource_fork_size += other.resource_fork_size;
+        self.resource_fork_count += other.resource_fork_count;
+        self.zero_byte_count += other.zero_byte_count;
+        self.average_file_size = average(self.size, self.file_count);
+
+        if !other.top_files.is_empty() {
+            self.top_files.extend(other.top_files.iter().cloned());
+            self.top_files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            self.top_files.truncate(top_files);
+        }
+
+        for (ext, size) in &other.file_types {
+            *self.file_types.entry(ext.clone()).or_insert(0) += size;
+        }
+        for (ext, count) in &other.file_type_counts {
+            *self.file_type_counts.entry(ext.clone()).or_insert(0) += count;
+        }
+        for (category, size) in &other.categories {
+            *self.categories.entry(*category).or_insert(0) += size;
+        }
+
+        for (path, modified) in &other.oldest_files {
+            insert_file_time(&mut self.oldest_files, path, *modified, false);
+        }
+        for (path, modified) in &other.newest_files {
+            insert_file_time(&mut self.newest_files, path, *modified, true);
+        }
+    }
+
+    // Средний и медианный размер по размерам всех файлов директории
+    fn set_file_size_stats(&mut self, sizes: &mut [u64]) {
+        self.average_file_size = average(self.size, self.file_count);
+        self.median_file_size = if sizes.is_empty() {
+            0
+        } else {
+            let middle = sizes.len() / 2;
+            *sizes.select_nth_unstable(middle).1
+        };
+    }
+
+    // Обновляет списки самых старых и самых новых файлов
+    fn update_mtime(&mut self, path: &Path, modified: SystemTime) {
+        insert_file_time(&mut self.oldest_files, path, modified, false);
+        insert_file_time(&mut self.newest_files, path, modified, true);
+    }
+}
+
+// Вставляет файл в список, упорядоченный по времени изменения (newest_first -
+// по убыванию), оставляя не больше MTIME_FILES первых
+fn insert_file_time(list: &mut Vec<(PathBuf, SystemTime)>, path: &Path, modified: SystemTime, newest_first: bool) {
+    let at = list.partition_point(|(p, time)| {
+        let before = if newest_first { *time > modified } else { *time < modified };
+        before || *time == modified && p.as_path() < path
+    });
+    if at >= MTIME_FILES || list.get(at).is_some_and(|(p, _)| p == path) {
+        return;
+    }
+    list.insert(at, (path.to_path_buf(), modified));
+    list.truncate(MTIME_FILES);
+}
+
+// Время изменения в serde-представлении - секунды Unix, а не структура
+// { secs_since_epoch, nanos_since_epoch }, как у SystemTime по умолчанию
+#[cfg(feature = "serde")]
+mod file_time_serde {
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::format::{from_unix_seconds, unix_seconds};
+
+    pub fn serialize<S: Serializer>(value: &[(PathBuf, SystemTime)], serializer: S) -> Result<S::Ok, S::Error> {
+        value.iter().map(|(path, time)| (path, unix_seconds(*time))).collect::<Vec<_>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(PathBuf, SystemTime)>, D::Error> {
+        let value = Vec::<(PathBuf, i64)>::deserialize(deserializer)?;
+        Ok(value.into_iter().map(|(path, seconds)| (path, from_unix_seconds(seconds))).collect())
+    }
+}
+
+// Моменты спанов в serde-представлении - наносекунды от начала эпохи Unix:
+// секунд для длительности обхода директории мало
+#[cfg(feature = "serde")]
+mod span_time_serde {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let nanos = value.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos());
+        u64::try_from(nanos).unwrap_or(u64::MAX).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let nanos = u64::deserialize(deserializer)?;
+        Ok(UNIX_EPOCH + Duration::from_nanos(nanos))
+    }
+}
+
+/// Диапазон размеров файлов, учитываемых при сканировании
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeFilter {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl SizeFilter {
+    pub fn is_active(&self) -> bool {
+        self.min.is_some() || self.max.is_some()
+    }
+
+    pub fn contains(&self, size: u64) -> bool {
+        let above_min = match self.min {
+            Some(min) => size >= min,
+            None => true,
+        };
+        let below_max = match self.max {
+            Some(max) => size <= max,
+            None => true,
+        };
+        above_min && below_max
+    }
+}
+
+/// Диапазон времени изменения файлов, учитываемых при сканировании
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MtimeFilter {
+    /// Файл изменен не раньше этого момента
+    pub newer_than: Option<SystemTime>,
+    /// Файл изменен раньше этого момента
+    pub older_than: Option<SystemTime>,
+}
+
+impl MtimeFilter {
+    pub fn is_active(&self) -> bool {
+        self.newer_than.is_some() || self.older_than.is_some()
+    }
+
+    pub fn contains(&self, modified: SystemTime) -> bool {
+        let newer = match self.newer_than {
+            Some(newer_than) => modified >= newer_than,
+            None => true,
+        };
+        let older = match self.older_than {
+            Some(older_than) => modified < older_than,
+            None => true,
+        };
+        newer && older
+    }
+}
+
+/// Параметры сканирования
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Число потоков сканирования; при 1 сканирование строго последовательное.
+    /// По умолчанию - число логических ядер, но не больше `DEFAULT_MAX_JOBS`
+    pub jobs: usize,
+    /// Максимальная глубина: директория на уровне max_depth (у корня уровень 0)
+    /// учитывает только свои файлы, а при 0 корень лишь проверяется на существование
+    pub max_depth: Option<u32>,
+    pub size_filter: SizeFilter,
+    /// Файлы вне диапазона (и файлы без времени изменения при активном
+    /// фильтре) не учитываются
+    pub mtime_filter: MtimeFilter,
+    pub follow_symlinks: bool,
+    /// Собирать список временных файлов в `ScanResult::temp_files`
+    pub collect_temp_files: bool,
+    /// Собирать список файлов нулевого размера в `ScanResult::zero_byte_files`
+    pub collect_zero_byte_files: bool,
+    /// Пропускаемые пути (шаблоны сравниваются с путем относительно корня)
+    pub exclude: GlobSet,
+    /// Пропускаемые пути по регулярным выражениям; сравниваются с абсолютным
+    /// путем элемента
+    pub regex_exclude: RegexSet,
+    /// Если не пуст, учитываются только файлы, совпавшие хотя бы с одним
+    /// шаблоном; директории обходятся как обычно. `exclude` действует поверх
+    pub include: GlobSet,
+    /// Если задано, все файлы не меньше этого размера попадают в `ScanResult::files`
+    pub collect_files_min_size: Option<u64>,
+    /// Не учитывать и не обходить директории `.git`
+    pub skip_git: bool,
+    /// Подсчитывать иноды в `DirInfo::inode_count`
+    pub count_inodes: bool,
+    /// Считать размером файла выделенные блоки, а не логическую длину:
+    /// разреженные и сжатые файлы занимают на диске меньше своей длины
+    pub disk_usage: bool,
+    /// Учитывать в размере и место, занятое самими директориями (как `du`)
+    pub count_dir_sizes: bool,
+    /// Округлять размер каждого файла и директории вверх до кратного этому
+    /// размеру блока (кластера) файловой системы
+    pub block_size: Option<u64>,
+    /// Прерывать сканирование при любой ошибке вместо пропуска недоступного пути
+    pub fail_on_error: bool,
+    /// Учитывать размер файла с несколькими жесткими ссылками для каждой ссылки,
+    /// а не только для первой найденной
+    pub count_hardlinks: bool,
+    /// Собирать файлы с несколькими жесткими ссылками в `ScanResult::hard_links`
+    pub collect_hard_links: bool,
+    /// Не переходить на другие файловые системы и в точки монтирования, в том
+    /// числе bind-монтирования той же файловой системы (как `du -x`)
+    pub one_filesystem: bool,
+    /// Обходить и директории из `VIRTUAL_FILESYSTEMS`
+    pub include_virtual: bool,
+    /// Учитывать скрытые файлы Windows (по умолчанию они пропускаются)
+    pub show_hidden: bool,
+    /// Пропускать служебные файлы и директории macOS (см. `is_macos_metadata`)
+    pub skip_macos_metadata: bool,
+    /// Учитывать только файлы владельца с этим uid (на платформах без uid
+    /// при заданном фильтре файлы не учитываются)
+    pub owner: Option<u32>,
+    /// Подсчитывать занятое место по владельцам в `ScanResult::owners`
+    pub collect_owners: bool,
+    /// Группировать `DirInfo::file_types` по MIME-типу содержимого (`image/`,
+    /// `video/`...), а не по расширению; файлы нераспознанного типа остаются
+    /// под своим расширением. Файлы, у которых расширение противоречит
+    /// содержимому, попадают в `ScanResult::mime_mismatches`
+    pub mime_classify: bool,
+    /// Собирать файлы, опасные с точки зрения безопасности, в
+    /// `ScanResult::world_writable` и `ScanResult::suid_files` (только Unix)
+    pub collect_security: bool,
+    /// Печатать в stderr пути по мере обхода, чтобы было видно, где сканирование
+    /// зависло: 1 - каждую директорию (`[SCAN] depth=3 /путь`), 2 - и каждый файл
+    pub trace: u8,
+    /// Флаг прерывания: как только он выставлен, обход останавливается, а
+    /// результат содержит уже собранные данные и `ScanResult::cancelled`
+    pub cancel: Option<Arc<AtomicBool>>,
+    /// Остановить обход, как только суммарный размер учтенных файлов превысит
+    /// бюджет; результат содержит собранные данные и `ScanResult::budget_exceeded`
+    pub size_budget: Option<u64>,
+    /// Оценить размер по случайной выборке: читать метаданные только этого
+    /// процента файлов (1-100) и экстраполировать размер и число файлов.
+    /// Статистика по типам и самые большие файлы строятся только по выборке
+    pub sample_percent: Option<u8>,
+    /// Сохранить размеры всех учтенных файлов в `ScanResult::file_sizes`
+    /// (для перцентилей, см. `compute_percentiles`)
+    pub collect_file_sizes: bool,
+    /// Считать медиану размеров файлов каждой директории (`DirInfo::median_file_size`).
+    /// Размеры всех файлов поддерева хранятся до конца его обхода, поэтому
+    /// память растет с числом файлов, в том числе у `scan_stream`
+    pub file_size_median: bool,
+    /// Сколько самых больших файлов хранит `DirInfo::top_files` каждой директории
+    pub top_files: usize,
+    /// Собирать размер и число файлов по дням изменения в `ScanResult::mtime_days`
+    pub collect_mtime_days: bool,
+    /// Записывать время обхода каждой директории в `ScanResult::spans`
+    pub collect_spans: bool,
+    /// Оценивать размер файлов после сжатия в `ScanResult::compress_estimates`
+    /// (читает до `compress::SAMPLE_LEN` байт каждого файла)
+    pub compress_estimate: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            jobs: thread::available_parallelism().map_or(1, |n| n.get()).min(DEFAULT_MAX_JOBS),
+            max_depth: None,
+            size_filter: SizeFilter::default(),
+            mtime_filter: MtimeFilter::default(),
+            follow_symlinks: false,
+            collect_temp_files: false,
+            collect_zero_byte_files: false,
+            exclude: GlobSet::new(),
+            regex_exclude: RegexSet::new(),
+            include: GlobSet::new(),
+            collect_files_min_size: None,
+            skip_git: false,
+            count_inodes: false,
+            disk_usage: false,
+            count_dir_sizes: false,
+            block_size: None,
+            fail_on_error: false,
+            count_hardlinks: false,
+            collect_hard_links: false,
+            one_filesystem: false,
+            include_virtual: false,
+            show_hidden: false,
+            skip_macos_metadata: false,
+            owner: None,
+            collect_owners: false,
+            collect_security: false,
+            trace: 0,
+            cancel: None,
+            size_budget: None,
+            sample_percent: None,
+            collect_file_sizes: false,
+            file_size_median: false,
+            collect_mtime_days: false,
+            collect_spans: false,
+            compress_estimate: false,
+            mime_classify: false,
+            top_files: DEFAULT_TOP_FILES,
+        }
+    }
+}
+
+// Построитель для вызова scan_directory из библиотеки:
+// ScanOptions::new().max_depth(3).min_size(1024). Параметры без отдельного
+// метода задаются напрямую через поля
+impl ScanOptions {
+    /// Параметры по умолчанию, как у `ScanOptions::default()`
+    pub fn new() -> Self {
+        ScanOptions::default()
+    }
+
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.size_filter.min = Some(min_size);
+        self
+    }
+
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.size_filter.max = Some(max_size);
+        self
+    }
+
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Добавляет шаблоны исключения в синтаксисе `--exclude`; ошибка - первый
+    /// некорректный шаблон
+    pub fn exclude_patterns<I, S>(mut self, patterns: I) -> Result<Self, GlobError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for pattern in patterns {
+            self.exclude.add(pattern.as_ref())?;
+        }
+        Ok(self)
+    }
+
+    /// Добавляет регулярные выражения в синтаксисе `--regex-exclude`
+    pub fn regex_exclude_patterns<I, S>(mut self, patterns: I) -> Result<Self, RegexError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for pattern in patterns {
+            self.regex_exclude.add(pattern.as_ref())?;
+        }
+        Ok(self)
+    }
+
+    /// Добавляет шаблоны отбора файлов в синтаксисе `--include`
+    pub fn include_patterns<I, S>(mut self, patterns: I) -> Result<Self, GlobError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for pattern in patterns {
+            self.include.add(pattern.as_ref())?;
+        }
+        Ok(self)
+    }
+
+    pub fn one_filesystem(mut self, one_filesystem: bool) -> Self {
+        self.one_filesystem = one_filesystem;
+        self
+    }
+
+    pub fn show_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = show_hidden;
+        self
+    }
+
+    pub fn disk_usage(mut self, disk_usage: bool) -> Self {
+        self.disk_usage = disk_usage;
+        self
+    }
+
+    pub fn block_size(mut self, block_size: Option<u64>) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    pub fn skip_git(mut self, skip_git: bool) -> Self {
+        self.skip_git = skip_git;
+        self
+    }
+
+    pub fn fail_on_error(mut self, fail_on_error: bool) -> Self {
+        self.fail_on_error = fail_on_error;
+        self
+    }
+
+    /// Сколько самых больших файлов хранить в каждой директории; 0 - все
+    pub fn top_files(mut self, top_files: usize) -> Self {
+        self.top_files = if top_files == 0 { usize::MAX } else { top_files };
+        self
+    }
+}
+
+/// Git-репозиторий, найденный при сканировании
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GitRepo {
+    /// Корень рабочей копии (директория, содержащая `.git`)
+    pub path: PathBuf,
+    /// Размер директории `.git`
+    pub git_size: u64,
+    /// Размер всего репозитория вместе с `.git`
+    pub repo_size: u64,
+}
+
+impl GitRepo {
+    /// Доля `.git` в размере репозитория, в процентах
+    pub fn git_percent(&self) -> f64 {
+        if self.repo_size == 0 {
+            0.0
+        } else {
+            self.git_size as f64 / self.repo_size as f64 * 100.0
+        }
+    }
+}
+
+/// Файл с несколькими жесткими ссылками
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HardLink {
+    pub size: u64,
+    /// Общее число ссылок на инод, в том числе вне корня сканирования
+    pub nlink: u64,
+    /// Ссылки, найденные при сканировании, по возрастанию пути
+    pub paths: Vec<PathBuf>,
+}
+
+/// Файл, расширение которого не соответствует содержимому
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MimeMismatch {
+    pub path: PathBuf,
+    /// Расширение в нижнем регистре
+    pub extension: String,
+    /// MIME-тип, определенный по содержимому
+    pub mime: String,
+}
+
+/// Место, занятое файлами одного владельца
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnerUsage {
+    pub uid: u32,
+    pub size: u64,
+    pub file_count: usize,
+    /// Самый большой файл владельца
+    pub largest_file: Option<(PathBuf, u64)>,
+}
+
+impl OwnerUsage {
+    pub fn new(uid: u32) -> Self {
+        OwnerUsage { uid, size: 0, file_count: 0, largest_file: None }
+    }
+
+    /// Учитывает файл владельца
+    pub fn add_file(&mut self, path: &Path, size: u64) {
+        self.size += size;
+        self.file_count += 1;
+        if !matches!(&self.largest_file, Some((_, largest)) if *largest >= size) {
+            self.largest_file = Some((path.to_path_buf(), size));
+        }
+    }
+
+    /// Добавляет данные того же владельца из другой части дерева
+    pub fn merge(&mut self, other: &OwnerUsage) {
+        self.size += other.size;
+        self.file_count += other.file_count;
+        if let Some((path, size)) = &other.largest_file {
+            if !matches!(&self.largest_file, Some((_, largest)) if largest >= size) {
+                self.largest_file = Some((path.clone(), *size));
+            }
+        }
+    }
+}
+
+/// Директории одного уровня вложенности и их собственные файлы (без
+/// поддиректорий)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DepthStats {
+    pub dir_count: usize,
+    pub file_count: usize,
+    pub size: u64,
+}
+
+impl DepthStats {
+    fn add(&mut self, other: &DepthStats) {
+        self.dir_count += other.dir_count;
+        self.file_count += other.file_count;
+        self.size += other.size;
+    }
+}
+
+/// Время обхода одной директории, если включен `ScanOptions::collect_spans`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirSpan {
+    pub path: PathBuf,
+    /// Глубина директории: у корня сканирования - 0
+    pub depth: u32,
+    /// Начало обхода; в serde-представлении моменты спана - наносекунды от
+    /// начала эпохи Unix
+    #[cfg_attr(feature = "serde", serde(with = "span_time_serde"))]
+    pub start: SystemTime,
+    /// Конец чтения собственных элементов, до обхода поддиректорий
+    #[cfg_attr(feature = "serde", serde(with = "span_time_serde"))]
+    pub own_end: SystemTime,
+    /// Конец обхода вместе с поддиректориями
+    #[cfg_attr(feature = "serde", serde(with = "span_time_serde"))]
+    pub end: SystemTime,
+    /// Число элементов директории
+    pub child_count: usize,
+}
+
+impl DirSpan {
+    /// Время обхода вместе с поддиректориями
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+
+    /// Время чтения собственных элементов директории
+    pub fn own_duration(&self) -> Duration {
+        self.own_end.duration_since(self.start).unwrap_or_default()
+    }
+}
+
+/// Результат сканирования
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanResult {
+    /// Суммарная информация по корню сканирования
+    pub root: DirInfo,
+    /// Информация по каждой вложенной директории, ключ - путь
+    pub dir_infos: BTreeMap<String, DirInfo>,
+    pub broken_symlinks: Vec<PathBuf>,
+    /// Ссылки на директорию-предка, которые не обходились при `follow_symlinks`
+    pub circular_symlinks: Vec<PathBuf>,
+    /// Директории без единого элемента (корень сканирования не включается)
+    pub empty_dirs: Vec<PathBuf>,
+    /// Временные файлы, если включен `ScanOptions::collect_temp_files`
+    pub temp_files: Vec<(PathBuf, u64)>,
+    /// Файлы нулевого размера, если включен `ScanOptions::collect_zero_byte_files`
+    pub zero_byte_files: Vec<PathBuf>,
+    /// Файлы, собранные согласно `ScanOptions::collect_files_min_size`
+    pub files: Vec<(PathBuf, u64)>,
+    /// Найденные Git-репозитории, по возрастанию пути
+    pub git_repos: Vec<GitRepo>,
+    /// Пропущенные из-за ошибок пути (например, без прав доступа) и текст ошибки
+    pub skipped: Vec<(PathBuf, String)>,
+    /// Файлы с несколькими жесткими ссылками, если включен
+    /// `ScanOptions::collect_hard_links`
+    pub hard_links: Vec<HardLink>,
+    /// Резервные копии Time Machine (только macOS): путь и размер
+    pub time_machine: Vec<(PathBuf, u64)>,
+    /// Занятое место по владельцам, если включен `ScanOptions::collect_owners`,
+    /// по убыванию размера
+    pub owners: Vec<OwnerUsage>,
+    /// Файлы, доступные на запись всем, если включен `ScanOptions::collect_security`
+    pub world_writable: Vec<PathBuf>,
+    /// Файлы с битом SUID или SGID, если включен `ScanOptions::collect_security`
+    pub suid_files: Vec<PathBuf>,
+    /// Файлы с расширением, противоречащим содержимому, если включен
+    /// `ScanOptions::mime_classify`
+    pub mime_mismatches: Vec<MimeMismatch>,
+    /// Сканирование прервано через `ScanOptions::cancel`, данные неполные
+    pub cancelled: bool,
+    /// Суммарный размер превысил `ScanOptions::size_budget`, данные неполные
+    pub budget_exceeded: bool,
+    /// Тип файловой системы корня сканирования (ext4, btrfs, nfs...), если
+    /// его удалось определить. У объединенного результата - общий тип всех
+    /// частей или `None`, если они на разных файловых системах
+    pub filesystem_type: Option<String>,
+    /// Самая глубоко вложенная директория и ее уровень (у корня - 0); при
+    /// одинаковой глубине - первая по алфавиту
+    pub max_depth_path: (PathBuf, u32),
+    /// Процент выборки, если размер оценен по `ScanOptions::sample_percent`
+    pub sample_percent: Option<u8>,
+    /// Оценка дисперсии экстраполированного размера (в байтах²)
+    pub sample_variance: f64,
+    /// Статистика по уровням вложенности: индекс - глубина директории (у
+    /// корня сканирования - 0)
+    pub depth_stats: Vec<DepthStats>,
+    /// Размеры учтенных файлов в порядке обхода, если включен
+    /// `ScanOptions::collect_file_sizes`
+    pub file_sizes: Vec<u64>,
+    /// Размер и число файлов по дню изменения (число дней от 1970-01-01, UTC),
+    /// если включен `ScanOptions::collect_mtime_days`
+    pub mtime_days: BTreeMap<i64, (u64, usize)>,
+    /// Исходный и оценочный сжатый размер по типам файлов (ключи как в
+    /// `DirInfo::file_types`), если включен `ScanOptions::compress_estimate`
+    pub compress_estimates: BTreeMap<String, (u64, u64)>,
+    /// Время обхода директорий по возрастанию начала, если включен
+    /// `ScanOptions::collect_spans`
+    pub spans: Vec<DirSpan>,
+    /// Точки монтирования, пропущенные из-за `ScanOptions::one_filesystem`,
+    /// по возрастанию пути
+    pub skipped_mounts: Vec<PathBuf>,
+}
+
+impl ScanResult {
+    /// Добавляет результат сканирования другого, непересекающегося дерева
+    /// (другого корня или части, отсканированной отдельно). Суммы и типы
+    /// файлов объединяются как в `DirInfo::merge`, списки остаются
+    /// упорядоченными.
+    ///
+    /// Паникует, если одна и та же директория есть в обоих результатах:
+    /// ее размер был бы посчитан дважды
+    pub fn merge(&mut self, other: ScanResult) {
+        use std::collections::btree_map::Entry;
+        self.root.merge(&other.root);
+        for (path, info) in other.dir_infos {
+            match self.dir_infos.entry(path) {
+                Entry::Vacant(slot) => {
+                    slot.insert(info);
+                },
+                Entry::Occupied(slot) => panic!("директория {} есть в обоих результатах сканирования", slot.key()),
+            }
+        }
+        self.broken_symlinks.extend(other.broken_symlinks);
+        self.broken_symlinks.sort();
+        self.circular_symlinks.extend(other.circular_symlinks);
+        self.circular_symlinks.sort();
+        self.empty_dirs.extend(other.empty_dirs);
+        self.empty_dirs.sort();
+        self.temp_files.extend(other.temp_files);
+        self.temp_files.sort();
+        self.zero_byte_files.extend(other.zero_byte_files);
+        self.zero_byte_files.sort();
+        self.files.extend(other.files);
+        self.git_repos.extend(other.git_repos);
+        self.git_repos.sort_by(|a, b| a.path.cmp(&b.path));
+        self.skipped.extend(other.skipped);
+        self.skipped.sort();
+        self.hard_links.extend(other.hard_links);
+        self.hard_links.sort_by(|a, b| a.paths.cmp(&b.paths));
+        self.time_machine.extend(other.time_machine);
+        self.time_machine.sort();
+        for owner in other.owners {
+            match self.owners.iter_mut().find(|o| o.uid == owner.uid) {
+                Some(usage) => usage.merge(&owner),
+                None => self.owners.push(owner),
+            }
+        }
+        self.owners.sort_by(|a, b| b.size.cmp(&a.size).then(a.uid.cmp(&b.uid)));
+        self.world_writable.extend(other.world_writable);
+        self.world_writable.sort();
+        self.suid_files.extend(other.suid_files);
+        self.suid_files.sort();
+        self.mime_mismatches.extend(other.mime_mismatches);
+        self.mime_mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+        self.cancelled |= other.cancelled;
+        self.budget_exceeded |= other.budget_exceeded;
+        if self.filesystem_type != other.filesystem_type {
+            self.filesystem_type = None;
+        }
+        let (path, depth) = other.max_depth_path;
+        if is_deeper(&path, depth, &self.max_depth_path) {
+            self.max_depth_path = (path, depth);
+        }
+        self.sample_percent = self.sample_percent.or(other.sample_percent);
+        self.sample_variance += other.sample_variance;
+        merge_depth_stats(&mut self.depth_stats, &other.depth_stats);
+        self.file_sizes.extend(other.file_sizes);
+        merge_mtime_days(&mut self.mtime_days, other.mtime_days);
+        merge_compress_estimates(&mut self.compress_estimates, other.compress_estimates);
+        self.spans.extend(other.spans);
+        self.spans.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.path.cmp(&b.path)));
+        self.skipped_mounts.extend(other.skipped_mounts);
+        self.skipped_mounts.sort();
+    }
+
+    /// Относительная погрешность оценки по выборке с доверительной
+    /// вероятностью 95%: 0.15 означает ±15%. `None`, если сканирование полное
+    pub fn sample_margin(&self) -> Option<f64> {
+        self.sample_percent?;
+        if self.root.size == 0 {
+            return Some(0.0);
+        }
+        Some(1.96 * self.sample_variance.sqrt() / self.root.size as f64)
+    }
+}
+
+/// Счетчики, которые обновляются во время сканирования (для индикатора прогресса)
+#[derive(Debug, Default)]
+pub struct ScanProgress {
+    dirs_scanned: AtomicU64,
+    files_scanned: AtomicU64,
+    bytes_scanned: AtomicU64,
+}
+
+impl ScanProgress {
+    pub fn dirs_scanned(&self) -> u64 {
+        self.dirs_scanned.load(Ordering::Relaxed)
+    }
+
+    pub fn files_scanned(&self) -> u64 {
+        self.files_scanned.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_scanned(&self) -> u64 {
+        self.bytes_scanned.load(Ordering::Relaxed)
+    }
+
+    /// Итоговая статистика сканирования, длившегося elapsed, с errors ошибками
+    pub fn stats(&self, errors: u64, elapsed: Duration) -> ScanStats {
+        ScanStats {
+            dirs_scanned: self.dirs_scanned(),
+            files_scanned: self.files_scanned(),
+            bytes_scanned: self.bytes_scanned(),
+            errors,
+            elapsed,
+        }
+    }
+}
+
+/// Скорость сканирования: помогает понять, во что упирается обход - в запросы
+/// метаданных (мало файлов в секунду) или в объем данных
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanStats {
+    pub dirs_scanned: u64,
+    pub files_scanned: u64,
+    pub bytes_scanned: u64,
+    /// Пути, пропущенные из-за ошибок
+    pub errors: u64,
+    pub elapsed: Duration,
+}
+
+impl ScanStats {
+    /// Добавляет статистику сканирования следующего корня
+    pub fn merge(&mut self, other: &ScanStats) {
+        self.dirs_scanned += other.dirs_scanned;
+        self.files_scanned += other.files_scanned;
+        self.bytes_scanned += other.bytes_scanned;
+        self.errors += other.errors;
+        self.elapsed += other.elapsed;
+    }
+
+    pub fn dirs_per_sec(&self) -> f64 {
+        self.per_sec(self.dirs_scanned)
+    }
+
+    pub fn files_per_sec(&self) -> f64 {
+        self.per_sec(self.files_scanned)
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        self.per_sec(self.bytes_scanned)
+    }
+
+    fn per_sec(&self, count: u64) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { count as f64 / secs } else { 0.0 }
+    }
+}
+
+// Общее состояние сканирования, разделяемое между потоками
+struct ScanContext<'a> {
+    root: &'a Path,
+    // Абсолютный путь корня для regex_exclude
+    absolute_root: Option<PathBuf>,
+    // Устройство корня сканирования для one_filesystem
+    root_dev: Option<u64>,
+    // Реальный путь корня и точки монтирования внутри него для one_filesystem;
+    // None, если таких точек нет
+    mounts: Option<(PathBuf, HashSet<PathBuf>)>,
+    skipped_mounts: Mutex<Vec<PathBuf>>,
+    options: &'a ScanOptions,
+    progress: &'a ScanProgress,
+    pool: &'a WorkerPool,
+    files: Mutex<Vec<(PathBuf, u64)>>,
+    broken_symlinks: Mutex<Vec<PathBuf>>,
+    circular_symlinks: Mutex<Vec<PathBuf>>,
+    empty_dirs: Mutex<Vec<PathBuf>>,
+    temp_files: Mutex<Vec<(PathBuf, u64)>>,
+    zero_byte_files: Mutex<Vec<PathBuf>>,
+    git_repos: Mutex<Vec<GitRepo>>,
+    // (устройство, инод) -> файл с несколькими жесткими ссылками
+    #[cfg_attr(not(unix), allow(dead_code))]
+    hard_links: Mutex<HashMap<(u64, u64), HardLink>>,
+    skipped: Mutex<Vec<(PathBuf, io::Error)>>,
+    time_machine: Mutex<Vec<(PathBuf, u64)>>,
+    // uid -> (размер, число файлов)
+    owners: Mutex<HashMap<u32, OwnerUsage>>,
+    world_writable: Mutex<Vec<PathBuf>>,
+    suid_files: Mutex<Vec<PathBuf>>,
+    mime_mismatches: Mutex<Vec<MimeMismatch>>,
+    deepest_dir: Mutex<(PathBuf, u32)>,
+    sample_variance: Mutex<f64>,
+    depth_stats: Mutex<Vec<DepthStats>>,
+    mtime_days: Mutex<BTreeMap<i64, (u64, usize)>>,
+    compress_estimates: Mutex<BTreeMap<String, (u64, u64)>>,
+    spans: Mutex<Vec<DirSpan>>,
+    // Обход действительно остановлен по ScanOptions::cancel
+    cancelled: AtomicBool,
+    // Суммарный размер учтенных файлов, если задан size_budget
+    budget_used: AtomicU64,
+    // Получатель итогов директорий при потоковом сканировании (scan_stream)
+    stream: Option<SyncSender<io::Result<(String, DirInfo)>>>,
+}
+
+impl<'a> ScanContext<'a> {
+    fn new(root: &'a Path, options: &'a ScanOptions, progress: &'a ScanProgress, pool: &'a WorkerPool) -> Self {
+        ScanContext {
+            root,
+            absolute_root: (!options.regex_exclude.is_empty()).then(|| absolute_path(root)),
+            root_dev: if options.one_filesystem { device_id(root) } else { None },
+            mounts: if options.one_filesystem { mounts_under(root) } else { None },
+            skipped_mounts: Mutex::new(Vec::new()),
+            options,
+            progress,
+            pool,
+            files: Mutex::new(Vec::new()),
+            broken_symlinks: Mutex::new(Vec::new()),
+            circular_symlinks: Mutex::new(Vec::new()),
+            empty_dirs: Mutex::new(Vec::new()),
+            temp_files: Mutex::new(Vec::new()),
+            zero_byte_files: Mutex::new(Vec::new()),
+            git_repos: Mutex::new(Vec::new()),
+            hard_links: Mutex::new(HashMap::new()),
+            skipped: Mutex::new(Vec::new()),
+            time_machine: Mutex::new(Vec::new()),
+            owners: Mutex::new(HashMap::new()),
+            world_writable: Mutex::new(Vec::new()),
+            suid_files: Mutex::new(Vec::new()),
+            mime_mismatches: Mutex::new(Vec::new()),
+            deepest_dir: Mutex::new((root.to_path_buf(), 0)),
+            sample_variance: Mutex::new(0.0),
+            depth_stats: Mutex::new(Vec::new()),
+            mtime_days: Mutex::new(BTreeMap::new()),
+            compress_estimates: Mutex::new(BTreeMap::new()),
+            spans: Mutex::new(Vec::new()),
+            cancelled: AtomicBool::new(false),
+            budget_used: AtomicU64::new(0),
+            stream: None,
+        }
+    }
+
+    // Ошибка на вложенном пути не прерывает сканирование: путь запоминается
+    // и пропускается, если не включен fail_on_error
+    fn skip_error(&self, path: &Path, err: io::Error) -> io::Result<()> {
+        if self.options.fail_on_error {
+            return Err(err);
+        }
+        self.skipped.lock().unwrap().push((path.to_path_buf(), err));
+        Ok(())
+    }
+
+    // Является ли директория точкой монтирования, в которую нельзя заходить
+    // при one_filesystem: другое устройство, чем у корня, или путь из таблицы
+    // монтирования (bind-монтирование на том же устройстве)
+    fn is_foreign_mount(&self, path: &Path, metadata: &fs::Metadata) -> bool {
+        let other_device = match self.root_dev {
+            Some(root_dev) => metadata_device_id(metadata).is_some_and(|dev| dev != root_dev),
+            None => false,
+        };
+        other_device || self.mounts.as_ref().is_some_and(|(real_root, mounts)| {
+            let relative = path.strip_prefix(self.root).unwrap_or(path);
+            mounts.contains(&real_root.join(relative))
+        })
+    }
+
+    // Нужно ли отслеживать иноды файлов с несколькими жесткими ссылками
+    fn tracks_hard_links(&self) -> bool {
+        let options = self.options;
+        options.count_inodes || !options.count_hardlinks || options.collect_hard_links
+    }
+
+    // Встретился ли инод впервые. Инод файла с одной жесткой ссылкой не может
+    // встретиться повторно, поэтому запоминаются только остальные
+    #[cfg(unix)]
+    fn is_new_inode(&self, metadata: &fs::Metadata, path: &Path) -> bool {
+        use std::collections::hash_map::Entry;
+        use std::os::unix::fs::MetadataExt;
+        if metadata.nlink() <= 1 {
+            return true;
+        }
+        let collect = self.options.collect_hard_links;
+        match self.hard_links.lock().unwrap().entry((metadata.dev(), metadata.ino())) {
+            Entry::Occupied(mut link) => {
+                if collect {
+                    link.get_mut().paths.push(path.to_path_buf());
+                }
+                false
+            },
+            Entry::Vacant(slot) => {
+                slot.insert(HardLink {
+                    size: metadata.len(),
+                    nlink: metadata.nlink(),
+                    paths: if collect { vec![path.to_path_buf()] } else { Vec::new() },
+                });
+                true
+            },
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn is_new_inode(&self, _metadata: &fs::Metadata, _path: &Path) -> bool {
+        true
+    }
+
+    // Нужно ли остановить обход; остановка запоминается для ScanResult::cancelled
+    fn should_stop(&self) -> bool {
+        let stop = self.options.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+        if stop {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+        stop || self.cancelled.load(Ordering::Relaxed) || self.budget_exceeded()
+    }
+
+    // Запоминает итог поддиректории в dir_infos, а при потоковом сканировании
+    // сразу отдает его потребителю. Если потребитель больше не читает, обход
+    // останавливается
+    fn finish_dir(&self, dir_infos: &mut BTreeMap<String, DirInfo>, path: &Path, info: DirInfo) {
+        let Some(path_str) = path.to_str() else {
+            return;
+        };
+        match &self.stream {
+            Some(stream) => {
+                if stream.send(Ok((path_str.to_string(), info))).is_err() {
+                    self.cancelled.store(true, Ordering::Relaxed);
+                }
+            },
+            None => {
+                dir_infos.insert(path_str.to_string(), info);
+            },
+        }
+    }
+
+    fn budget_exceeded(&self) -> bool {
+        self.options.size_budget.is_some_and(|budget| self.budget_used.load(Ordering::Relaxed) > budget)
+    }
+
+}
+
+/// Ограничение числа потоков сканирования. Вызывающий поток сканирует сам,
+/// а поддиректории отдает дополнительным потокам, пока в пуле есть свободные
+/// места. Один пул можно использовать для нескольких сканирований подряд
+#[derive(Debug)]
+pub struct WorkerPool {
+    free_workers: AtomicUsize,
+}
+
+impl WorkerPool {
+    /// Пул на jobs потоков, считая вызывающий: при jobs = 1 сканирование
+    /// строго последовательное
+    pub fn new(jobs: usize) -> Self {
+        WorkerPool { free_workers: AtomicUsize::new(jobs.saturating_sub(1)) }
+    }
+
+    fn try_acquire(&self) -> bool {
+        self.free_workers
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .is_ok()
+    }
+
+    fn release(&self) {
+        self.free_workers.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Рекурсивно сканирует директорию
+pub fn scan_directory(dir: &Path, options: &ScanOptions) -> io::Result<ScanResult> {
+    scan_directory_with_progress(dir, options, &ScanProgress::default())
+}
+
+/// То же, что `scan_directory`, но обновляет счетчики `progress` по ходу сканирования
+pub fn scan_directory_with_progress(dir: &Path, options: &ScanOptions, progress: &ScanProgress) -> io::Result<ScanResult> {
+    scan_directory_in_pool(dir, options, progress, &WorkerPool::new(options.jobs))
+}
+
+/// Число готовых директорий, которые `scan_stream` держит до того, как
+/// потребитель их прочитает
+pub const STREAM_BUFFER: usize = 1024;
+
+/// Потоковое сканирование для очень больших деревьев: директории отдаются по
+/// мере завершения их обхода (поддиректории раньше родителя, последним - сам
+/// dir) и не накапливаются в памяти. Ошибка сканирования корня приходит
+/// последним элементом. Прекращение чтения останавливает обход
+pub fn scan_stream(dir: &Path, options: &ScanOptions) -> impl Iterator<Item = io::Result<(String, DirInfo)>> {
+    let (sender, receiver) = mpsc::sync_channel(STREAM_BUFFER);
+    let dir = dir.to_path_buf();
+    let options = options.clone();
+    thread::spawn(move || {
+        let progress = ScanProgress::default();
+        let pool = WorkerPool::new(options.jobs);
+        let mut ctx = ScanContext::new(&dir, &options, &progress, &pool);
+        ctx.stream = Some(sender.clone());
+        let root = scan_dir(&dir, &mut BTreeMap::new(), &ctx, 1, &mut Vec::new());
+        let _ = sender.send(root.map(|info| (dir.to_string_lossy().into_owned(), info)));
+    });
+    receiver.into_iter()
+}
+
+/// То же, что `scan_directory_with_progress`, но с готовым пулом потоков;
+/// `ScanOptions::jobs` при этом не используется
+pub fn scan_directory_in_pool(
+    dir: &Path,
+    options: &ScanOptions,
+    progress: &ScanProgress,
+    pool: &WorkerPool,
+) -> io::Result<ScanResult> {
+    let ctx = ScanContext::new(dir, options, progress, pool);
+    let mut dir_infos = BTreeMap::new();
+    let mut file_sizes = Vec::new();
+    let root = scan_dir(dir, &mut dir_infos, &ctx, 1, &mut file_sizes)?;
+    if !options.collect_file_sizes {
+        file_sizes = Vec::new();
+    }
+    let budget_exceeded = ctx.budget_exceeded();
+
+    let mut broken_symlinks = ctx.broken_symlinks.into_inner().unwrap();
+    broken_symlinks.sort();
+    let mut circular_symlinks = ctx.circular_symlinks.into_inner().unwrap();
+    circular_symlinks.sort();
+    let mut empty_dirs = ctx.empty_dirs.into_inner().unwrap();
+    empty_dirs.sort();
+    let mut temp_files = ctx.temp_files.into_inner().unwrap();
+    temp_files.sort();
+    let mut zero_byte_files = ctx.zero_byte_files.into_inner().unwrap();
+    zero_byte_files.sort();
+    let mut git_repos = ctx.git_repos.into_inner().unwrap();
+    git_repos.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut skipped: Vec<(PathBuf, String)> = ctx.skipped.into_inner().unwrap().into_iter()
+        .map(|(path, err)| (path, err.to_string()))
+        .collect();
+    skipped.sort();
+    let mut hard_links: Vec<HardLink> = if options.collect_hard_links {
+        ctx.hard_links.into_inner().unwrap().into_values().collect()
+    } else {
+        Vec::new()
+    };
+    for link in &mut hard_links {
+        link.paths.sort();
+    }
+    hard_links.sort_by(|a, b| a.paths.cmp(&b.paths));
+    // Атрибут Time Machine могут нести и вложенные директории копии
+    let mut time_machine = ctx.time_machine.into_inner().unwrap();
+    time_machine.sort();
+    let mut spans = ctx.spans.into_inner().unwrap();
+    spans.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.path.cmp(&b.path)));
+    let mut owners: Vec<OwnerUsage> = ctx.owners.into_inner().unwrap().into_values().collect();
+    owners.sort_by(|a, b| b.size.cmp(&a.size).then(a.uid.cmp(&b.uid)));
+    let mut world_writable = ctx.world_writable.into_inner().unwrap();
+    world_writable.sort();
+    let mut suid_files = ctx.suid_files.into_inner().unwrap();
+    suid_files.sort();
+    let mut skipped_mounts = ctx.skipped_mounts.into_inner().unwrap();
+    skipped_mounts.sort();
+    let mut mime_mismatches = ctx.mime_mismatches.into_inner().unwrap();
+    mime_mismatches.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut backups: Vec<(PathBuf, u64)> = Vec::new();
+    for (path, size) in time_machine {
+        if !backups.iter().any(|(parent, _)| path.starts_with(parent)) {
+            backups.push((path, size));
+        }
+    }
+
+    Ok(ScanResult {
+        root,
+        dir_infos,
+        broken_symlinks,
+        circular_symlinks,
+        empty_dirs,
+        temp_files,
+        zero_byte_files,
+        files: ctx.files.into_inner().unwrap(),
+        git_repos,
+        skipped,
+        hard_links,
+        time_machine: backups,
+        owners,
+        world_writable,
+        suid_files,
+        mime_mismatches,
+        budget_exceeded,
+        cancelled: ctx.cancelled.into_inner(),
+        filesystem_type: filesystem_type(dir),
+        max_depth_path: ctx.deepest_dir.into_inner().unwrap(),
+        // При 100% выборкой оказались все файлы, и размер точный
+        sample_percent: options.sample_percent.filter(|&percent| percent < 100),
+        sample_variance: ctx.sample_variance.into_inner().unwrap(),
+        depth_stats: ctx.depth_stats.into_inner().unwrap(),
+        mtime_days: ctx.mtime_days.into_inner().unwrap(),
+        compress_estimates: ctx.compress_estimates.into_inner().unwrap(),
+        spans,
+        file_sizes,
+        skipped_mounts,
+    })
+}
+
+// Случайная выборка файлов одной директории для ScanOptions::sample_percent.
+// Первые SAMPLE_MIN_FILES файлов учитываются всегда, остальные - с
+// вероятностью percent%, и их вклад умножается на 100 / percent (оценка
+// Горвица-Томпсона). Если выборкой оказались все файлы, оценка точная
+struct Sampler {
+    percent: u8,
+    rng: u64,
+    seen: usize,
+    // Последний взятый файл попал в выборку случайно, а не в числе первых
+    weighted: bool,
+    weighted_size: u64,
+    weighted_count: usize,
+    variance: f64,
+}
+
+impl Sampler {
+    fn new(dir: &Path, percent: u8) -> Self {
+        // RandomState засевается случайно при каждом запуске
+        let rng = RandomState::new().hash_one(dir);
+        Sampler { percent, rng, seen: 0, weighted: false, weighted_size: 0, weighted_count: 0, variance: 0.0 }
+    }
+
+    // Брать ли очередной файл в выборку
+    fn take(&mut self) -> bool {
+        self.seen += 1;
+        self.weighted = self.seen > SAMPLE_MIN_FILES;
+        !self.weighted || self.next_u64() % 100 < self.percent as u64
+    }
+
+    // Учитывает размер файла, взятого последним вызовом take
+    fn record(&mut self, size: u64) {
+        if self.weighted {
+            let p = self.percent as f64 / 100.0;
+            self.weighted_size += size;
+            self.weighted_count += 1;
+            self.variance += (1.0 - p) / (p * p) * (size as f64).powi(2);
+        }
+    }
+
+    // Добавляет к итогу директории оценку файлов, не попавших в выборку
+    fn extrapolate(&self, info: &mut DirInfo, disk_usage: bool) {
+        let scale = (100 - self.percent) as f64 / self.percent as f64;
+        let extra_size = (self.weighted_size as f64 * scale).round() as u64;
+        info.size += extra_size;
+        if disk_usage {
+            info.allocated_size += extra_size;
+        }
+        info.file_count += (self.weighted_count as f64 * scale).round() as usize;
+    }
+
+    // SplitMix64
+    fn next_u64(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+// Складывает размеры и число файлов одинаковых дней
+fn merge_mtime_days(days: &mut BTreeMap<i64, (u64, usize)>, other: BTreeMap<i64, (u64, usize)>) {
+    for (day, (size, count)) in other {
+        let total = days.entry(day).or_insert((0, 0));
+        total.0 += size;
+        total.1 += count;
+    }
+}
+
+// Складывает исходные и сжатые размеры одинаковых типов файлов
+fn merge_compress_estimates(estimates: &mut BTreeMap<String, (u64, u64)>, other: BTreeMap<String, (u64, u64)>) {
+    for (type_key, (original, compressed)) in other {
+        let total = estimates.entry(type_key).or_insert((0, 0));
+        total.0 += original;
+        total.1 += compressed;
+    }
+}
+
+// Поуровнево складывает статистику other в stats
+fn merge_depth_stats(stats: &mut Vec<DepthStats>, other: &[DepthStats]) {
+    if stats.len() < other.len() {
+        stats.resize(other.len(), DepthStats::default());
+    }
+    for (level, other) in stats.iter_mut().zip(other) {
+        level.add(other);
+    }
+}
+
+// Средний размер файла; для директории без файлов - 0
+pub(crate) fn average(size: u64, file_count: usize) -> u64 {
+    if file_count == 0 { 0 } else { size / file_count as u64 }
+}
+
+// Глубже ли path, чем текущая самая глубокая директория; при равной глубине
+// выбирается первая по алфавиту, чтобы результат не зависел от числа потоков
+fn is_deeper(path: &Path, depth: u32, deepest: &(PathBuf, u32)) -> bool {
+    depth > deepest.1 || (depth == deepest.1 && path < deepest.0.as_path())
+}
+
+// depth - уровень вложенности элементов внутри dir (для корня сканирования равен 1).
+// Если нужны перцентили или медиана, размеры учтенных файлов dir и всех
+// поддиректорий добавляются в file_sizes
+fn scan_dir(
+    dir: &Path,
+    dir_infos: &mut BTreeMap<String, DirInfo>,
+    ctx: &ScanContext,
+    depth: u32,
+    file_sizes: &mut Vec<u64>,
+) -> io::Result<DirInfo> {
+    let options = ctx.options;
+    let mut current_info = DirInfo::new();
+    // Без отдельного потока поддиректории пишут в тот же вектор, что и dir
+    let sizes_start = file_sizes.len();
+    let keep_sizes = options.collect_file_sizes || options.file_size_median;
+
+    // При max_depth = 0 корень только проверяется на существование
+    if options.max_depth == Some(0) {
+        fs::metadata(dir)?;
+        return Ok(current_info);
+    }
+    // Поддиректории на уровне max_depth еще учитывают свои файлы, но глубже не обходятся
+    let descend = match options.max_depth {
+        Some(max_depth) => depth <= max_depth,
+        None => true,
+    };
+
+    if dir.is_dir() {
+        let span_start = SystemTime::now();
+        // Глубина самой директории: у корня сканирования - 0
+        if options.trace >= 1 {
+            eprintln!("[SCAN] depth={} {}", depth - 1, dir.display());
+        }
+        let mut deepest = ctx.deepest_dir.lock().unwrap();
+        if is_deeper(dir, depth - 1, &deepest) {
+            *deepest = (dir.to_path_buf(), depth - 1);
+        }
+        drop(deepest);
+        if options.count_inodes {
+            current_info.inode_count += 1;
+        }
+        if options.count_dir_sizes {
+            if let Ok(metadata) = fs::metadata(dir) {
+                let dir_size = round_to_block(if options.disk_usage { allocated_size(&metadata) } else { metadata.len() }, options.block_size);
+                current_info.size += dir_size;
+                if options.disk_usage {
+                    current_info.allocated_size += dir_size;
+                }
+            }
+        }
+        let mut subdirs: Vec<PathBuf> = Vec::new();
+        let mut entry_count = 0;
+        // Размер поддиректории .git, если dir - корень Git-репозитория
+        let mut git_size = None;
+        // Место по владельцам для файлов этой директории
+        let mut owners: HashMap<u32, OwnerUsage> = HashMap::new();
+        // Размер и число файлов этой директории по дням изменения
+        let mut mtime_days: BTreeMap<i64, (u64, usize)> = BTreeMap::new();
+        // Исходный и оценочный сжатый размер файлов этой директории по типам
+        let mut compress_estimates: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+
+        let mut sampler = options.sample_percent.filter(|&percent| percent < 100).map(|percent| Sampler::new(dir, percent));
+
+        // Недоступный корень сканирования - ошибка, вложенная директория - пропуск
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if depth > 1 => {
+                ctx.skip_error(dir, err)?;
+                return Ok(current_info);
+            },
+            Err(err) => return Err(err),
+        };
+
+        for entry in entries {
+            if ctx.should_stop() {
+                break;
+            }
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    ctx.skip_error(dir, err)?;
+                    continue;
+                }
+            };
+            entry_count += 1;
+            let path = entry.path();
+
+            // Исключенные пути не учитываются и не обходятся
+            if options.exclude.is_match_path(ctx.root, &path) {
+                continue;
+            }
+            if let Some(absolute_root) = &ctx.absolute_root {
+                let relative = path.strip_prefix(ctx.root).unwrap_or(&path);
+                if options.regex_exclude.is_match(&absolute_root.join(relative).to_string_lossy()) {
+                    continue;
+                }
+            }
+            if options.skip_git && entry.file_name() == ".git" {
+                continue;
+            }
+            if options.skip_macos_metadata && is_macos_metadata(&path) {
+                continue;
+            }
+
+            // Тип элемента определяем без перехода по символическим ссылкам
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(err) => {
+                    ctx.skip_error(&path, err)?;
+                    continue;
+                }
+            };
+            // Выборка экономит именно чтение метаданных, поэтому решение
+            // принимается до него
+            if let (true, Some(sampler)) = (file_type.is_file(), &mut sampler) {
+                if !sampler.take() {
+                    continue;
+                }
+            }
+            let metadata = if file_type.is_symlink() {
+                match fs::metadata(&path) {
+                    // Ссылки учитываются только при follow_symlinks
+                    Ok(metadata) if options.follow_symlinks => {
+                        // Ссылка на директорию-предка привела бы к бесконечному обходу
+                        if metadata.is_dir() && points_to_ancestor(&path, dir) {
+                            ctx.circular_symlinks.lock().unwrap().push(path);
+                            continue;
+                        }
+                        metadata
+                    },
+                    Ok(_) => {
+                        // Не пройденная ссылка все равно занимает собственный инод
+                        if options.count_inodes {
+                            current_info.inode_count += 1;
+                        }
+                        continue;
+                    },
+                    Err(_) => {
+                        if options.count_inodes {
+                            current_info.inode_count += 1;
+                        }
+                        ctx.broken_symlinks.lock().unwrap().push(path);
+                        continue;
+                    }
+                }
+            } else {
+                match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(err) => {
+                        ctx.skip_error(&path, err)?;
+                        continue;
+                    }
+                }
+            };
+
+            // Фильтр --include зависит только от пути. Не прошедшая его ссылка не
+            // запоминается как первая, иначе она скрыла бы подходящую ссылку на тот же инод
+            let included = metadata.is_dir() || options.include.is_empty() || options.include.is_match_path(ctx.root, &path);
+            // Инод директории учитывается при ее собственном сканировании,
+            // а повторные жесткие ссылки на файл - только в списке ссылок
+            let first_link = metadata.is_dir() || !ctx.tracks_hard_links() || !included || ctx.is_new_inode(&metadata, &path);
+            if options.count_inodes && !metadata.is_dir() && first_link {
+                current_info.inode_count += 1;
+            }
+
+            if metadata.is_dir() {
+                if ctx.is_foreign_mount(&path, &metadata) {
+                    ctx.skipped_mounts.lock().unwrap().push(path);
+                    continue;
+                }
+                if !options.include_virtual && is_virtual_filesystem(&path) {
+                    continue;
+                }
+                // Поддиректории обходим после файлов, возможно параллельно
+                if descend {
+                    subdirs.push(path);
+                }
+            } else if metadata.is_file() {
+                if !first_link && !options.count_hardlinks {
+                    continue;
+                }
+                // Права проверяются до фильтров: они не должны скрывать опасные файлы
+                if options.collect_security {
+                    if let Some(mode) = unix_mode(&metadata) {
+                        if mode & 0o002 != 0 {
+                            ctx.world_writable.lock().unwrap().push(path.clone());
+                        }
+                        if mode & 0o6000 != 0 {
+                            ctx.suid_files.lock().unwrap().push(path.clone());
+                        }
+                    }
+                }
+                let file_size = round_to_block(if options.disk_usage { allocated_size(&metadata) } else { metadata.len() }, options.block_size);
+                if !included {
+                    continue;
+                }
+                // Файлы вне заданного диапазона размеров не учитываются
+                if !options.size_filter.contains(file_size) {
+                    continue;
+                }
+                if options.mtime_filter.is_active()
+                    && !metadata.modified().is_ok_and(|modified| options.mtime_filter.contains(modified)) {
+                    continue;
+                }
+                let owner = file_owner(&metadata);
+                if options.owner.is_some() && owner != options.owner {
+                    continue;
+                }
+                let (hidden, system) = windows_attributes(&metadata);
+                if system {
+                    current_info.system_size += file_size;
+                    current_info.system_count += 1;
+                }
+                if hidden {
+                    current_info.hidden_size += file_size;
+                    current_info.hidden_count += 1;
+                    if !options.show_hidden {
+                        continue;
+                    }
+                }
+                if options.trace >= 2 {
+                    eprintln!("[FILE] depth={} {}", depth, path.display());
+                }
+                current_info.size += file_size;
+                if keep_sizes {
+                    file_sizes.push(file_size);
+                }
+                if let Some(sampler) = &mut sampler {
+                    sampler.record(file_size);
+                }
+                if options.size_budget.is_some() {
+                    ctx.budget_used.fetch_add(file_size, Ordering::Relaxed);
+                }
+                if options.disk_usage {
+                    current_info.allocated_size += file_size;
+                }
+                current_info.file_count += 1;
+                if let (true, Some(uid)) = (options.collect_owners, owner) {
+                    owners.entry(uid).or_insert_with(|| OwnerUsage::new(uid)).add_file(&path, file_size);
+                }
+
+                // Обновляем список самых больших файлов
+                current_info.add_top_file(&path, file_size, options.top_files);
+
+                // Обновляем статистику по типам файлов
+                let extension = path.extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+
+                *current_info.categories.entry(categorize_extension(&extension)).or_insert(0) += file_size;
+                let type_key = match options.mime_classify.then(|| read_mime(&path)).flatten() {
+                    Some(mime) => {
+                        if extension_mismatch(&extension, mime) {
+                            ctx.mime_mismatches.lock().unwrap().push(MimeMismatch {
+                                path: path.clone(),
+                                extension: extension.clone(),
+                                mime: mime.to_string(),
+                            });
+                        }
+                        mime_group(mime).to_string()
+                    },
+                    None => extension,
+                };
+                *current_info.file_type_counts.entry(type_key.clone()).or_insert(0) += 1;
+                // Нечитаемый файл в оценку не попадает, но остается в статистике
+                if options.compress_estimate {
+                    if let Ok(compressed) = compress::estimate_file(&path, file_size) {
+                        let estimate = compress_estimates.entry(type_key.clone()).or_insert((0, 0));
+                        estimate.0 += file_size;
+                        estimate.1 += compressed;
+                    }
+                }
+                *current_info.file_types.entry(type_key).or_insert(0) += file_size;
+
+                // Пустые файлы - часто lock-файлы и недокачанные загрузки
+                if metadata.len() == 0 {
+                    current_info.zero_byte_count += 1;
+                    if options.collect_zero_byte_files {
+                        ctx.zero_byte_files.lock().unwrap().push(path.clone());
+                    }
+                }
+
+                if is_resource_fork(&path) {
+                    current_info.resource_fork_size += file_size;
+                    current_info.resource_fork_count += 1;
+                }
+
+                if classify_temp_file(&path) {
+                    current_info.temp_size += file_size;
+                    current_info.temp_count += 1;
+                    if options.collect_temp_files {
+                        ctx.temp_files.lock().unwrap().push((path.clone(), file_size));
+                    }
+                }
+
+                // Время изменения доступно не на всех платформах и файловых системах
+                if let Ok(modified) = metadata.modified() {
+                    current_info.update_mtime(&path, modified);
+                    if options.collect_mtime_days {
+                        let day = mtime_days.entry(unix_seconds(modified).div_euclid(86400)).or_insert((0, 0));
+                        day.0 += file_size;
+                        day.1 += 1;
+                    }
+                }
+
+                // Запоминаем файл, если вызывающему нужен список файлов
+                if let Some(min_size) = options.collect_files_min_size {
+                    if file_size >= min_size {
+                        ctx.files.lock().unwrap().push((path.clone(), file_size));
+                    }
+                }
+            }
+        }
+
+        if let Some(sampler) = sampler {
+            sampler.extrapolate(&mut current_info, options.disk_usage);
+            *ctx.sample_variance.lock().unwrap() += sampler.variance;
+        }
+
+        if !owners.is_empty() {
+            let mut total = ctx.owners.lock().unwrap();
+            for (uid, usage) in owners {
+                match total.entry(uid) {
+                    std::collections::hash_map::Entry::Occupied(mut slot) => slot.get_mut().merge(&usage),
+                    std::collections::hash_map::Entry::Vacant(slot) => {
+                        slot.insert(usage);
+                    },
+                }
+            }
+        }
+
+        if !mtime_days.is_empty() {
+            merge_mtime_days(&mut ctx.mtime_days.lock().unwrap(), mtime_days);
+        }
+        if !compress_estimates.is_empty() {
+            merge_compress_estimates(&mut ctx.compress_estimates.lock().unwrap(), compress_estimates);
+        }
+
+        // Пустой считаем только директорию без единого элемента: даже исключенные
+        // или отфильтрованные файлы не позволят удалить ее через fs::remove_dir
+        if entry_count == 0 && depth > 1 && !ctx.should_stop() {
+            ctx.empty_dirs.lock().unwrap().push(dir.to_path_buf());
+        }
+
+        // Пока поддиректории не добавлены, в current_info только собственные файлы
+        let mut depth_stats = ctx.depth_stats.lock().unwrap();
+        let level = depth as usize - 1;
+        if depth_stats.len() <= level {
+            depth_stats.resize(level + 1, DepthStats::default());
+        }
+        depth_stats[level].add(&DepthStats { dir_count: 1, file_count: current_info.file_count, size: current_info.size });
+        drop(depth_stats);
+
+        ctx.progress.dirs_scanned.fetch_add(1, Ordering::Relaxed);
+        ctx.progress.files_scanned.fetch_add(current_info.file_count as u64, Ordering::Relaxed);
+        ctx.progress.bytes_scanned.fetch_add(current_info.size, Ordering::Relaxed);
+        let own_end = SystemTime::now();
+
+        // Рекурсивно обходим поддиректории: если есть свободный поток, отдаем
+        // поддиректорию ему, иначе сканируем в текущем потоке
+        thread::scope(|scope| -> io::Result<()> {
+            let mut handles = Vec::new();
+
+            for path in subdirs {
+                if ctx.should_stop() {
+                    break;
+                }
+                if ctx.pool.try_acquire() {
+                    handles.push(scope.spawn(move || {
+                        let mut sub_infos = BTreeMap::new();
+                        let mut sub_sizes = Vec::new();
+                        let result = scan_dir(&path, &mut sub_infos, ctx, depth + 1, &mut sub_sizes);
+                        ctx.pool.release();
+                        result.map(|info| (path, info, sub_infos, sub_sizes))
+                    }));
+                } else {
+                    let subdir_info = scan_dir(&path, dir_infos, ctx, depth + 1, file_sizes)?;
+                    current_info.merge_limited(&subdir_info, options.top_files);
+                    if is_git_dir(&path) {
+                        git_size = Some(subdir_info.size);
+                    }
+
+                    // Сохраняем информацию о поддиректории
+                    ctx.finish_dir(dir_infos, &path, subdir_info);
+                }
+            }
+
+            for handle in handles {
+                let (path, subdir_info, mut sub_infos, mut sub_sizes) = handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))?;
+                current_info.merge_limited(&subdir_info, options.top_files);
+                dir_infos.append(&mut sub_infos);
+                file_sizes.append(&mut sub_sizes);
+                if is_git_dir(&path) {
+                    git_size = Some(subdir_info.size);
+                }
+
+                ctx.finish_dir(dir_infos, &path, subdir_info);
+            }
+
+            Ok(())
+        })?;
+
+        if is_time_machine_backup(dir) {
+            ctx.time_machine.lock().unwrap().push((dir.to_path_buf(), current_info.size));
+        }
+
+        if let Some(git_size) = git_size {
+            ctx.git_repos.lock().unwrap().push(GitRepo {
+                path: dir.to_path_buf(),
+                git_size,
+                repo_size: current_info.size,
+            });
+        }
+        // Медиана требует выбора по всем размерам поддерева на каждом уровне
+        let subtree_sizes: &mut [u64] = if options.file_size_median { &mut file_sizes[sizes_start..] } else { &mut [] };
+        current_info.set_file_size_stats(subtree_sizes);
+
+        if options.collect_spans {
+            ctx.spans.lock().unwrap().push(DirSpan {
+                path: dir.to_path_buf(),
+                depth: depth - 1,
+                start: span_start,
+                own_end,
+                end: SystemTime::now(),
+                child_count: entry_count,
+            });
+        }
+    }
+
+    Ok(current_info)
+}
+
+// Размер, округленный вверх до целого числа блоков; пустой файл не занимает блоков
+fn round_to_block(size: u64, block_size: Option<u64>) -> u64 {
+    match block_size {
+        Some(block) => size.div_ceil(block).saturating_mul(block),
+        None => size,
+    }
+}
+
+// Размер выделенных файлу блоков; st_blocks всегда считается в 512-байтовых блоках
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+// Без сведений о блоках используем логическую длину
+#[cfg(not(unix))]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+// MIME-тип по сигнатуре в начале файла; None, если файл не прочитать
+// или тип не распознан
+fn read_mime(path: &Path) -> Option<&'static str> {
+    let mut header = [0u8; MIME_HEADER_LEN];
+    let mut len = 0;
+    let mut file = fs::File::open(path).ok()?;
+    while len < header.len() {
+        match file.read(&mut header[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        }
+    }
+    detect_mime(&header[..len])
+}
+
+// uid владельца файла
+#[cfg(unix)]
+fn file_owner(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn file_owner(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+// Права доступа файла
+#[cfg(unix)]
+fn unix_mode(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.mode())
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+// Атрибуты файла Windows (скрытый, системный)
+#[cfg(windows)]
+fn windows_attributes(metadata: &fs::Metadata) -> (bool, bool) {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    let attributes = metadata.file_attributes();
+    (attributes & FILE_ATTRIBUTE_HIDDEN != 0, attributes & FILE_ATTRIBUTE_SYSTEM != 0)
+}
+
+#[cfg(not(windows))]
+fn windows_attributes(_metadata: &fs::Metadata) -> (bool, bool) {
+    (false, false)
+}
+
+// Помечена ли директория атрибутом com.apple.backupd* резервной копии Time Machine
+#[cfg(target_os = "macos")]
+fn is_time_machine_backup(dir: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_int};
+    use std::os::unix::ffi::OsStrExt;
+
+    extern "C" {
+        fn listxattr(path: *const c_char, namebuf: *mut c_char, size: usize, options: c_int) -> isize;
+    }
+
+    let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut names = vec![0u8; 4096];
+    // SAFETY: listxattr записывает не более names.len() байт в буфер
+    let len = unsafe { listxattr(path.as_ptr(), names.as_mut_ptr() as *mut c_char, names.len(), 0) };
+    if len <= 0 {
+        return false;
+    }
+    // Имена атрибутов разделены нулевыми байтами
+    names[..len as usize].split(|&b| b == 0).any(|name| name.starts_with(b"com.apple.backupd"))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_time_machine_backup(_dir: &Path) -> bool {
+    false
+}
+
+// Абсолютный путь без разрешения символических ссылок и без компонентов "."
+fn absolute_path(path: &Path) -> PathBuf {
+    let joined = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path))
+    };
+    joined.components().filter(|c| *c != std::path::Component::CurDir).collect()
+}
+
+// Реальный путь root и точки монтирования строго внутри него
+fn mounts_under(root: &Path) -> Option<(PathBuf, HashSet<PathBuf>)> {
+    let real_root = fs::canonicalize(root).ok()?;
+    let mounts: HashSet<PathBuf> = mount_points().into_iter()
+        .filter(|mount| mount.starts_with(&real_root) && *mount != real_root)
+        .collect();
+    (!mounts.is_empty()).then_some((real_root, mounts))
+}
+
+// Идентификатор устройства, на котором находится path
+fn device_id(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().as_ref().and_then(metadata_device_id)
+}
+
+#[cfg(unix)]
+fn metadata_device_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn metadata_device_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+// Является ли path точкой монтирования из VIRTUAL_FILESYSTEMS. Реальный путь
+// вычисляется только для директорий с подходящим именем
+fn is_virtual_filesystem(path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let candidate = VIRTUAL_FILESYSTEMS.iter().any(|mount| Path::new(mount).file_name() == Some(name));
+    candidate && fs::canonicalize(path)
+        .is_ok_and(|real| VIRTUAL_FILESYSTEMS.iter().any(|mount| real == Path::new(mount)))
+}
+
+// Указывает ли ссылка link из директории dir на саму dir или ее предка.
+// Реальный путь dir уже содержит всех предков, поэтому достаточно сравнить префиксы
+fn points_to_ancestor(link: &Path, dir: &Path) -> bool {
+    match (fs::canonicalize(link), fs::canonicalize(dir)) {
+        (Ok(target), Ok(dir)) => dir.starts_with(target),
+        _ => false,
+    }
+}
+
+fn is_git_dir(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == ".git")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn infos(entries: &[(&str, u64)]) -> BTreeMap<String, DirInfo> {
+        entries.iter()
+            .map(|(path, size)| (path.to_string(), DirInfo { size: *size, ..DirInfo::default() }))
+            .collect()
+    }
+
+    #[test]
+    fn merges_disjoint_results() {
+        let mut left = ScanResult { root: DirInfo { size: 10, file_count: 1, ..DirInfo::default() }, dir_infos: infos(&[("/x/a", 10)]), ..ScanResult::default() };
+        left.empty_dirs.push("/x/z".into());
+        let mut right = ScanResult { root: DirInfo { size: 5, file_count: 2, ..DirInfo::default() }, dir_infos: infos(&[("/y/b", 5)]), ..ScanResult::default() };
+        right.empty_dirs.push("/y/a".into());
+        right.cancelled = true;
+        left.max_depth_path = ("/x/a".into(), 1);
+        right.max_depth_path = ("/y/b/c".into(), 2);
+        left.depth_stats = vec![DepthStats { dir_count: 1, file_count: 1, size: 10 }];
+        right.depth_stats = vec![DepthStats { dir_count: 1, file_count: 0, size: 0 }, DepthStats { dir_count: 2, file_count: 2, size: 5 }];
+
+        left.merge(right);
+        assert_eq!(left.root.size, 15);
+        assert_eq!(left.root.file_count, 3);
+        assert_eq!(left.dir_infos.len(), 2);
+        assert_eq!(left.empty_dirs, vec![PathBuf::from("/x/z"), "/y/a".into()]);
+        assert!(left.cancelled);
+        assert_eq!(left.max_depth_path, ("/y/b/c".into(), 2));
+        assert_eq!(left.depth_stats, vec![
+            DepthStats { dir_count: 2, file_count: 1, size: 10 },
+            DepthStats { dir_count: 2, file_count: 2, size: 5 },
+        ]);
+    }
+
+    #[test]
+    fn top_files_stay_capped() {
+        let mut left = DirInfo::new();
+        for (path, size) in [("/a/1", 10), ("/a/2", 30), ("/a/3", 20), ("/a/4", 5)] {
+            left.add_top_file(Path::new(path), size, 3);
+        }
+        assert_eq!(left.top_n_files(10), vec![(PathBuf::from("/a/2"), 30), ("/a/3".into(), 20), ("/a/1".into(), 10)]);
+
+        let mut right = DirInfo::new();
+        right.add_top_file(Path::new("/b/1"), 25, 3);
+        left.merge_limited(&right, 3);
+        assert_eq!(left.top_n_files(2), vec![(PathBuf::from("/a/2"), 30), ("/b/1".into(), 25)]);
+        assert_eq!(left.top_files.len(), 3);
+        assert_eq!(left.largest_file(), Some(&(PathBuf::from("/a/2"), 30)));
+    }
+
+    #[test]
+    fn zero_top_files_keeps_all() {
+        let dir = std::env::temp_dir().join(format!("diskspace-top-files-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for i in 0..DEFAULT_TOP_FILES + 5 {
+            fs::write(dir.join(if i % 2 == 0 { "sub" } else { "" }).join(format!("f{}", i)), vec![0u8; i + 1]).unwrap();
+        }
+        let result = scan_directory(&dir, &ScanOptions::new().jobs(1).top_files(0));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let root = result.unwrap().root;
+        assert_eq!(root.top_files.len(), DEFAULT_TOP_FILES + 5);
+        assert_eq!(root.top_n_files(0).len(), DEFAULT_TOP_FILES + 5);
+        assert_eq!(root.largest_file().map(|(_, size)| *size), Some(DEFAULT_TOP_FILES as u64 + 5));
+    }
+
+    #[test]
+    fn mtime_extremes_stay_capped() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+        let mut left = DirInfo::new();
+        for i in 1..=6 {
+            left.update_mtime(Path::new(&format!("/a/{}", i)), at(i));
+        }
+        let mut right = DirInfo::new();
+        right.update_mtime(Path::new("/b/1"), at(3));
+        left.merge(&right);
+
+        let paths = |list: &[(PathBuf, SystemTime)]| list.iter().map(|(path, _)| path.to_string_lossy().into_owned()).collect::<Vec<_>>();
+        assert_eq!(paths(&left.oldest_files), ["/a/1", "/a/2", "/a/3", "/b/1", "/a/4"]);
+        assert_eq!(paths(&left.newest_files), ["/a/6", "/a/5", "/a/4", "/a/3", "/b/1"]);
+        assert_eq!(left.oldest_file(), Some(&(PathBuf::from("/a/1"), at(1))));
+    }
+
+    #[test]
+    fn rounds_sizes_to_blocks() {
+        assert_eq!(round_to_block(0, Some(4096)), 0);
+        assert_eq!(round_to_block(1, Some(4096)), 4096);
+        assert_eq!(round_to_block(4096, Some(4096)), 4096);
+        assert_eq!(round_to_block(4097, Some(4096)), 8192);
+        assert_eq!(round_to_block(4097, None), 4097);
+    }
+
+    #[test]
+    fn stats_rates() {
+        let mut stats = ScanStats { dirs_scanned: 10, files_scanned: 100, bytes_scanned: 4096, errors: 1, elapsed: Duration::from_secs(2) };
+        assert_eq!(stats.files_per_sec(), 50.0);
+        assert_eq!(stats.bytes_per_sec(), 2048.0);
+        stats.merge(&ScanStats { dirs_scanned: 10, elapsed: Duration::from_secs(2), ..ScanStats::default() });
+        assert_eq!(stats.dirs_per_sec(), 5.0);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(ScanStats::default().files_per_sec(), 0.0);
+    }
+
+    #[test]
+    fn sampler_extrapolates_beyond_first_files() {
+        let mut sampler = Sampler::new(Path::new("/data"), 25);
+        assert!((0..SAMPLE_MIN_FILES).all(|_| sampler.take()));
+        for _ in 0..SAMPLE_MIN_FILES {
+            sampler.record(10);
+        }
+        sampler.weighted = true;
+        sampler.record(100);
+        let mut info = DirInfo { size: SAMPLE_MIN_FILES as u64 * 10 + 100, file_count: SAMPLE_MIN_FILES + 1, ..DirInfo::default() };
+        sampler.extrapolate(&mut info, false);
+        assert_eq!(info.size, SAMPLE_MIN_FILES as u64 * 10 + 400);
+        assert_eq!(info.file_count, SAMPLE_MIN_FILES + 4);
+        assert!(sampler.variance > 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn merge_rejects_same_directory() {
+        let mut left = ScanResult { dir_infos: infos(&[("/a", 1)]), ..ScanResult::default() };
+        left.merge(ScanResult { dir_infos: infos(&[("/a", 2)]), ..ScanResult::default() });
+    }
+}
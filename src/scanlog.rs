@@ -0,0 +1,87 @@
+//! Журнал сканирования (--log-file): события и ошибки обхода отдельно от отчета
+
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::format::format_timestamp;
+use crate::scan::{ScanResult, ScanStats};
+
+/// Уровень записи журнала
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        })
+    }
+}
+
+/// Строка журнала: время (UTC), уровень и сообщение через табуляцию
+pub fn log_line(time: SystemTime, level: LogLevel, message: &str) -> String {
+    format!("{}\t{}\t{}\n", format_timestamp(time), level, message)
+}
+
+/// Журнал, открытый на дозапись
+pub struct ScanLog {
+    file: File,
+}
+
+impl ScanLog {
+    /// Открывает журнал на дозапись, создавая файл и его директорию при необходимости
+    pub fn open(path: &Path) -> io::Result<ScanLog> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(ScanLog { file: OpenOptions::new().create(true).append(true).open(path)? })
+    }
+
+    pub fn write(&mut self, level: LogLevel, message: &str) -> io::Result<()> {
+        self.file.write_all(log_line(SystemTime::now(), level, message).as_bytes())
+    }
+
+    /// Записывает ошибки и пропуски сканирования root и итоговую статистику.
+    /// Сканер не ведет журнал во время обхода, поэтому у этих записей время
+    /// окончания сканирования
+    pub fn write_result(&mut self, root: &Path, result: &ScanResult, stats: &ScanStats) -> io::Result<()> {
+        for (path, error) in &result.skipped {
+            self.write(LogLevel::Error, &format!("пропущен {}: {}", path.display(), error))?;
+        }
+        for path in &result.skipped_mounts {
+            self.write(LogLevel::Info, &format!("пропущена точка монтирования {}", path.display()))?;
+        }
+        for path in &result.broken_symlinks {
+            self.write(LogLevel::Warn, &format!("битая ссылка {}", path.display()))?;
+        }
+        for path in &result.circular_symlinks {
+            self.write(LogLevel::Warn, &format!("циклическая ссылка {}", path.display()))?;
+        }
+        if result.cancelled {
+            self.write(LogLevel::Warn, &format!("сканирование {} прервано, результат неполный", root.display()))?;
+        }
+        self.write(LogLevel::Info, &format!("конец сканирования {}: {} файлов, {} директорий, {} байт, ошибок: {}, {:.2} с",
+            root.display(), stats.files_scanned, stats.dirs_scanned, result.root.size, stats.errors, stats.elapsed.as_secs_f64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::from_unix_seconds;
+
+    #[test]
+    fn formats_log_line() {
+        let line = log_line(from_unix_seconds(86400), LogLevel::Warn, "битая ссылка /p/link");
+        assert_eq!(line, "1970-01-02 00:00:00\tWARN\tбитая ссылка /p/link\n");
+    }
+}
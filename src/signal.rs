@@ -0,0 +1,68 @@
+//! Обработка Ctrl-C (SIGINT) для корректного завершения длительных режимов
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Устанавливает обработчик SIGINT: вместо завершения процесса
+/// выставляется флаг, который проверяет [`interrupted`]. Повторный Ctrl-C
+/// завершает процесс сразу, на случай если программа зависла, например на
+/// недоступной сетевой файловой системе
+pub fn install_interrupt_handler() {
+    sys::install();
+}
+
+/// Возвращает стандартную реакцию на SIGINT - завершение процесса
+pub fn restore_default_handler() {
+    sys::restore_default();
+}
+
+/// Был ли получен SIGINT после установки обработчика
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::c_int;
+    use std::sync::atomic::Ordering;
+
+    const SIGINT: c_int = 2;
+    const SIG_DFL: usize = 0;
+
+    extern "C" {
+        // Обработчик - адрес функции или SIG_DFL
+        fn signal(signum: c_int, handler: usize) -> usize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    // В обработчике сигнала допустимы только атомарные операции и _exit
+    extern "C" fn on_interrupt(_: c_int) {
+        if super::INTERRUPTED.swap(true, Ordering::SeqCst) {
+            // SAFETY: _exit async-signal-safe; 128 + номер сигнала, как у оболочки
+            unsafe { _exit(128 + SIGINT) }
+        }
+    }
+
+    pub fn install() {
+        // SAFETY: обработчик async-signal-safe, см. on_interrupt
+        unsafe {
+            signal(SIGINT, on_interrupt as extern "C" fn(c_int) as usize);
+        }
+    }
+
+    pub fn restore_default() {
+        // SAFETY: SIG_DFL - штатное значение обработчика
+        unsafe {
+            signal(SIGINT, SIG_DFL);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    // На остальных платформах Ctrl-C завершает процесс как обычно
+    pub fn install() {}
+
+    pub fn restore_default() {}
+}
@@ -0,0 +1,578 @@
+//! Снимки результатов сканирования в двоичном файле.
+//!
+//! Формат: сигнатура `DSKSNAP`, номер версии, затем поля в порядке объявления.
+//! Целые числа записываются в little-endian, строки и пути - длиной (u64)
+//! и байтами UTF-8, `Option` - байтом-признаком 0/1 перед значением.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::classify::categories_from_file_types;
+use crate::scan::{average, DirInfo, GitRepo, HardLink, MimeMismatch, OwnerUsage, ScanResult};
+
+const MAGIC: &[u8; 7] = b"DSKSNAP";
+const VERSION: u8 = 17;
+
+// Защита от выделения гигантских буферов при чтении поврежденного файла
+const MAX_STRING_LEN: u64 = 1 << 20;
+
+/// Ошибка чтения снимка
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    /// Файл не является снимком diskspace
+    NotASnapshot,
+    UnsupportedVersion(u8),
+    /// Данные повреждены
+    Corrupted(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "{}", err),
+            SnapshotError::NotASnapshot => write!(f, "файл не является снимком diskspace"),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "неподдерживаемая версия снимка: {}", v),
+            SnapshotError::Corrupted(what) => write!(f, "снимок поврежден: {}", what),
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            SnapshotError::Corrupted("неожиданный конец файла".to_string())
+        } else {
+            SnapshotError::Io(err)
+        }
+    }
+}
+
+/// Результат сканирования вместе с метаданными
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Просканированная директория
+    pub root: PathBuf,
+    /// Время начала сканирования
+    pub scanned_at: SystemTime,
+    pub result: ScanResult,
+}
+
+impl Snapshot {
+    pub fn new(root: &Path, scanned_at: SystemTime, result: ScanResult) -> Self {
+        Snapshot { root: root.to_path_buf(), scanned_at, result }
+    }
+
+    /// Записывает снимок в файл
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Читает снимок из файла
+    pub fn load(path: &Path) -> Result<Snapshot, SnapshotError> {
+        let mut reader = BufReader::new(File::open(path).map_err(SnapshotError::Io)?);
+        Snapshot::read_from(&mut reader)
+    }
+
+    /// Проверяет сигнатуру снимка в начале файла
+    pub fn is_snapshot_file(path: &Path) -> bool {
+        let mut magic = [0u8; 7];
+        File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && &magic == MAGIC
+    }
+
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        write_path(w, &self.root)?;
+        write_time(w, self.scanned_at)?;
+
+        let result = &self.result;
+        write_dir_info(w, &result.root)?;
+        write_u64(w, result.dir_infos.len() as u64)?;
+        for (path, info) in &result.dir_infos {
+            write_str(w, path)?;
+            write_dir_info(w, info)?;
+        }
+        write_paths(w, &result.broken_symlinks)?;
+        write_paths(w, &result.empty_dirs)?;
+        write_sized_paths(w, &result.temp_files)?;
+        write_sized_paths(w, &result.files)?;
+        write_u64(w, result.skipped.len() as u64)?;
+        for (path, error) in &result.skipped {
+            write_path(w, path)?;
+            write_str(w, error)?;
+        }
+        write_paths(w, &result.circular_symlinks)?;
+        write_u64(w, result.hard_links.len() as u64)?;
+        for link in &result.hard_links {
+            write_u64(w, link.size)?;
+            write_u64(w, link.nlink)?;
+            write_paths(w, &link.paths)?;
+        }
+        write_sized_paths(w, &result.time_machine)?;
+        write_paths(w, &result.zero_byte_files)?;
+        write_u64(w, result.owners.len() as u64)?;
+        for owner in &result.owners {
+            write_u64(w, owner.uid as u64)?;
+            write_u64(w, owner.size)?;
+            write_u64(w, owner.file_count as u64)?;
+            match &owner.largest_file {
+                Some((path, size)) => {
+                    w.write_all(&[1])?;
+                    write_path(w, path)?;
+                    write_u64(w, *size)?;
+                },
+                None => w.write_all(&[0])?,
+            }
+        }
+        write_paths(w, &result.world_writable)?;
+        write_paths(w, &result.suid_files)?;
+        write_u64(w, result.mime_mismatches.len() as u64)?;
+        for mismatch in &result.mime_mismatches {
+            write_path(w, &mismatch.path)?;
+            write_str(w, &mismatch.extension)?;
+            write_str(w, &mismatch.mime)?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(r: &mut R) -> Result<Snapshot, SnapshotError> {
+        let mut magic = [0u8; 7];
+        r.read_exact(&mut magic).map_err(|_| SnapshotError::NotASnapshot)?;
+        if &magic != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = read_u8(r)?;
+        // Версия 2 добавила DirInfo::inode_count, версия 3 - DirInfo::allocated_size,
+        // версия 4 - ScanResult::skipped, версия 5 - ScanResult::circular_symlinks,
+        // версия 6 - ScanResult::hard_links, версия 7 - скрытые и системные файлы
+        // в DirInfo, версия 8 - ресурсные вилки и копии Time Machine, версия 9 -
+        // файлы нулевого размера, версия 10 - ScanResult::owners, версия 11 -
+        // файлы, доступные на запись всем, и SUID/SGID, версия 12 - несовпадения
+        // расширения и MIME-типа, версия 13 - DirInfo::median_file_size, версия
+        // 14 - DirInfo::file_type_counts, версия 15 - DirInfo::top_files вместо
+        // одного самого большого файла, версия 16 - самый большой файл владельца,
+        // версия 17 - списки самых старых и самых новых файлов DirInfo вместо
+        // одного файла; в снимках старых версий эти поля пустые
+        if !(1..=VERSION).contains(&version) {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let root = read_path(r)?;
+        let scanned_at = read_time(r)?;
+        let root_info = read_dir_info(r, version)?;
+        let mut dir_infos = BTreeMap::new();
+        for _ in 0..read_u64(r)? {
+            let path = read_string(r)?;
+            dir_infos.insert(path, read_dir_info(r, version)?);
+        }
+
+        let mut result = ScanResult {
+            root: root_info,
+            dir_infos,
+            broken_symlinks: read_paths(r)?,
+            empty_dirs: read_paths(r)?,
+            temp_files: read_sized_paths(r)?,
+            files: read_sized_paths(r)?,
+            git_repos: Vec::new(),
+            skipped: Vec::new(),
+            circular_symlinks: Vec::new(),
+            hard_links: Vec::new(),
+            time_machine: Vec::new(),
+            zero_byte_files: Vec::new(),
+            owners: Vec::new(),
+            world_writable: Vec::new(),
+            suid_files: Vec::new(),
+            mime_mismatches: Vec::new(),
+            cancelled: false,
+            budget_exceeded: false,
+            filesystem_type: None,
+            max_depth_path: (PathBuf::new(), 0),
+            sample_percent: None,
+            sample_variance: 0.0,
+            depth_stats: Vec::new(),
+            mtime_days: BTreeMap::new(),
+            compress_estimates: BTreeMap::new(),
+            spans: Vec::new(),
+            file_sizes: Vec::new(),
+            skipped_mounts: Vec::new(),
+        };
+        if version >= 4 {
+            for _ in 0..read_u64(r)? {
+                result.skipped.push((read_path(r)?, read_string(r)?));
+            }
+        }
+        if version >= 5 {
+            result.circular_symlinks = read_paths(r)?;
+        }
+        if version >= 6 {
+            for _ in 0..read_u64(r)? {
+                result.hard_links.push(HardLink { size: read_u64(r)?, nlink: read_u64(r)?, paths: read_paths(r)? });
+            }
+        }
+        if version >= 8 {
+            result.time_machine = read_sized_paths(r)?;
+        }
+        if version >= 9 {
+            result.zero_byte_files = read_paths(r)?;
+        }
+        if version >= 10 {
+            for _ in 0..read_u64(r)? {
+                let uid = u32::try_from(read_u64(r)?)
+                    .map_err(|_| SnapshotError::Corrupted("некорректный uid".to_string()))?;
+                let (size, file_count) = (read_u64(r)?, read_usize(r)?);
+                let largest_file = if version >= 16 && read_flag(r)? { Some((read_path(r)?, read_u64(r)?)) } else { None };
+                result.owners.push(OwnerUsage { uid, size, file_count, largest_file });
+            }
+        }
+        if version >= 11 {
+            result.world_writable = read_paths(r)?;
+            result.suid_files = read_paths(r)?;
+        }
+        if version >= 12 {
+            for _ in 0..read_u64(r)? {
+                result.mime_mismatches.push(MimeMismatch {
+                    path: read_path(r)?,
+                    extension: read_string(r)?,
+                    mime: read_string(r)?,
+                });
+            }
+        }
+        result.git_repos = git_repos_from_dir_infos(&root, &result);
+        Ok(Snapshot { root, scanned_at, result })
+    }
+}
+
+fn write_u64<W: Write>(w: &mut W, value: u64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_str<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    write_u64(w, s.len() as u64)?;
+    w.write_all(s.as_bytes())
+}
+
+fn write_path<W: Write>(w: &mut W, path: &Path) -> io::Result<()> {
+    write_str(w, &path.to_string_lossy())
+}
+
+// Время хранится как секунды и наносекунды от эпохи Unix (до эпохи - отрицательные секунды)
+fn write_time<W: Write>(w: &mut W, time: SystemTime) -> io::Result<()> {
+    let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+        Err(e) => {
+            let d = e.duration();
+            if d.subsec_nanos() == 0 {
+                (-(d.as_secs() as i64), 0)
+            } else {
+                (-(d.as_secs() as i64) - 1, 1_000_000_000 - d.subsec_nanos())
+            }
+        }
+    };
+    w.write_all(&secs.to_le_bytes())?;
+    w.write_all(&nanos.to_le_bytes())
+}
+
+fn write_file_times<W: Write>(w: &mut W, entries: &[(PathBuf, SystemTime)]) -> io::Result<()> {
+    write_u64(w, entries.len() as u64)?;
+    for (path, time) in entries {
+        write_path(w, path)?;
+        write_time(w, *time)?;
+    }
+    Ok(())
+}
+
+fn write_dir_info<W: Write>(w: &mut W, info: &DirInfo) -> io::Result<()> {
+    write_u64(w, info.size)?;
+    write_u64(w, info.file_count as u64)?;
+    write_u64(w, info.top_files.len() as u64)?;
+    for (path, size) in &info.top_files {
+        write_path(w, path)?;
+        write_u64(w, *size)?;
+    }
+    write_u64(w, info.file_types.len() as u64)?;
+    for (ext, size) in &info.file_types {
+        write_str(w, ext)?;
+        write_u64(w, *size)?;
+    }
+    write_file_times(w, &info.oldest_files)?;
+    write_file_times(w, &info.newest_files)?;
+    write_u64(w, info.temp_size)?;
+    write_u64(w, info.temp_count as u64)?;
+    write_u64(w, info.inode_count)?;
+    write_u64(w, info.allocated_size)?;
+    write_u64(w, info.hidden_size)?;
+    write_u64(w, info.hidden_count as u64)?;
+    write_u64(w, info.system_size)?;
+    write_u64(w, info.system_count as u64)?;
+    write_u64(w, info.resource_fork_size)?;
+    write_u64(w, info.resource_fork_count as u64)?;
+    write_u64(w, info.zero_byte_count as u64)?;
+    write_u64(w, info.median_file_size)?;
+    write_u64(w, info.file_type_counts.len() as u64)?;
+    for (ext, count) in &info.file_type_counts {
+        write_str(w, ext)?;
+        write_u64(w, *count as u64)?;
+    }
+    Ok(())
+}
+
+fn write_paths<W: Write>(w: &mut W, paths: &[PathBuf]) -> io::Result<()> {
+    write_u64(w, paths.len() as u64)?;
+    paths.iter().try_for_each(|path| write_path(w, path))
+}
+
+fn write_sized_paths<W: Write>(w: &mut W, entries: &[(PathBuf, u64)]) -> io::Result<()> {
+    write_u64(w, entries.len() as u64)?;
+    for (path, size) in entries {
+        write_path(w, path)?;
+        write_u64(w, *size)?;
+    }
+    Ok(())
+}
+
+fn read_u8<R: Read>(r: &mut R) -> Result<u8, SnapshotError> {
+    let mut buf = [0u8; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u64<R: Read>(r: &mut R) -> Result<u64, SnapshotError> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_usize<R: Read>(r: &mut R) -> Result<usize, SnapshotError> {
+    usize::try_from(read_u64(r)?).map_err(|_| SnapshotError::Corrupted("слишком большое число".to_string()))
+}
+
+fn read_flag<R: Read>(r: &mut R) -> Result<bool, SnapshotError> {
+    match read_u8(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        other => Err(SnapshotError::Corrupted(format!("некорректный признак значения: {}", other))),
+    }
+}
+
+fn read_string<R: Read>(r: &mut R) -> Result<String, SnapshotError> {
+    let len = read_u64(r)?;
+    if len > MAX_STRING_LEN {
+        return Err(SnapshotError::Corrupted(format!("слишком длинная строка: {} байт", len)));
+    }
+    let mut buf = vec![0u8; len as usize];
+    r.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|_| SnapshotError::Corrupted("строка не в UTF-8".to_string()))
+}
+
+fn read_path<R: Read>(r: &mut R) -> Result<PathBuf, SnapshotError> {
+    read_string(r).map(PathBuf::from)
+}
+
+fn read_time<R: Read>(r: &mut R) -> Result<SystemTime, SnapshotError> {
+    let mut secs = [0u8; 8];
+    let mut nanos = [0u8; 4];
+    r.read_exact(&mut secs)?;
+    r.read_exact(&mut nanos)?;
+    let secs = i64::from_le_bytes(secs);
+    let nanos = u32::from_le_bytes(nanos);
+    if nanos >= 1_000_000_000 {
+        return Err(SnapshotError::Corrupted("некорректная метка времени".to_string()));
+    }
+    let time = if secs >= 0 {
+        UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+            .and_then(|t| t.checked_add(Duration::from_nanos(nanos as u64)))
+    };
+    time.ok_or_else(|| SnapshotError::Corrupted("некорректная метка времени".to_string()))
+}
+
+// До версии 17 хранился только один файл с признаком наличия
+fn read_file_times<R: Read>(r: &mut R, version: u8) -> Result<Vec<(PathBuf, SystemTime)>, SnapshotError> {
+    if version >= 17 {
+        let count = read_u64(r)?;
+        (0..count).map(|_| Ok((read_path(r)?, read_time(r)?))).collect()
+    } else if read_flag(r)? {
+        Ok(vec![(read_path(r)?, read_time(r)?)])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn read_dir_info<R: Read>(r: &mut R, version: u8) -> Result<DirInfo, SnapshotError> {
+    let size = read_u64(r)?;
+    let file_count = read_usize(r)?;
+    // До версии 15 хранился только самый большой файл
+    let top_files = if version >= 15 {
+        read_sized_paths(r)?
+    } else if read_flag(r)? {
+        vec![(read_path(r)?, read_u64(r)?)]
+    } else {
+        Vec::new()
+    };
+    let mut file_types = BTreeMap::new();
+    for _ in 0..read_u64(r)? {
+        let ext = read_string(r)?;
+        file_types.insert(ext, read_u64(r)?);
+    }
+    // Категории однозначно выводятся из расширений и в снимке не хранятся
+    let mut info = DirInfo {
+        size,
+        file_count,
+        top_files,
+        categories: categories_from_file_types(&file_types),
+        file_types,
+        oldest_files: read_file_times(r, version)?,
+        newest_files: read_file_times(r, version)?,
+        temp_size: read_u64(r)?,
+        temp_count: read_usize(r)?,
+        inode_count: if version >= 2 { read_u64(r)? } else { 0 },
+        allocated_size: if version >= 3 { read_u64(r)? } else { 0 },
+        hidden_size: if version >= 7 { read_u64(r)? } else { 0 },
+        hidden_count: if version >= 7 { read_usize(r)? } else { 0 },
+        system_size: if version >= 7 { read_u64(r)? } else { 0 },
+        system_count: if version >= 7 { read_usize(r)? } else { 0 },
+        resource_fork_size: if version >= 8 { read_u64(r)? } else { 0 },
+        resource_fork_count: if version >= 8 { read_usize(r)? } else { 0 },
+        zero_byte_count: if version >= 9 { read_usize(r)? } else { 0 },
+        average_file_size: average(size, file_count),
+        median_file_size: if version >= 13 { read_u64(r)? } else { 0 },
+        file_type_counts: BTreeMap::new(),
+    };
+    if version >= 14 {
+        for _ in 0..read_u64(r)? {
+            let ext = read_string(r)?;
+            info.file_type_counts.insert(ext, read_usize(r)?);
+        }
+    }
+    Ok(info)
+}
+
+fn read_paths<R: Read>(r: &mut R) -> Result<Vec<PathBuf>, SnapshotError> {
+    let count = read_u64(r)?;
+    (0..count).map(|_| read_path(r)).collect()
+}
+
+fn read_sized_paths<R: Read>(r: &mut R) -> Result<Vec<(PathBuf, u64)>, SnapshotError> {
+    let count = read_u64(r)?;
+    (0..count).map(|_| Ok((read_path(r)?, read_u64(r)?))).collect()
+}
+
+// Git-репозитории не хранятся в снимке: директории .git есть среди dir_infos,
+// а размер репозитория - это размер их родителя
+fn git_repos_from_dir_infos(root: &Path, result: &ScanResult) -> Vec<GitRepo> {
+    let mut repos: Vec<GitRepo> = result.dir_infos.iter()
+        .filter_map(|(path, info)| {
+            let path = Path::new(path);
+            if path.file_name()? != ".git" {
+                return None;
+            }
+            let parent = path.parent()?;
+            let repo_size = match parent.to_str().and_then(|p| result.dir_infos.get(p)) {
+                Some(parent_info) => parent_info.size,
+                None if parent == root => result.root.size,
+                None => return None,
+            };
+            Some(GitRepo { path: parent.to_path_buf(), git_size: info.size, repo_size })
+        })
+        .collect();
+    repos.sort_by(|a, b| a.path.cmp(&b.path));
+    repos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut info = DirInfo::new();
+        info.size = 4096;
+        info.file_count = 3;
+        info.inode_count = 4;
+        info.allocated_size = 8192;
+        info.system_size = 96;
+        info.system_count = 1;
+        info.resource_fork_size = 4096;
+        info.resource_fork_count = 1;
+        info.zero_byte_count = 2;
+        info.median_file_size = 1000;
+        info.top_files = vec![(PathBuf::from("/data/a/big.iso"), 4000), (PathBuf::from("/data/a/small.iso"), 96)];
+        info.file_types.insert("iso".to_string(), 4000);
+        info.file_type_counts.insert("iso".to_string(), 1);
+        info.oldest_files = vec![(PathBuf::from("/data/a/old"), UNIX_EPOCH - Duration::new(10, 5)),
+            (PathBuf::from("/data/a/older"), UNIX_EPOCH + Duration::from_secs(5))];
+        info.newest_files = vec![(PathBuf::from("/data/a/new"), UNIX_EPOCH + Duration::new(1_700_000_000, 42))];
+
+        let mut result = ScanResult { root: info.clone(), ..ScanResult::default() };
+        result.dir_infos.insert("/data/a".to_string(), info);
+        result.empty_dirs.push(PathBuf::from("/data/empty"));
+        result.zero_byte_files.push(PathBuf::from("/data/a/.lock"));
+        result.owners.push(OwnerUsage { uid: 1000, size: 4096, file_count: 3, largest_file: Some((PathBuf::from("/data/a/big.iso"), 4000)) });
+        result.world_writable.push(PathBuf::from("/data/a/shared.txt"));
+        result.suid_files.push(PathBuf::from("/data/bin/helper"));
+        result.mime_mismatches.push(MimeMismatch {
+            path: PathBuf::from("/data/a/photo.jpg"),
+            extension: "jpg".to_string(),
+            mime: "application/zip".to_string(),
+        });
+        result.circular_symlinks.push(PathBuf::from("/data/a/loop"));
+        result.hard_links.push(HardLink {
+            size: 100,
+            nlink: 3,
+            paths: vec![PathBuf::from("/data/a/one"), PathBuf::from("/data/a/two")],
+        });
+        result.temp_files.push((PathBuf::from("/data/x.tmp"), 12));
+        result.time_machine.push((PathBuf::from("/data/Backups.backupdb"), 2048));
+        result.skipped.push((PathBuf::from("/data/secret"), "Permission denied (os error 13)".to_string()));
+        let mut git = DirInfo::new();
+        git.size = 1024;
+        result.dir_infos.insert("/data/a/.git".to_string(), git);
+
+        let snapshot = Snapshot::new(Path::new("/data"), UNIX_EPOCH + Duration::from_secs(1_000), result);
+        let mut bytes = Vec::new();
+        snapshot.write_to(&mut bytes).unwrap();
+        let loaded = Snapshot::read_from(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(loaded.root, snapshot.root);
+        assert_eq!(loaded.scanned_at, snapshot.scanned_at);
+        let (a, b) = (&loaded.result.dir_infos["/data/a"], &snapshot.result.dir_infos["/data/a"]);
+        assert_eq!(a.top_files, b.top_files);
+        assert_eq!(a.file_types, b.file_types);
+        assert_eq!(a.file_type_counts, b.file_type_counts);
+        assert_eq!(a.oldest_files, b.oldest_files);
+        assert_eq!(a.newest_files, b.newest_files);
+        assert_eq!(a.inode_count, b.inode_count);
+        assert_eq!(a.allocated_size, b.allocated_size);
+        assert_eq!((a.system_size, a.system_count), (b.system_size, b.system_count));
+        assert_eq!((a.resource_fork_size, a.resource_fork_count), (b.resource_fork_size, b.resource_fork_count));
+        assert_eq!(a.zero_byte_count, b.zero_byte_count);
+        assert_eq!(a.median_file_size, b.median_file_size);
+        assert_eq!(loaded.result.empty_dirs, snapshot.result.empty_dirs);
+        assert_eq!(loaded.result.zero_byte_files, snapshot.result.zero_byte_files);
+        assert_eq!(loaded.result.owners, snapshot.result.owners);
+        assert_eq!(loaded.result.world_writable, snapshot.result.world_writable);
+        assert_eq!(loaded.result.suid_files, snapshot.result.suid_files);
+        assert_eq!(loaded.result.mime_mismatches, snapshot.result.mime_mismatches);
+        assert_eq!(loaded.result.temp_files, snapshot.result.temp_files);
+        assert_eq!(loaded.result.skipped, snapshot.result.skipped);
+        assert_eq!(loaded.result.circular_symlinks, snapshot.result.circular_symlinks);
+        assert_eq!(loaded.result.hard_links, snapshot.result.hard_links);
+        assert_eq!(loaded.result.time_machine, snapshot.result.time_machine);
+        assert_eq!(loaded.result.git_repos, vec![GitRepo {
+            path: PathBuf::from("/data/a"),
+            git_size: 1024,
+            repo_size: 4096,
+        }]);
+
+        assert!(matches!(Snapshot::read_from(&mut &b"garbage"[..]), Err(SnapshotError::NotASnapshot)));
+        assert!(matches!(Snapshot::read_from(&mut &bytes[..bytes.len() - 3]), Err(SnapshotError::Corrupted(_))));
+    }
+}
@@ -0,0 +1,96 @@
+//! Трассировка обхода (--tracing): спаны директорий в формате OpenTelemetry
+//! (OTLP/JSON) и их отправка по OTLP/HTTP, например в Jaeger
+
+use std::collections::hash_map::RandomState;
+use std::fmt::Write as _;
+use std::hash::BuildHasher;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::export::json_escape;
+use crate::pushgateway::{self, GatewayUrl, PushError};
+use crate::scan::DirSpan;
+
+/// Путь приема трасс OTLP/HTTP относительно адреса приемника
+pub const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+// Имя сервиса и библиотеки инструментирования в трассе
+const SERVICE_NAME: &str = "diskspace";
+
+/// Спаны сканирования в формате OTLP/JSON: одна трасса, спан `scan_dir` на
+/// каждую директорию, родитель спана - спан родительской директории.
+/// Идентификаторы случайны для каждого вызова
+pub fn to_otlp_json(spans: &[DirSpan]) -> String {
+    let state = RandomState::new();
+    let trace_id = format!("{:016x}{:016x}", state.hash_one("trace"), RandomState::new().hash_one("trace"));
+    otlp_json(spans, &trace_id, |path| state.hash_one(path))
+}
+
+// Собирает документ OTLP/JSON; span_id дает идентификатор спана директории
+fn otlp_json(spans: &[DirSpan], trace_id: &str, span_id: impl Fn(&Path) -> u64) -> String {
+    let mut out = String::new();
+    let _ = write!(out, "{{\"resourceSpans\":[{{\"resource\":{{\"attributes\":[{}]}},\"scopeSpans\":[{{\"scope\":{{\"name\":{}}},\"spans\":[",
+        string_attribute("service.name", SERVICE_NAME), json_escape(SERVICE_NAME));
+    for (i, span) in spans.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let parent = match span.path.parent() {
+            Some(parent) if span.depth > 0 => format!("{:016x}", span_id(parent)),
+            _ => String::new(),
+        };
+        let _ = write!(out, "{{\"traceId\":\"{}\",\"spanId\":\"{:016x}\",\"parentSpanId\":\"{}\",\"name\":\"scan_dir\",\"kind\":1,\
+            \"startTimeUnixNano\":\"{}\",\"endTimeUnixNano\":\"{}\",\"attributes\":[{},{},{},{}]}}",
+            trace_id, span_id(&span.path), parent, unix_nanos(span.start), unix_nanos(span.end),
+            string_attribute("dir.path", &span.path.to_string_lossy()),
+            int_attribute("dir.depth", u128::from(span.depth)),
+            int_attribute("dir.child_count", span.child_count as u128),
+            int_attribute("dir.own_time_ns", span.own_duration().as_nanos()));
+    }
+    out.push_str("]}]}]}");
+    out
+}
+
+/// Отправляет спаны по OTLP/HTTP в `<адрес>/v1/traces`
+pub fn export(url: &GatewayUrl, spans: &[DirSpan]) -> Result<(), PushError> {
+    pushgateway::post(url, &format!("{}{}", url.path, OTLP_TRACES_PATH), "application/json", &to_otlp_json(spans))
+}
+
+fn string_attribute(key: &str, value: &str) -> String {
+    format!("{{\"key\":{},\"value\":{{\"stringValue\":{}}}}}", json_escape(key), json_escape(value))
+}
+
+// Целые числа в OTLP/JSON передаются строками
+fn int_attribute(key: &str, value: u128) -> String {
+    format!("{{\"key\":{},\"value\":{{\"intValue\":\"{}\"}}}}", json_escape(key), value)
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn links_spans_to_parent_dirs() {
+        let at = |nanos| UNIX_EPOCH + Duration::from_nanos(nanos);
+        let span = |path: &str, depth, start, own_end, end| DirSpan {
+            path: PathBuf::from(path), depth, start: at(start), own_end: at(own_end), end: at(end), child_count: 2,
+        };
+        let spans = [span("/data", 0, 100, 150, 400), span("/data/a", 1, 160, 300, 300)];
+        let ids = |path: &Path| if path == Path::new("/data") { 1 } else { 2 };
+        let json = otlp_json(&spans, "00000000000000000000000000000abc", ids);
+
+        assert!(json.starts_with("{\"resourceSpans\":[{\"resource\":{\"attributes\":[{\"key\":\"service.name\""));
+        assert!(json.contains("\"spanId\":\"0000000000000001\",\"parentSpanId\":\"\",\"name\":\"scan_dir\",\"kind\":1,\
+            \"startTimeUnixNano\":\"100\",\"endTimeUnixNano\":\"400\""));
+        assert!(json.contains("\"spanId\":\"0000000000000002\",\"parentSpanId\":\"0000000000000001\""));
+        assert!(json.contains("{\"key\":\"dir.path\",\"value\":{\"stringValue\":\"/data/a\"}}"));
+        assert!(json.contains("{\"key\":\"dir.own_time_ns\",\"value\":{\"intValue\":\"140\"}}"));
+        assert!(json.ends_with("]}]}]}"));
+    }
+}
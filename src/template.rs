@@ -0,0 +1,726 @@
+//! Шаблоны отчетов для `--report-template`.
+//!
+//! Поддерживается подмножество синтаксиса Tera (Jinja2): вывод `{{ выражение }}`,
+//! блоки `{% if %}`/`{% elif %}`/`{% else %}`/`{% endif %}` и
+//! `{% for x in список %}`/`{% endfor %}` с переменными `loop.index`,
+//! `loop.index0`, `loop.first`, `loop.last`, комментарии `{# #}` и удаление
+//! пробелов вокруг тега через `{%-` и `-%}`. В условиях доступны `and`, `or`,
+//! `not` и сравнения `==`, `!=`, `<`, `<=`, `>`, `>=`. Фильтры перечислены в
+//! [`FILTERS`]
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::format::{format_size, size_format};
+
+/// Фильтры выражений: `size` - размер в байтах в читаемом виде,
+/// `round(precision=N)` - число с N знаками после запятой, `ljust(width=N)`
+/// и `rjust(width=N)` - выравнивание по левому и правому краю, `upper`,
+/// `lower`, `length`, `default(value=...)` - замена пустого значения
+pub const FILTERS: &[&str] = &["size", "round", "ljust", "rjust", "upper", "lower", "length", "default"];
+
+/// Значение контекста шаблона
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// Объект из пар (ключ, значение)
+    pub fn object<I, K>(fields: I) -> Value
+    where
+        I: IntoIterator<Item = (K, Value)>,
+        K: Into<String>,
+    {
+        Value::Object(fields.into_iter().map(|(key, value)| (key.into(), value)).collect())
+    }
+
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Integer(n) => *n != 0,
+            Value::Float(x) => *x != 0.0,
+            Value::String(s) => !s.is_empty(),
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(fields) => !fields.is_empty(),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(n) => Some(*n as f64),
+            Value::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "логическое значение",
+            Value::Integer(_) | Value::Float(_) => "число",
+            Value::String(_) => "строка",
+            Value::Array(_) => "список",
+            Value::Object(_) => "объект",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::String(s) => f.write_str(s),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            },
+            Value::Object(_) => f.write_str("[объект]"),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Value {
+        Value::Integer(i64::try_from(n).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<usize> for Value {
+    fn from(n: usize) -> Value {
+        Value::from(n as u64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Value {
+        Value::Float(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Value {
+        Value::Array(items)
+    }
+}
+
+/// Ошибка разбора или вывода шаблона в строке с указанным номером
+#[derive(Debug, PartialEq)]
+pub struct TemplateError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "строка {}: {}", self.line, self.message)
+    }
+}
+
+fn error<T>(line: usize, message: impl Into<String>) -> Result<T, TemplateError> {
+    Err(TemplateError { line, message: message.into() })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    /// Путь к значению: `dir.size`
+    Var(Vec<String>),
+    Filter { value: Box<Expr>, name: String, args: Vec<(String, Expr)> },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Output(Expr, usize),
+    /// Ветви (условие, тело) и тело `else`
+    If(Vec<(Expr, Vec<Node>)>, Vec<Node>, usize),
+    For { var: String, iterable: Expr, body: Vec<Node>, line: usize },
+}
+
+/// Разобранный шаблон
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+// Фрагмент исходного текста шаблона до разбора тегов
+#[derive(Debug)]
+enum Piece {
+    Text(String),
+    Output(String, usize),
+    Tag(String, usize),
+}
+
+// Делит текст на фрагменты и применяет удаление пробелов `{%-` / `-%}`
+fn split_pieces(text: &str) -> Result<Vec<Piece>, TemplateError> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    let mut line = 1;
+    let mut trim_next = false;
+    while !rest.is_empty() {
+        let start = ["{{", "{%", "{#"].iter().filter_map(|open| rest.find(open)).min();
+        let Some(start) = start else {
+            push_text(&mut pieces, rest, trim_next, false);
+            break;
+        };
+        let kind = &rest[start..start + 2];
+        let close = match kind {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let body_start = start + 2;
+        let trim_before = rest[body_start..].starts_with('-');
+        push_text(&mut pieces, &rest[..start], trim_next, trim_before);
+        line += rest[..start].matches('\n').count();
+        let Some(len) = rest[body_start..].find(close) else {
+            return error(line, format!("незакрытый тег {}", kind));
+        };
+        let body = &rest[body_start..body_start + len];
+        trim_next = body.ends_with('-') && kind != "{#";
+        let inner = body.strip_prefix('-').unwrap_or(body);
+        let inner = inner.strip_suffix('-').filter(|_| trim_next).unwrap_or(inner).trim();
+        match kind {
+            "{{" => pieces.push(Piece::Output(inner.to_string(), line)),
+            "{%" => pieces.push(Piece::Tag(inner.to_string(), line)),
+            _ => {},
+        }
+        line += body.matches('\n').count();
+        rest = &rest[body_start + len + 2..];
+    }
+    Ok(pieces)
+}
+
+fn push_text(pieces: &mut Vec<Piece>, text: &str, trim_start: bool, trim_end: bool) {
+    let text = if trim_start { text.trim_start() } else { text };
+    let text = if trim_end { text.trim_end() } else { text };
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text.to_string()));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(Value),
+    Str(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: &[&str] = &["==", "!=", "<=", ">=", "<", ">", ".", "|", "(", ")", ",", "="];
+
+fn tokenize(src: &str, line: usize) -> Result<Vec<Token>, TemplateError> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let end = chars[i + 1..].iter().position(|&ch| ch == c).map(|pos| i + 1 + pos);
+            let Some(end) = end else {
+                return error(line, format!("незакрытая строка в выражении: {}", src));
+            };
+            tokens.push(Token::Str(chars[i + 1..end].iter().collect()));
+            i = end + 1;
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = match text.parse::<i64>() {
+                Ok(n) => Value::Integer(n),
+                Err(_) => match text.parse::<f64>() {
+                    Ok(x) => Value::Float(x),
+                    Err(_) => return error(line, format!("некорректное число: {}", text)),
+                },
+            };
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) else {
+                return error(line, format!("неожиданный символ '{}' в выражении: {}", c, src));
+            };
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+// Разбор выражения рекурсивным спуском: or > and > not > сравнение > фильтры
+struct ExprParser {
+    tokens: Vec<Token>,
+    pos: usize,
+    line: usize,
+}
+
+impl ExprParser {
+    fn new(src: &str, line: usize) -> Result<Self, TemplateError> {
+        Ok(ExprParser { tokens: tokenize(src, line)?, pos: 0, line })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Ident(s)) if s == keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn ident(&mut self) -> Result<String, TemplateError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Ok(name.clone())
+            },
+            _ => error(self.line, "ожидалось имя"),
+        }
+    }
+
+    fn finish(&self) -> Result<(), TemplateError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => error(self.line, format!("лишний элемент выражения: {:?}", token)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, TemplateError> {
+        let mut expr = self.and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, TemplateError> {
+        let mut expr = self.not()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, TemplateError> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, TemplateError> {
+        let left = self.filtered()?;
+        let ops = [("==", CompareOp::Eq), ("!=", CompareOp::Ne), ("<=", CompareOp::Le), (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt), (">", CompareOp::Gt)];
+        for (symbol, op) in ops {
+            if self.eat_symbol(symbol) {
+                return Ok(Expr::Compare(op, Box::new(left), Box::new(self.filtered()?)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn filtered(&mut self) -> Result<Expr, TemplateError> {
+        let mut expr = self.primary()?;
+        while self.eat_symbol("|") {
+            let name = self.ident()?;
+            if !FILTERS.contains(&name.as_str()) {
+                return error(self.line, format!("неизвестный фильтр '{}' (доступны: {})", name, FILTERS.join(", ")));
+            }
+            let mut args = Vec::new();
+            if self.eat_symbol("(") && !self.eat_symbol(")") {
+                loop {
+                    let key = self.ident()?;
+                    if !self.eat_symbol("=") {
+                        return error(self.line, format!("аргумент фильтра '{}' задается как имя=значение", name));
+                    }
+                    args.push((key, self.primary()?));
+                    if self.eat_symbol(")") {
+                        break;
+                    }
+                    if !self.eat_symbol(",") {
+                        return error(self.line, format!("ожидалась ',' или ')' в аргументах фильтра '{}'", name));
+                    }
+                }
+            }
+            expr = Expr::Filter { value: Box::new(expr), name, args };
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, TemplateError> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Literal(n)),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Ident(name)) if name == "true" || name == "false" => Ok(Expr::Literal(Value::Bool(name == "true"))),
+            Some(Token::Ident(name)) => {
+                let mut path = vec![name];
+                while self.eat_symbol(".") {
+                    path.push(self.ident()?);
+                }
+                Ok(Expr::Var(path))
+            },
+            Some(Token::Symbol("(")) => {
+                let expr = self.or()?;
+                if !self.eat_symbol(")") {
+                    return error(self.line, "ожидалась ')'");
+                }
+                Ok(expr)
+            },
+            _ => error(self.line, "ожидалось значение"),
+        }
+    }
+}
+
+fn parse_expr(src: &str, line: usize) -> Result<Expr, TemplateError> {
+    let mut parser = ExprParser::new(src, line)?;
+    let expr = parser.or()?;
+    parser.finish()?;
+    Ok(expr)
+}
+
+// Закрывающий тег блока и номер его строки
+type EndTag = Option<(String, usize)>;
+
+// Разбирает фрагменты до одного из тегов end; возвращает узлы и найденный тег
+fn parse_nodes<I>(pieces: &mut I, end: &[&str]) -> Result<(Vec<Node>, EndTag), TemplateError>
+where
+    I: Iterator<Item = Piece>,
+{
+    let mut nodes = Vec::new();
+    while let Some(piece) = pieces.next() {
+        match piece {
+            Piece::Text(text) => nodes.push(Node::Text(text)),
+            Piece::Output(src, line) => nodes.push(Node::Output(parse_expr(&src, line)?, line)),
+            Piece::Tag(tag, line) => {
+                let keyword = tag.split_whitespace().next().unwrap_or("");
+                if end.contains(&keyword) {
+                    return Ok((nodes, Some((tag, line))));
+                }
+                let args = tag[keyword.len()..].trim();
+                match keyword {
+                    "if" => nodes.push(parse_if(pieces, args, line)?),
+                    "for" => nodes.push(parse_for(pieces, args, line)?),
+                    _ => return error(line, format!("неожиданный тег {{% {} %}}", tag)),
+                }
+            },
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn parse_if<I>(pieces: &mut I, condition: &str, line: usize) -> Result<Node, TemplateError>
+where
+    I: Iterator<Item = Piece>,
+{
+    let mut branches = Vec::new();
+    let mut condition = parse_expr(condition, line)?;
+    loop {
+        let (body, end) = parse_nodes(pieces, &["elif", "else", "endif"])?;
+        let Some((tag, tag_line)) = end else {
+            return error(line, "нет {% endif %}");
+        };
+        branches.push((condition, body));
+        match tag.split_whitespace().next() {
+            Some("elif") => condition = parse_expr(tag["elif".len()..].trim(), tag_line)?,
+            Some("else") => {
+                let (else_body, end) = parse_nodes(pieces, &["endif"])?;
+                if end.is_none() {
+                    return error(line, "нет {% endif %}");
+                }
+                return Ok(Node::If(branches, else_body, line));
+            },
+            _ => return Ok(Node::If(branches, Vec::new(), line)),
+        }
+    }
+}
+
+fn parse_for<I>(pieces: &mut I, args: &str, line: usize) -> Result<Node, TemplateError>
+where
+    I: Iterator<Item = Piece>,
+{
+    let (var, iterable) = match args.split_once(" in ") {
+        Some((var, iterable)) if !var.trim().is_empty() && var.trim().chars().all(|c| c.is_alphanumeric() || c == '_') => {
+            (var.trim().to_string(), parse_expr(iterable, line)?)
+        },
+        _ => return error(line, "ожидалось {% for имя in список %}"),
+    };
+    let (body, end) = parse_nodes(pieces, &["endfor"])?;
+    if end.is_none() {
+        return error(line, "нет {% endfor %}");
+    }
+    Ok(Node::For { var, iterable, body, line })
+}
+
+// Переменные цикла поверх контекста шаблона
+struct Scope<'a> {
+    context: &'a Value,
+    locals: Vec<(String, Value)>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, path: &[String], line: usize) -> Result<Value, TemplateError> {
+        let root = self.locals.iter().rev()
+            .find(|(name, _)| *name == path[0])
+            .map(|(_, value)| value)
+            .or_else(|| match self.context {
+                Value::Object(fields) => fields.get(&path[0]),
+                _ => None,
+            });
+        let Some(mut value) = root else {
+            return error(line, format!("неизвестная переменная '{}'", path[0]));
+        };
+        for key in &path[1..] {
+            value = match value {
+                Value::Object(fields) => match fields.get(key) {
+                    Some(field) => field,
+                    None => return error(line, format!("у '{}' нет поля '{}'", path.join("."), key)),
+                },
+                other => return error(line, format!("'{}': у значения типа {} нет полей", path.join("."), other.type_name())),
+            };
+        }
+        Ok(value.clone())
+    }
+
+    fn eval(&self, expr: &Expr, line: usize) -> Result<Value, TemplateError> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Var(path) => self.lookup(path, line),
+            Expr::Not(inner) => Ok(Value::Bool(!self.eval(inner, line)?.is_truthy())),
+            Expr::And(a, b) => Ok(Value::Bool(self.eval(a, line)?.is_truthy() && self.eval(b, line)?.is_truthy())),
+            Expr::Or(a, b) => Ok(Value::Bool(self.eval(a, line)?.is_truthy() || self.eval(b, line)?.is_truthy())),
+            Expr::Compare(op, a, b) => compare(*op, &self.eval(a, line)?, &self.eval(b, line)?, line).map(Value::Bool),
+            Expr::Filter { value, name, args } => {
+                let value = self.eval(value, line)?;
+                let mut evaluated = Vec::with_capacity(args.len());
+                for (key, arg) in args {
+                    evaluated.push((key.as_str(), self.eval(arg, line)?));
+                }
+                apply_filter(name, value, &evaluated, line)
+            },
+        }
+    }
+
+    fn render(&mut self, nodes: &[Node], out: &mut String) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Output(expr, line) => out.push_str(&self.eval(expr, *line)?.to_string()),
+                Node::If(branches, else_body, line) => {
+                    let mut taken = None;
+                    for (condition, body) in branches {
+                        if self.eval(condition, *line)?.is_truthy() {
+                            taken = Some(body);
+                            break;
+                        }
+                    }
+                    self.render(taken.unwrap_or(else_body), out)?;
+                },
+                Node::For { var, iterable, body, line } => {
+                    let items = match self.eval(iterable, *line)? {
+                        Value::Array(items) => items,
+                        other => return error(*line, format!("for обходит только списки, а не {}", other.type_name())),
+                    };
+                    let count = items.len();
+                    for (index, item) in items.into_iter().enumerate() {
+                        let info = Value::object([
+                            ("index", Value::from(index + 1)),
+                            ("index0", Value::from(index)),
+                            ("first", Value::Bool(index == 0)),
+                            ("last", Value::Bool(index + 1 == count)),
+                        ]);
+                        self.locals.push(("loop".to_string(), info));
+                        self.locals.push((var.clone(), item));
+                        let result = self.render(body, out);
+                        self.locals.truncate(self.locals.len() - 2);
+                        result?;
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn compare(op: CompareOp, a: &Value, b: &Value, line: usize) -> Result<bool, TemplateError> {
+    let ordering = match (a.as_f64(), b.as_f64(), a, b) {
+        (Some(x), Some(y), _, _) => x.partial_cmp(&y),
+        (_, _, Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    };
+    match (op, ordering) {
+        (CompareOp::Eq, Some(ordering)) => Ok(ordering.is_eq()),
+        (CompareOp::Ne, Some(ordering)) => Ok(ordering.is_ne()),
+        (CompareOp::Eq, None) => Ok(a == b),
+        (CompareOp::Ne, None) => Ok(a != b),
+        (CompareOp::Lt, Some(ordering)) => Ok(ordering.is_lt()),
+        (CompareOp::Le, Some(ordering)) => Ok(ordering.is_le()),
+        (CompareOp::Gt, Some(ordering)) => Ok(ordering.is_gt()),
+        (CompareOp::Ge, Some(ordering)) => Ok(ordering.is_ge()),
+        _ => error(line, format!("нельзя сравнить {} и {}", a.type_name(), b.type_name())),
+    }
+}
+
+fn apply_filter(name: &str, value: Value, args: &[(&str, Value)], line: usize) -> Result<Value, TemplateError> {
+    let arg = |key: &str| args.iter().find(|(k, _)| *k == key).map(|(_, v)| v);
+    let count_arg = |key: &str, default: Option<usize>| -> Result<usize, TemplateError> {
+        match (arg(key), default) {
+            (Some(Value::Integer(n)), _) if *n >= 0 => Ok(*n as usize),
+            (None, Some(default)) => Ok(default),
+            _ => error(line, format!("фильтру {} нужен аргумент {}=ЧИСЛО", name, key)),
+        }
+    };
+    let number = |value: &Value| value.as_f64()
+        .map_or_else(|| error(line, format!("фильтр {} применим только к числам, а не к {}", name, value.type_name())), Ok);
+    match name {
+        "size" => Ok(Value::String(format_size(number(&value)?.max(0.0) as u64, size_format()))),
+        "round" => {
+            let precision = count_arg("precision", Some(0))?;
+            Ok(Value::String(format!("{:.*}", precision, number(&value)?)))
+        },
+        "ljust" => Ok(Value::String(format!("{:<w$}", value.to_string(), w = count_arg("width", None)?))),
+        "rjust" => Ok(Value::String(format!("{:>w$}", value.to_string(), w = count_arg("width", None)?))),
+        "upper" => Ok(Value::String(value.to_string().to_uppercase())),
+        "lower" => Ok(Value::String(value.to_string().to_lowercase())),
+        "length" => match &value {
+            Value::Array(items) => Ok(Value::from(items.len())),
+            Value::Object(fields) => Ok(Value::from(fields.len())),
+            Value::String(s) => Ok(Value::from(s.chars().count())),
+            other => error(line, format!("у значения типа {} нет длины", other.type_name())),
+        },
+        "default" => match arg("value") {
+            Some(default) if !value.is_truthy() => Ok(default.clone()),
+            Some(_) => Ok(value),
+            None => error(line, "фильтру default нужен аргумент value=..."),
+        },
+        _ => error(line, format!("неизвестный фильтр '{}'", name)),
+    }
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Template, TemplateError> {
+        let mut pieces = split_pieces(text)?.into_iter();
+        let (nodes, end) = parse_nodes(&mut pieces, &[])?;
+        debug_assert!(end.is_none());
+        Ok(Template { nodes })
+    }
+
+    /// Выводит шаблон; context должен быть объектом с переменными верхнего уровня
+    pub fn render(&self, context: &Value) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        Scope { context, locals: Vec::new() }.render(&self.nodes, &mut out)?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> Value {
+        let dirs = vec![
+            Value::object([("path", Value::from("/a")), ("size", Value::from(2048u64))]),
+            Value::object([("path", Value::from("/b")), ("size", Value::from(10u64))]),
+        ];
+        Value::object([("title", Value::from("Отчет")), ("dirs", Value::from(dirs)), ("pct", Value::from(12.345))])
+    }
+
+    fn render(template: &str) -> Result<String, TemplateError> {
+        Template::parse(template)?.render(&context())
+    }
+
+    #[test]
+    fn renders_loops_and_conditions() {
+        let template = "{{ title | upper }}:\n{% for dir in dirs -%}\n\
+            {{ loop.index }}. {{ dir.path }} {% if dir.size > 1000 %}большая{% elif dir.size == 10 %}десять{% else %}?{% endif %}\
+            {% if not loop.last %}, {% endif %}\n{%- endfor %}\n{# комментарий #}{{ dirs | length }}";
+        assert_eq!(render(template).unwrap(), "ОТЧЕТ:\n1. /a большая, 2. /b десять\n2");
+    }
+
+    #[test]
+    fn applies_filters() {
+        assert_eq!(render("[{{ pct | round(precision=1) | rjust(width=6) }}]").unwrap(), "[  12.3]");
+        assert_eq!(render("[{{ 'абв' | ljust(width=4) }}]").unwrap(), "[абв ]");
+        assert_eq!(render("{{ '' | default(value='-') }} {{ title | default(value='-') }}").unwrap(), "- Отчет");
+        assert_eq!(render("{{ title | round }}").unwrap_err().message, "фильтр round применим только к числам, а не к строка");
+    }
+
+    #[test]
+    fn default_template_parses() {
+        assert!(Template::parse(crate::export::DEFAULT_TEMPLATE).is_ok());
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        assert_eq!(Template::parse("a\n{% if x %}").unwrap_err(), TemplateError { line: 2, message: "нет {% endif %}".to_string() });
+        assert_eq!(Template::parse("{{ x | nope }}").unwrap_err().line, 1);
+        assert_eq!(Template::parse("\n\n{{ x").unwrap_err().line, 3);
+        assert_eq!(render("\n{{ unknown }}").unwrap_err(), TemplateError { line: 2, message: "неизвестная переменная 'unknown'".to_string() });
+    }
+}
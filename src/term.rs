@@ -0,0 +1,68 @@
+//! Сведения о терминале
+
+use std::env;
+
+/// Ширина терминала по умолчанию, если ее не удалось определить
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Высота терминала по умолчанию, если ее не удалось определить
+pub const DEFAULT_HEIGHT: usize = 24;
+
+/// Ширина терминала в символах: ioctl(TIOCGWINSZ) для stdout,
+/// затем переменная окружения `COLUMNS`, иначе [`DEFAULT_WIDTH`]
+pub fn terminal_width() -> usize {
+    sys::stdout_size().map(|(cols, _)| cols)
+        .or_else(|| env_size("COLUMNS"))
+        .filter(|&w| w > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Высота терминала в строках: ioctl(TIOCGWINSZ), затем `LINES`, иначе [`DEFAULT_HEIGHT`]
+pub fn terminal_height() -> usize {
+    sys::stdout_size().map(|(_, rows)| rows)
+        .or_else(|| env_size("LINES"))
+        .filter(|&h| h > 0)
+        .unwrap_or(DEFAULT_HEIGHT)
+}
+
+fn env_size(name: &str) -> Option<usize> {
+    env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod sys {
+    use std::os::raw::{c_int, c_ulong, c_ushort};
+
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Winsize {
+        ws_row: c_ushort,
+        ws_col: c_ushort,
+        ws_xpixel: c_ushort,
+        ws_ypixel: c_ushort,
+    }
+
+    #[cfg(target_os = "linux")]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(target_os = "macos")]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    /// (колонки, строки) терминала, к которому подключен stdout
+    pub fn stdout_size() -> Option<(usize, usize)> {
+        let mut size = Winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        // SAFETY: ioctl записывает только в переданную структуру Winsize
+        let rc = unsafe { ioctl(1, TIOCGWINSZ, &mut size as *mut Winsize) };
+        (rc == 0 && size.ws_col > 0).then_some((size.ws_col as usize, size.ws_row as usize))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod sys {
+    pub fn stdout_size() -> Option<(usize, usize)> {
+        None
+    }
+}
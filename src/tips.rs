@@ -0,0 +1,319 @@
+//! Советы по оптимизации дискового пространства.
+//!
+//! Каждый совет формирует отдельное правило ([`Rule`]); набор встроенных
+//! правил возвращает [`builtin_rules`]. Правила можно отключать по имени
+//! (`--disable-rule`)
+
+use crate::color::{self, Color};
+use crate::format::{format_size, size_format};
+use crate::scan::{DirInfo, ScanResult};
+
+const GIB: u64 = 1024 * 1024 * 1024;
+const MIB: u64 = 1024 * 1024;
+
+/// Сколько крупнейших директорий просматривают правила по путям и типам файлов
+const TOP_DIRS: usize = 5;
+
+/// Важность совета
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    /// Значок перед текстом совета
+    pub fn marker(self) -> &'static str {
+        match self {
+            Severity::Info => "🔸",
+            Severity::Warning => "⚠️ ",
+            Severity::Critical => "❗",
+        }
+    }
+}
+
+/// Совет, выданный правилом
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tip {
+    /// Имя правила (см. [`Rule::name`])
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Правило, по результату сканирования решающее, нужен ли совет
+pub trait Rule {
+    /// Имя для `--disable-rule`: строчные буквы и дефисы
+    fn name(&self) -> &'static str;
+
+    fn severity(&self) -> Severity;
+
+    /// Совет, если правило сработало
+    fn evaluate(&self, result: &ScanResult) -> Option<Tip>;
+
+    /// Совет этого правила с текстом message
+    fn tip(&self, message: String) -> Tip {
+        Tip { rule: self.name(), severity: self.severity(), message }
+    }
+}
+
+/// Самая большая директория занимает больше [`LargeDirRule::THRESHOLD`]
+pub struct LargeDirRule;
+
+impl LargeDirRule {
+    pub const THRESHOLD: u64 = GIB;
+}
+
+impl Rule for LargeDirRule {
+    fn name(&self) -> &'static str {
+        "large-dir"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn evaluate(&self, result: &ScanResult) -> Option<Tip> {
+        let (path, info) = largest_dirs(result, 1).into_iter().next()?;
+        (info.size > Self::THRESHOLD).then(|| self.tip(format!(
+            "Директория '{}' занимает {}, что составляет значительную часть дискового пространства.",
+            path, format_size(info.size, size_format()))))
+    }
+}
+
+/// Среди крупнейших директорий есть директория логов больше
+/// [`LargeLogsRule::THRESHOLD`]
+pub struct LargeLogsRule;
+
+impl LargeLogsRule {
+    pub const THRESHOLD: u64 = 100 * MIB;
+}
+
+impl Rule for LargeLogsRule {
+    fn name(&self) -> &'static str {
+        "large-logs"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn evaluate(&self, result: &ScanResult) -> Option<Tip> {
+        largest_dirs(result, TOP_DIRS).into_iter()
+            .any(|(path, info)| path.to_lowercase().contains("log") && info.size > Self::THRESHOLD)
+            .then(|| self.tip("Обнаружены большие лог-файлы. Регулярная очистка логов может освободить значительное пространство.".to_string()))
+    }
+}
+
+/// Видео в одной из крупнейших директорий занимает больше
+/// [`LargeMediaRule::THRESHOLD`] одного формата
+pub struct LargeMediaRule;
+
+impl LargeMediaRule {
+    pub const THRESHOLD: u64 = 500 * MIB;
+    const EXTENSIONS: [&'static str; 3] = ["mp4", "mov", "avi"];
+}
+
+impl Rule for LargeMediaRule {
+    fn name(&self) -> &'static str {
+        "large-media"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn evaluate(&self, result: &ScanResult) -> Option<Tip> {
+        largest_dirs(result, TOP_DIRS).into_iter()
+            .flat_map(|(_, info)| &info.file_types)
+            .any(|(ext, size)| Self::EXTENSIONS.contains(&ext.as_str()) && *size > Self::THRESHOLD)
+            .then(|| self.tip("Медиафайлы занимают много места. Рассмотрите возможность переноса видео на внешний носитель или в облачное хранилище.".to_string()))
+    }
+}
+
+/// Среди крупнейших директорий есть директория загрузок
+pub struct DownloadsRule;
+
+impl Rule for DownloadsRule {
+    fn name(&self) -> &'static str {
+        "downloads"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn evaluate(&self, result: &ScanResult) -> Option<Tip> {
+        largest_dirs(result, TOP_DIRS).into_iter()
+            .any(|(path, _)| path.to_lowercase().contains("download"))
+            .then(|| self.tip("Директория загрузок содержит много файлов. Очистка временных и ненужных загрузок может освободить пространство.".to_string()))
+    }
+}
+
+/// Самый большой файл больше [`LargeFileRule::THRESHOLD`]
+pub struct LargeFileRule;
+
+impl LargeFileRule {
+    pub const THRESHOLD: u64 = GIB;
+}
+
+impl Rule for LargeFileRule {
+    fn name(&self) -> &'static str {
+        "large-file"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn evaluate(&self, result: &ScanResult) -> Option<Tip> {
+        let (path, size) = result.root.largest_file()?;
+        (*size > Self::THRESHOLD).then(|| self.tip(format!(
+            "Файл '{}' занимает {}. Удаление или архивация этого файла значительно освободит место.",
+            path.display(), format_size(*size, size_format()))))
+    }
+}
+
+/// Общий совет о сжатии, выдается всегда
+pub struct CompressionRule;
+
+impl Rule for CompressionRule {
+    fn name(&self) -> &'static str {
+        "compression"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn evaluate(&self, _result: &ScanResult) -> Option<Tip> {
+        Some(self.tip("Рассмотрите использование инструментов сжатия для регулярно используемых файлов.".to_string()))
+    }
+}
+
+/// Общий совет об очистке системных файлов, выдается всегда
+pub struct SystemCleanupRule;
+
+impl Rule for SystemCleanupRule {
+    fn name(&self) -> &'static str {
+        "system-cleanup"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn evaluate(&self, _result: &ScanResult) -> Option<Tip> {
+        Some(self.tip("Для системных файлов используйте команды очистки, специфичные для вашей ОС.".to_string()))
+    }
+}
+
+/// Встроенные правила в порядке вывода советов одинаковой важности
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(LargeDirRule),
+        Box::new(LargeLogsRule),
+        Box::new(LargeMediaRule),
+        Box::new(DownloadsRule),
+        Box::new(LargeFileRule),
+        Box::new(CompressionRule),
+        Box::new(SystemCleanupRule),
+    ]
+}
+
+/// Имена встроенных правил через запятую, для сообщений об ошибках
+pub fn builtin_rule_names() -> String {
+    builtin_rules().iter().map(|rule| rule.name()).collect::<Vec<_>>().join(", ")
+}
+
+/// Советы всех правил, кроме отключенных, от самых важных к менее важным
+pub fn evaluate_rules(rules: &[Box<dyn Rule>], result: &ScanResult, disabled: &[String]) -> Vec<Tip> {
+    let mut tips: Vec<Tip> = rules.iter()
+        .filter(|rule| !disabled.iter().any(|name| name == rule.name()))
+        .filter_map(|rule| rule.evaluate(result))
+        .collect();
+    tips.sort_by_key(|tip| std::cmp::Reverse(tip.severity));
+    tips
+}
+
+/// Советы встроенных правил, кроме отключенных
+pub fn optimization_tips(result: &ScanResult, disabled: &[String]) -> Vec<Tip> {
+    evaluate_rules(&builtin_rules(), result, disabled)
+}
+
+/// Печатает советы по освобождению места
+pub fn print_tips(tips: &[Tip]) {
+    println!("\n💡 СОВЕТЫ ПО ОПТИМИЗАЦИИ:");
+    println!("{:-<60}", "");
+
+    for tip in tips {
+        let line = format!("{} {}", tip.severity.marker(), tip.message);
+        match tip.severity {
+            Severity::Info => println!("{}", line),
+            Severity::Warning => println!("{}", color::paint(&line, Color::Yellow)),
+            Severity::Critical => println!("{}", color::paint(&line, Color::Red)),
+        }
+    }
+}
+
+/// Вычисляет и печатает советы встроенных правил, кроме отключенных
+pub fn generate_optimization_tips(result: &ScanResult, disabled: &[String]) {
+    print_tips(&optimization_tips(result, disabled));
+}
+
+// limit крупнейших директорий по убыванию размера
+fn largest_dirs(result: &ScanResult, limit: usize) -> Vec<(&str, &DirInfo)> {
+    let mut dirs: Vec<(&str, &DirInfo)> = result.dir_infos.iter().map(|(path, info)| (path.as_str(), info)).collect();
+    dirs.sort_by(|a, b| b.1.size.cmp(&a.1.size).then_with(|| a.0.cmp(b.0)));
+    dirs.truncate(limit);
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn result_with(dirs: &[(&str, DirInfo)]) -> ScanResult {
+        ScanResult {
+            dir_infos: dirs.iter().map(|(path, info)| (path.to_string(), info.clone())).collect(),
+            ..ScanResult::default()
+        }
+    }
+
+    fn sized(size: u64) -> DirInfo {
+        DirInfo { size, file_count: 1, ..DirInfo::default() }
+    }
+
+    #[test]
+    fn rules_fire_on_thresholds() {
+        let small = result_with(&[("/data", sized(GIB))]);
+        assert_eq!(LargeDirRule.evaluate(&small), None);
+        let large = result_with(&[("/data", sized(GIB + 1)), ("/var/log", sized(200 * MIB))]);
+        assert_eq!(LargeDirRule.evaluate(&large).map(|tip| tip.severity), Some(Severity::Warning));
+        assert!(LargeLogsRule.evaluate(&large).is_some());
+        assert!(LargeLogsRule.evaluate(&small).is_none());
+
+        let mut video = sized(600 * MIB);
+        video.file_types.insert("mov".to_string(), 600 * MIB);
+        assert!(LargeMediaRule.evaluate(&result_with(&[("/films", video)])).is_some());
+        assert!(DownloadsRule.evaluate(&result_with(&[("/home/Downloads", sized(1))])).is_some());
+
+        let mut root = sized(2 * GIB);
+        root.top_files = vec![(PathBuf::from("/disk.img"), 2 * GIB)];
+        let with_file = ScanResult { root, ..ScanResult::default() };
+        assert_eq!(LargeFileRule.evaluate(&with_file).map(|tip| tip.rule), Some("large-file"));
+    }
+
+    #[test]
+    fn disabled_rules_and_order() {
+        let result = result_with(&[("/data", sized(2 * GIB))]);
+        let tips = optimization_tips(&result, &[]);
+        assert_eq!(tips.iter().map(|tip| tip.rule).collect::<Vec<_>>(), ["large-dir", "compression", "system-cleanup"]);
+        let disabled = ["large-dir".to_string(), "compression".to_string()];
+        let tips = optimization_tips(&result, &disabled);
+        assert_eq!(tips.iter().map(|tip| tip.rule).collect::<Vec<_>>(), ["system-cleanup"]);
+    }
+}
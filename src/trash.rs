@@ -0,0 +1,37 @@
+//! Корзина текущего пользователя: удаленные файлы, которые все еще занимают
+//! место на диске
+
+use std::env;
+use std::path::PathBuf;
+
+/// Размер корзины, начиная с которого отчет советует ее очистить
+pub const DEFAULT_TRASH_THRESHOLD: u64 = 500 * 1024 * 1024;
+
+/// Существующие директории корзины для текущей платформы: на Linux -
+/// `$XDG_DATA_HOME/Trash/files` (по умолчанию `~/.local/share/Trash/files`),
+/// на macOS - `~/.Trash`, на Windows - `$Recycle.Bin` системного диска
+pub fn trash_dirs() -> Vec<PathBuf> {
+    candidate_dirs().into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+#[cfg(target_os = "macos")]
+fn candidate_dirs() -> Vec<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".Trash")).into_iter().collect()
+}
+
+#[cfg(windows)]
+fn candidate_dirs() -> Vec<PathBuf> {
+    let drive = env::var_os("SystemDrive").unwrap_or_else(|| "C:".into());
+    let mut root = PathBuf::from(drive);
+    root.push("\\");
+    vec![root.join("$Recycle.Bin")]
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn candidate_dirs() -> Vec<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")));
+    data_home.map(|dir| dir.join("Trash").join("files")).into_iter().collect()
+}
@@ -0,0 +1,416 @@
+//! Интерактивный режим: просмотр дерева директорий с клавиатуры.
+//!
+//! Экран рисуется ANSI-последовательностями в альтернативном буфере
+//! терминала, режим ввода без буферизации включается через `stty`.
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::format::{format_size, size_format};
+use crate::report::{percent, SortKey};
+use crate::scan::{scan_directory, ScanOptions, ScanResult};
+use crate::term::{terminal_height, terminal_width};
+
+/// Порядок переключения сортировки клавишей `s`
+const SORT_CYCLE: [SortKey; 3] = [SortKey::Size, SortKey::Name, SortKey::Count];
+
+/// Нажатая клавиша
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Esc,
+    CtrlC,
+    Char(char),
+    Unknown,
+}
+
+// Разбирает байты одного нажатия, прочитанные из терминала в raw-режиме
+fn parse_key(bytes: &[u8]) -> Key {
+    match bytes {
+        [0x1b] => Key::Esc,
+        [0x1b, b'[', b'A'] | [0x1b, b'O', b'A'] => Key::Up,
+        [0x1b, b'[', b'B'] | [0x1b, b'O', b'B'] => Key::Down,
+        [0x1b, b'[', b'C'] | [0x1b, b'O', b'C'] => Key::Right,
+        [0x1b, b'[', b'D'] | [0x1b, b'O', b'D'] => Key::Left,
+        [0x1b, b'[', b'H'] | [0x1b, b'[', b'1', b'~'] => Key::Home,
+        [0x1b, b'[', b'F'] | [0x1b, b'[', b'4', b'~'] => Key::End,
+        [0x1b, b'[', b'5', b'~'] => Key::PageUp,
+        [0x1b, b'[', b'6', b'~'] => Key::PageDown,
+        [b'\r'] | [b'\n'] => Key::Enter,
+        [0x7f] | [0x08] => Key::Backspace,
+        [0x03] => Key::CtrlC,
+        _ => match std::str::from_utf8(bytes).ok().and_then(|s| {
+            let mut chars = s.chars();
+            chars.next().filter(|_| chars.next().is_none())
+        }) {
+            Some(c) if !c.is_control() => Key::Char(c),
+            _ => Key::Unknown,
+        },
+    }
+}
+
+// Переводит терминал в raw-режим и альтернативный буфер; при уничтожении
+// (в том числе при панике) возвращает исходное состояние
+struct Screen {
+    saved_stty: String,
+}
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(Screen { saved_stty: saved.trim().to_string() })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        let _ = stty(&[self.saved_stty.as_str()]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("не удалось переключить режим терминала (stty)"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Строка списка: поддиректория или файлы, лежащие прямо в текущей директории
+struct Entry {
+    name: String,
+    path: Option<PathBuf>,
+    size: u64,
+    file_count: usize,
+}
+
+// Просмотренная директория; стек уровней позволяет вернуться без пересканирования
+struct Level {
+    path: PathBuf,
+    result: ScanResult,
+    selected: usize,
+    offset: usize,
+}
+
+struct App<'a> {
+    options: &'a ScanOptions,
+    stack: Vec<Level>,
+    sort: SortKey,
+    filter: String,
+    filtering: bool,
+    message: Option<String>,
+}
+
+/// Запускает интерактивный просмотр начиная с root.
+///
+/// Управление: стрелки - навигация, Enter - открыть директорию (с повторным
+/// сканированием), Backspace/← - назад, `s` - сортировка, `/` - фильтр по имени,
+/// `r` - пересканировать, `q` - выход.
+pub fn run(root: &Path, options: &ScanOptions) -> io::Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(io::Error::other("интерактивный режим требует терминал"));
+    }
+
+    let _screen = Screen::enter()?;
+    draw_message(&format!("Сканирование {} ...", root.display()))?;
+    let result = scan_directory(root, options)?;
+    let mut app = App {
+        options,
+        stack: vec![Level { path: root.to_path_buf(), result, selected: 0, offset: 0 }],
+        sort: SortKey::Size,
+        filter: String::new(),
+        filtering: false,
+        message: None,
+    };
+
+    let mut stdin = io::stdin();
+    let mut buf = [0u8; 32];
+    loop {
+        app.draw()?;
+        let n = stdin.read(&mut buf)?;
+        if n == 0 {
+            return Ok(());
+        }
+        if !app.handle_key(parse_key(&buf[..n]))? {
+            return Ok(());
+        }
+    }
+}
+
+fn draw_message(text: &str) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    write!(out, "\x1b[H\x1b[2J{}", text)?;
+    out.flush()
+}
+
+impl App<'_> {
+    fn level(&self) -> &Level {
+        self.stack.last().expect("стек уровней не бывает пустым")
+    }
+
+    fn level_mut(&mut self) -> &mut Level {
+        self.stack.last_mut().expect("стек уровней не бывает пустым")
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let level = self.level();
+        let filter = self.filter.to_lowercase();
+        let mut entries: Vec<Entry> = level.result.dir_infos.iter()
+            .filter(|(path, _)| Path::new(path).parent() == Some(level.path.as_path()))
+            .map(|(path, info)| Entry {
+                name: Path::new(path).file_name()
+                    .map_or_else(|| path.clone(), |n| n.to_string_lossy().into_owned()),
+                path: Some(PathBuf::from(path)),
+                size: info.size,
+                file_count: info.file_count,
+            })
+            .collect();
+
+        // Размер файлов самой директории - разница с суммой поддиректорий
+        let child_size: u64 = entries.iter().map(|e| e.size).sum();
+        let child_count: usize = entries.iter().map(|e| e.file_count).sum();
+        let root = &level.result.root;
+        if root.file_count > child_count {
+            entries.push(Entry {
+                name: "[файлы]".to_string(),
+                path: None,
+                size: root.size.saturating_sub(child_size),
+                file_count: root.file_count - child_count,
+            });
+        }
+
+        entries.retain(|e| filter.is_empty() || e.name.to_lowercase().contains(&filter));
+        entries.sort_by(|a, b| {
+            let ordering = match self.sort {
+                SortKey::Name => a.name.cmp(&b.name),
+                SortKey::Count => b.file_count.cmp(&a.file_count),
+                _ => b.size.cmp(&a.size),
+            };
+            ordering.then_with(|| a.name.cmp(&b.name))
+        });
+        entries
+    }
+
+    // Возвращает false, если нужно выйти
+    fn handle_key(&mut self, key: Key) -> io::Result<bool> {
+        self.message = None;
+        if self.filtering {
+            match key {
+                Key::Enter => self.filtering = false,
+                Key::Esc => {
+                    self.filtering = false;
+                    self.filter.clear();
+                },
+                Key::Backspace => {
+                    self.filter.pop();
+                },
+                Key::Char(c) => self.filter.push(c),
+                Key::CtrlC => return Ok(false),
+                _ => {}
+            }
+            let level = self.level_mut();
+            level.selected = 0;
+            level.offset = 0;
+            return Ok(true);
+        }
+
+        let count = self.entries().len();
+        let page = self.list_height();
+        let level = self.level_mut();
+        match key {
+            Key::Char('q') | Key::CtrlC => return Ok(false),
+            Key::Up | Key::Char('k') => level.selected = level.selected.saturating_sub(1),
+            Key::Down | Key::Char('j') => level.selected = (level.selected + 1).min(count.saturating_sub(1)),
+            Key::PageUp => level.selected = level.selected.saturating_sub(page),
+            Key::PageDown => level.selected = (level.selected + page).min(count.saturating_sub(1)),
+            Key::Home => level.selected = 0,
+            Key::End => level.selected = count.saturating_sub(1),
+            Key::Enter | Key::Right | Key::Char('l') => self.open_selected()?,
+            Key::Backspace | Key::Left | Key::Char('h') => self.go_up()?,
+            Key::Char('s') => {
+                let position = SORT_CYCLE.iter().position(|&k| k == self.sort).unwrap_or(0);
+                self.sort = SORT_CYCLE[(position + 1) % SORT_CYCLE.len()];
+            },
+            Key::Char('/') => {
+                self.filtering = true;
+                self.filter.clear();
+            },
+            Key::Char('r') => {
+                let path = self.level().path.clone();
+                if let Some(result) = self.scan(&path) {
+                    self.level_mut().result = result;
+                }
+            },
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn scan(&mut self, path: &Path) -> Option<ScanResult> {
+        if draw_message(&format!("Сканирование {} ...", path.display())).is_err() {
+            return None;
+        }
+        match scan_directory(path, self.options) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                self.message = Some(format!("Ошибка сканирования {}: {}", path.display(), e));
+                None
+            }
+        }
+    }
+
+    fn open_selected(&mut self) -> io::Result<()> {
+        let entries = self.entries();
+        let path = match entries.get(self.level().selected).and_then(|e| e.path.clone()) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(result) = self.scan(&path) {
+            self.stack.push(Level { path, result, selected: 0, offset: 0 });
+            self.filter.clear();
+        }
+        Ok(())
+    }
+
+    // Возврат к родителю: из стека, а выше начальной директории - новым сканированием
+    fn go_up(&mut self) -> io::Result<()> {
+        self.filter.clear();
+        if self.stack.len() > 1 {
+            self.stack.pop();
+            return Ok(());
+        }
+        let current = self.level().path.clone();
+        let parent = match current.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => return Ok(()),
+        };
+        if let Some(result) = self.scan(&parent) {
+            self.stack[0] = Level { path: parent, result, selected: 0, offset: 0 };
+            // Курсор остается на директории, из которой поднялись
+            if let Some(index) = self.entries().iter().position(|e| e.path.as_deref() == Some(current.as_path())) {
+                self.level_mut().selected = index;
+            }
+        }
+        Ok(())
+    }
+
+    // Строки экрана под список: без заголовка, шапки таблицы, статуса и подсказки
+    fn list_height(&self) -> usize {
+        terminal_height().saturating_sub(4).max(1)
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        let width = terminal_width();
+        let height = self.list_height();
+        let entries = self.entries();
+        let total = self.level().result.root.size;
+        let file_count = self.level().result.root.file_count;
+        let sort = self.sort;
+        let filter = self.filter.clone();
+        let filtering = self.filtering;
+        let message = self.message.clone();
+
+        let level = self.level_mut();
+        level.selected = level.selected.min(entries.len().saturating_sub(1));
+        if level.selected < level.offset {
+            level.offset = level.selected;
+        } else if level.selected >= level.offset + height {
+            level.offset = level.selected + 1 - height;
+        }
+
+        let mut frame = String::from("\x1b[H");
+        push_line(&mut frame, &format!("\x1b[1m diskspace: {}\x1b[0m", level.path.display()), width + 8);
+        push_line(&mut frame, &format!("\x1b[1m{:>12} {:>7} {:>10}  {}\x1b[0m", "РАЗМЕР", "PCT", "ФАЙЛОВ", "ИМЯ"), width + 8);
+
+        for row in 0..height {
+            let index = level.offset + row;
+            let Some(entry) = entries.get(index) else {
+                push_line(&mut frame, "", width);
+                continue;
+            };
+            let suffix = if entry.path.is_some() { "/" } else { "" };
+            let text = format!("{:>12} {:>6.1}% {:>10}  {}{}",
+                format_size(entry.size, size_format()), percent(entry.size, total), entry.file_count, entry.name, suffix);
+            let text = truncate(&text, width);
+            if index == level.selected {
+                let padding = width.saturating_sub(text.chars().count());
+                push_line(&mut frame, &format!("\x1b[7m{}{}\x1b[0m", text, " ".repeat(padding)), width + 8);
+            } else {
+                push_line(&mut frame, &text, width);
+            }
+        }
+
+        let sort_name = match sort {
+            SortKey::Name => "имя",
+            SortKey::Count => "файлы",
+            _ => "размер",
+        };
+        let mut status = format!(" Всего: {} ({} файлов) | Сортировка: {}", format_size(total, size_format()), file_count, sort_name);
+        if !filter.is_empty() {
+            status.push_str(&format!(" | Фильтр: {}", filter));
+        }
+        let status = truncate(&status, width);
+        let padding = width.saturating_sub(status.chars().count());
+        push_line(&mut frame, &format!("\x1b[7m{}{}\x1b[0m", status, " ".repeat(padding)), width + 8);
+
+        let bottom = if filtering {
+            format!("/{}", filter)
+        } else if let Some(message) = message {
+            message
+        } else {
+            "↑↓ выбор  Enter открыть  ← назад  s сортировка  / фильтр  r обновить  q выход".to_string()
+        };
+        frame.push_str(&truncate(&bottom, width));
+        frame.push_str("\x1b[K");
+
+        let mut out = io::stdout().lock();
+        out.write_all(frame.as_bytes())?;
+        out.flush()
+    }
+}
+
+// Добавляет строку кадра, обрезанную до max_chars символов, и очищает остаток строки
+fn push_line(frame: &mut String, text: &str, max_chars: usize) {
+    frame.push_str(&truncate(text, max_chars));
+    frame.push_str("\x1b[K\r\n");
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(parse_key(b"\x1b[A"), Key::Up);
+        assert_eq!(parse_key(b"\x1bOB"), Key::Down);
+        assert_eq!(parse_key(b"\x1b[6~"), Key::PageDown);
+        assert_eq!(parse_key(b"\r"), Key::Enter);
+        assert_eq!(parse_key(b"\x7f"), Key::Backspace);
+        assert_eq!(parse_key(b"q"), Key::Char('q'));
+        assert_eq!(parse_key("ж".as_bytes()), Key::Char('ж'));
+        assert_eq!(parse_key(b"\x1b[99~"), Key::Unknown);
+    }
+}
@@ -0,0 +1,64 @@
+//! Имена пользователей Unix по uid и обратно (по /etc/passwd)
+
+use std::collections::HashMap;
+use std::fs;
+
+const PASSWD: &str = "/etc/passwd";
+
+/// uid пользователя с именем name; число принимается как uid напрямую
+pub fn uid_by_name(name: &str) -> Option<u32> {
+    if let Ok(uid) = name.parse() {
+        return Some(uid);
+    }
+    let passwd = fs::read_to_string(PASSWD).ok()?;
+    let uid = parse_passwd(&passwd).find(|(user, _)| *user == name).map(|(_, uid)| uid);
+    uid
+}
+
+/// Имя пользователя с данным uid, если он есть в /etc/passwd
+pub fn name_by_uid(uid: u32) -> Option<String> {
+    let passwd = fs::read_to_string(PASSWD).ok()?;
+    let name = parse_passwd(&passwd).find(|&(_, id)| id == uid).map(|(user, _)| user.to_string());
+    name
+}
+
+/// Кэш имен пользователей: /etc/passwd читается один раз на каждый uid
+#[derive(Debug, Default)]
+pub struct UserNames {
+    names: HashMap<u32, String>,
+}
+
+impl UserNames {
+    /// Имя пользователя с данным uid; для неизвестного uid - сам uid
+    pub fn name(&mut self, uid: u32) -> &str {
+        self.names.entry(uid).or_insert_with(|| name_by_uid(uid).unwrap_or_else(|| uid.to_string()))
+    }
+}
+
+// Пары (имя, uid) из строк вида `имя:пароль:uid:gid:...`; комментарии
+// и некорректные строки пропускаются
+fn parse_passwd(passwd: &str) -> impl Iterator<Item = (&str, u32)> {
+    passwd.lines().filter(|line| !line.starts_with('#')).filter_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        let uid = fields.nth(1)?.parse().ok()?;
+        Some((name, uid))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_passwd_lines() {
+        let passwd = "# comment\nroot:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\nbroken\nbob:x:abc:0::/:/bin/sh\n";
+        let users: Vec<_> = parse_passwd(passwd).collect();
+        assert_eq!(users, vec![("root", 0), ("alice", 1000)]);
+    }
+
+    #[test]
+    fn numeric_name_is_uid() {
+        assert_eq!(uid_by_name("1234"), Some(1234));
+    }
+}